@@ -0,0 +1,349 @@
+use crate::constants::*;
+use crate::game::enemy::components::{EnemyManager, Size};
+use crate::game::enemy::systems::move_enemies;
+use crate::game::enemy::utils::spawn_enemy;
+use crate::game::map::components::FogOfWar;
+use crate::game::map::systems::{run_animations, update_game};
+use crate::game::resources::*;
+use crate::game::weapon::components::*;
+use crate::game::weapon::systems::{
+    move_bullets, move_drones, move_soldiers, run_lightning, spawn_bullets, spawn_sortie,
+    spawn_weapons,
+};
+use crate::game::AppState;
+use crate::messages::Messages;
+use bevy::app::{AppExit, ScheduleRunnerPlugin};
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsPlugin, DiagnosticsStore,
+    RegisterDiagnostic,
+};
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::utils::{HashMap, Instant};
+use bevy_kira_audio::AudioSource;
+use rand::prelude::*;
+use std::collections::HashSet;
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+/// Systems that are timed during the benchmark, in order of execution
+const SYSTEMS: [&str; 8] = [
+    "move_enemies",
+    "spawn_bullets",
+    "move_bullets",
+    "move_drones",
+    "move_soldiers",
+    "run_lightning",
+    "run_animations",
+    "update_game",
+];
+
+/// Default number of enemies spawned per enemy type
+const DEFAULT_ENEMIES: u32 = 100;
+
+/// Default number of ticks to run the benchmark
+const DEFAULT_TICKS: u32 = 600;
+
+/// Simulated duration of a single tick (60 fps)
+const TICK_DURATION: Duration = Duration::from_nanos(16_666_667);
+
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct BenchmarkSchedule(&'static str);
+
+#[derive(Resource, Clone)]
+pub struct BenchmarkSettings {
+    /// Number of enemies spawned for every enemy type
+    pub enemies: u32,
+
+    /// Overrides of the number of enemies per enemy name
+    pub overrides: HashMap<String, u32>,
+
+    /// Number of ticks to run
+    pub ticks: u32,
+}
+
+impl BenchmarkSettings {
+    /// Parse the settings from the command line arguments. Returns
+    /// None when the `--benchmark` flag is not provided.
+    ///
+    /// Usage: `--benchmark [--ticks N] [--enemies N] [--enemy NAME=N]...`
+    pub fn from_args() -> Option<Self> {
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+        if !args.iter().any(|a| a == "--benchmark") {
+            return None;
+        }
+
+        let mut settings = Self {
+            enemies: DEFAULT_ENEMIES,
+            overrides: HashMap::default(),
+            ticks: DEFAULT_TICKS,
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--ticks" => {
+                    settings.ticks = args
+                        .next()
+                        .and_then(|v| v.parse().ok())
+                        .expect("--ticks expects a positive integer.");
+                }
+                "--enemies" => {
+                    settings.enemies = args
+                        .next()
+                        .and_then(|v| v.parse().ok())
+                        .expect("--enemies expects a positive integer.");
+                }
+                "--enemy" => {
+                    let (name, n) = args
+                        .next()
+                        .and_then(|v| v.split_once('='))
+                        .and_then(|(name, n)| Some((name.to_string(), n.parse().ok()?)))
+                        .expect("--enemy expects a value of the form NAME=N.");
+                    settings.overrides.insert(name, n);
+                }
+                _ => (),
+            }
+        }
+
+        Some(settings)
+    }
+
+    /// Number of enemies to spawn of the given type
+    pub fn get(&self, name: &str) -> u32 {
+        *self.overrides.get(name).unwrap_or(&self.enemies)
+    }
+}
+
+#[derive(Resource, Default)]
+struct BenchmarkTicks(u32);
+
+fn diagnostic_path(name: &str) -> DiagnosticPath {
+    DiagnosticPath::from_components(["benchmark", name])
+}
+
+/// Run the game headless (no window nor GPU required) with all weapons on
+/// the wall and a fixed number of enemies, and report the per-system timings.
+pub fn run(settings: BenchmarkSettings) {
+    let mut app = App::new();
+
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+        AssetPlugin::default(),
+        StatesPlugin,
+        DiagnosticsPlugin,
+    ))
+    .init_asset::<Image>()
+    .init_asset::<TextureAtlasLayout>()
+    .init_asset::<AudioSource>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(TICK_DURATION))
+    .insert_resource(Messages(default()))
+    .insert_resource(settings.clone())
+    .init_state::<AppState>()
+    .init_resource::<EnemyManager>()
+    .init_resource::<WeaponManager>()
+    .init_resource::<GameSettings>()
+    .init_resource::<NightStats>()
+    .init_resource::<BenchmarkTicks>()
+    .insert_resource(benchmark_player())
+    .add_systems(
+        Startup,
+        (
+            setup_benchmark,
+            spawn_weapons,
+            reinforce_weapons,
+            deploy_sortie,
+            spawn_benchmark_enemies,
+        )
+            .chain(),
+    )
+    .add_systems(Update, run_benchmark);
+
+    app.add_systems(BenchmarkSchedule("move_enemies"), move_enemies)
+        .add_systems(BenchmarkSchedule("spawn_bullets"), spawn_bullets)
+        .add_systems(BenchmarkSchedule("move_bullets"), move_bullets)
+        .add_systems(BenchmarkSchedule("move_drones"), move_drones)
+        .add_systems(BenchmarkSchedule("move_soldiers"), move_soldiers)
+        .add_systems(BenchmarkSchedule("run_lightning"), run_lightning)
+        .add_systems(BenchmarkSchedule("run_animations"), run_animations)
+        .add_systems(BenchmarkSchedule("update_game"), update_game);
+
+    for name in SYSTEMS {
+        app.register_diagnostic(
+            Diagnostic::new(diagnostic_path(name))
+                .with_suffix("ms")
+                .with_max_history_length(settings.ticks as usize),
+        );
+    }
+
+    app.run();
+}
+
+/// Player with every weapon on the wall, all technologies and plenty of resources
+fn benchmark_player() -> Player {
+    let mut player = Player::init();
+
+    player.population.soldier = 100_000;
+    player.resources += 1e9;
+    player.technology = HashSet::from_iter(TechnologyName::iter());
+    player.fence.health = 1e9;
+    player.fence.max_health = 1e9;
+    player.fence.enabled = true;
+    player.wall.health = 1e9;
+    player.wall.max_health = 1e9;
+    player.spotlight.power = MAX_SPOTLIGHT_POWER;
+
+    let settings = WeaponSettings {
+        aaa: AirFireStrategy::All,
+        artillery: FireStrategy::Closest,
+        canon: AirFireStrategy::Grounded,
+        cryo: MAX_CRYO_POWER,
+        flamethrower: MAX_FLAMETHROWER_POWER,
+        machine_gun: MAX_MACHINE_GUN_FIRE_RATE,
+        machine_gun_strategy: FireStrategy::Closest,
+        missile_launcher: MAX_MISSILE_LAUNCHER_SHELLS,
+        missile_launcher_strategy: FireStrategy::Strongest,
+        mortar: MortarShell::Light,
+        sniper: FireStrategy::Strongest,
+        tesla: FireStrategy::Closest,
+        turret: 0.,
+    };
+
+    player.weapons.spots = WeaponName::iter()
+        .map(|w| Spot {
+            settings: settings.clone(),
+            ..Spot::new(Some(w))
+        })
+        .collect();
+    player.weapons.owned = WeaponName::iter().map(|w| (w, 1)).collect();
+    player.weapons.mines = MAX_MINES;
+    player.weapons.drones = MAX_DRONES;
+    player.constructions.drone_bay = MAX_DRONES;
+    player.weapons.explosives = ExplosivesSettings {
+        bomb: FireStrategy::Density,
+        mine: Size::Small,
+    };
+
+    player
+}
+
+fn setup_benchmark(mut commands: Commands) {
+    // The fog of war is pushed all the way up, so every enemy is visible
+    commands.spawn((
+        Transform::from_xyz(
+            -WEAPONS_PANEL_SIZE.x * 0.5,
+            SIZE.y * 0.5 - MENU_PANEL_SIZE.y - FOW_SIZE.y * 0.5 + FOW_SIZE.y,
+            FOW_Z,
+        ),
+        FogOfWar,
+    ));
+}
+
+/// Like the fence and wall, the weapons can't be destroyed during the benchmark
+fn reinforce_weapons(mut weapon_q: Query<&mut Weapon>) {
+    for mut weapon in weapon_q.iter_mut() {
+        weapon.health = 1e9;
+        weapon.max_health = 1e9;
+    }
+}
+
+/// Send out a full squad of soldiers in front of the wall
+fn deploy_sortie(
+    mut commands: Commands,
+    player: Res<Player>,
+    weapons: Res<WeaponManager>,
+    asset_server: Res<AssetServer>,
+) {
+    spawn_sortie(&mut commands, MAX_SORTIE, &player, &weapons, &asset_server);
+}
+
+fn spawn_benchmark_enemies(
+    mut commands: Commands,
+    enemies: Res<EnemyManager>,
+    settings: Res<BenchmarkSettings>,
+    asset_server: Res<AssetServer>,
+) {
+    for enemy in enemies.list.iter() {
+        for _ in 0..settings.get(enemy.name) {
+            let x = thread_rng().gen_range(
+                (-SIZE.x + enemy.dim.x) * 0.5..=(SIZE.x - enemy.dim.x) * 0.5 - WEAPONS_PANEL_SIZE.x,
+            );
+            let y = thread_rng().gen_range(
+                -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + 2. * WALL_SIZE.y..=SIZE.y * 0.5,
+            );
+
+            spawn_enemy(
+                &mut commands,
+                enemy,
+                Vec3::new(x, y, ENEMY_Z),
+                &asset_server,
+            );
+        }
+    }
+}
+
+/// Run every benchmarked system once, measure its duration and
+/// report the results after the last tick
+fn run_benchmark(world: &mut World) {
+    for name in SYSTEMS {
+        let start = Instant::now();
+        world.run_schedule(BenchmarkSchedule(name));
+        let elapsed = start.elapsed();
+
+        if let Some(diagnostic) = world
+            .resource_mut::<DiagnosticsStore>()
+            .get_mut(&diagnostic_path(name))
+        {
+            diagnostic.add_measurement(DiagnosticMeasurement {
+                time: Instant::now(),
+                value: elapsed.as_secs_f64() * 1000.,
+            });
+        }
+    }
+
+    let ticks = {
+        let mut ticks = world.resource_mut::<BenchmarkTicks>();
+        ticks.0 += 1;
+        ticks.0
+    };
+
+    if ticks >= world.resource::<BenchmarkSettings>().ticks {
+        report(world, ticks);
+        world.send_event(AppExit::Success);
+    }
+}
+
+fn report(world: &World, ticks: u32) {
+    let store = world.resource::<DiagnosticsStore>();
+
+    println!("Benchmark finished after {} ticks.", ticks);
+    println!("Remaining entities: {}", world.entities().len());
+    println!(
+        "Critical hits: {}",
+        world.resource::<NightStats>().critical_hits
+    );
+    println!();
+    println!(
+        "{:<16}{:>12}{:>12}{:>12}{:>12}",
+        "system", "mean (ms)", "min (ms)", "max (ms)", "total (ms)"
+    );
+
+    for name in SYSTEMS {
+        if let Some(diagnostic) = store.get(&diagnostic_path(name)) {
+            let values = diagnostic.values().copied().collect::<Vec<_>>();
+            let min = values.iter().copied().fold(f64::MAX, f64::min);
+            let max = values.iter().copied().fold(0., f64::max);
+
+            println!(
+                "{:<16}{:>12.3}{:>12.3}{:>12.3}{:>12.3}",
+                name,
+                diagnostic.average().unwrap_or_default(),
+                min,
+                max,
+                values.iter().sum::<f64>(),
+            );
+        }
+    }
+}
@@ -1,11 +1,13 @@
 use crate::game::enemy::components::Enemy;
-use crate::game::weapon::components::{Bullet, Weapon};
+use crate::game::weapon::components::{Bullet, Shadow, Weapon, WeaponBar};
 use bevy::math::Vec2;
 use bevy::prelude::*;
 
 pub type BulletQ<'a> = (Entity, &'a Transform, &'a Bullet);
 pub type EnemyQ<'a> = (Entity, &'a Transform, &'a Enemy);
+pub type ShadowQ<'a> = (Entity, &'a mut Transform, &'a Shadow);
 pub type SpriteQ<'a> = (Entity, &'a Transform, &'a Sprite);
+pub type WeaponBarQ<'a> = (&'a mut Transform, &'a WeaponBar);
 pub type WeaponQ<'a> = (Entity, &'a Transform, &'a Weapon);
 
 pub const TITLE: &str = "Fortress";
@@ -34,6 +36,8 @@ pub const GAME_SPEED_STEP: f32 = 0.5;
 
 // Map settings
 pub const STRUCTURE_OFFSET: f32 = 5.; // Distance of structure to stop enemy movement
+pub const WALL_Y: f32 = -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + WALL_SIZE.y * 0.5; // Center of the wall
+pub const FENCE_Y: f32 = -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + WALL_SIZE.y * 1.4; // Center of the fence
 pub const MAP_Z: f32 = 0.0;
 pub const STRUCTURE_Z: f32 = 1.0;
 pub const BULLET_Z: f32 = 2.0;
@@ -52,19 +56,78 @@ pub const BETA: f32 = 5.; // Probability factor decrease for above-level enemies
 pub const POPULATION_MEAN_INCREASE: u32 = 100;
 pub const POPULATION_STD_INCREASE: u32 = 10;
 pub const SOLDIER_BASE_DAMAGE: u32 = 3;
+pub const TRAINING_COST: f32 = 20.; // Bullets to train a recruit into a soldier
 pub const RESOURCE_FACTOR: f32 = 3.; // Factor to multiply population by to get resources
+pub const DAY_EVENT_PROB: f32 = 0.5; // Probability of an event at the start of a day
+pub const FOOD_CONSUMPTION: f32 = 0.5; // Food eaten per person per day
+pub const MIGRATED_FOOD_DAYS: f32 = 3.; // Days of food given to saves from before food existed
+pub const STARVATION_RATE: f32 = 0.2; // Fraction of the people without food that starve
+pub const HOUSING_CAPACITY: u32 = 500; // Population housed per level of housing
+pub const WOUNDED_PROB: f32 = 0.4; // Probability that a casualty is wounded instead of killed
+pub const HOSPITAL_CAPACITY: u32 = 20; // Wounded treated per level of the hospital
+pub const HOSPITAL_RECOVERY: f32 = 0.2; // Fraction of treated wounded that recover per level
+pub const WOUNDED_DEATH_RATE: f32 = 0.1; // Fraction of untreated wounded that die per day
+
+// Morale settings
+pub const MAX_MORALE: f32 = 100.;
+pub const INITIAL_MORALE: f32 = 60.;
+pub const MORALE_EFFECT: f32 = 0.25; // Maximum relative change of productivity and soldier damage
+pub const MORALE_HISTORY: u32 = 3; // Number of days the causes of morale changes are remembered
+pub const MORALE_PER_KILL: f32 = 0.05;
+pub const MORALE_QUIET_NIGHT: f32 = 5.; // Morale gained by a night without casualties
+pub const MORALE_BREACH: f32 = 2.; // Morale lost per bug entering the fortress
+pub const MORALE_EXPEDITION_LOST: f32 = 10.;
+pub const MORALE_SHORTAGE: f32 = 5.; // Morale lost per resource that ran out
+pub const MORALE_STARVATION: f32 = 15.;
+pub const DESERTION_MORALE: f32 = 20.; // Below this morale, part of the population deserts
+pub const DESERTION_RATE: f32 = 0.1; // Fraction of the population that deserts at zero morale
+
+// Market settings
+pub const MARKET_SPREAD: f32 = 0.1; // Fraction of the value lost on every trade
+pub const MARKET_VOLATILITY: f32 = 0.08; // Standard deviation of the daily price change
+pub const MARKET_REVERSION: f32 = 0.2; // Fraction of the distance to the base price recovered per day
+pub const MARKET_DEPTH: f32 = 3000.; // Traded value (in materials) that changes a price by a factor e
+pub const MARKET_SUPPLY: f32 = 0.1; // Fraction of the fortress' net production that reaches the market
+pub const MARKET_HISTORY: usize = 20; // Number of days shown in the rate history
+pub const NIGHT_MARKUP: f32 = 0.5; // Extra cost of emergency trades during the night
+
+// Expedition settings
+pub const MAX_ESCORT_FACTOR: f32 = 2.; // Maximum escort size relative to the recommended one
+pub const SOLDIER_PROTECTION: f32 = 0.01; // Return probability increase per soldier
+pub const MAX_RETURN_PROB: f32 = 0.95;
+pub const EVENT_PROB: f32 = 0.4; // Probability per day of an event on an expedition
 
 // Weapon settings
 pub const MAX_SPOTS: u32 = 8;
 pub const MAX_UPGRADE_LEVEL: u32 = 10;
+pub const SPECIALIZATION_LEVEL: u32 = 8; // Sum of upgrade levels to unlock a specialization
+pub const MASTERY_LEVEL: u32 = 14; // Sum of upgrade levels to unlock the node following a specialization
 pub const MAX_MACHINE_GUN_FIRE_RATE: u32 = 5;
 pub const MAX_FLAMETHROWER_POWER: u32 = 5;
+pub const MAX_CRYO_POWER: u32 = 5;
+pub const MAX_SLOW: f32 = 0.7; // Maximum fraction of speed a frozen enemy loses
+pub const FROZEN_FIRE_MULTIPLIER: f32 = 2.; // Extra fire damage on frozen enemies
 pub const DEFAULT_TURRET_POWER_TIME: f32 = 10.; // Seconds to power-up the turret
 pub const MAX_TURRET_POWER: f32 = 100.;
 pub const TURRET_POWER_COST: f32 = 10.; // Gasoline cost per second
+pub const LIGHTNING_DURATION: f32 = 0.2; // Seconds the tesla's arcs are visible
+pub const LIGHTNING_SEGMENTS: u32 = 6; // Number of segments of a drawn arc
+pub const MAX_DRONES: u32 = 4; // Maximum level of the drone bay
+pub const MAX_SORTIE: u32 = 20; // Maximum number of soldiers sent on a sortie
+pub const DRONE_PATROL_BAND: f32 = 0.15; // Height of the patrolled band as fraction of the map
+pub const BALLISTIC_MIN_FLIGHT_TIME: f32 = 0.5; // Seconds a ballistic shell is at least in the air
+pub const BALLISTIC_ARC: f32 = 0.3; // Height of the arc's apex as fraction of the distance
+pub const BALLISTIC_APEX_SCALE: f32 = 0.6; // Extra scale of a ballistic shell at the apex
+pub const SNIPER_FOG_VISION: f32 = 0.45; // Fraction of the fog the sniper sees through
+pub const STRUCTURE_WEAPON_DAMAGE: f32 = 0.25; // Fraction of the damage to a structure that hits the weapons on it
+pub const WEAPON_REPAIR_COST: f32 = 0.5; // Materials per health point to repair a weapon
+pub const RANK_KILLS: [u32; 5] = [10, 25, 50, 100, 200]; // Kills needed to reach every rank
+pub const RANK_BONUS: f32 = 0.05; // Extra rotation speed, damage and fire rate per rank
 
 pub const MAX_MISSILE_LAUNCHER_SHELLS: u32 = 10;
 pub const MAX_SPOTLIGHT_POWER: u32 = 100;
 pub const MAX_MINES: u32 = 25;
+pub const MINE_SPACING: f32 = 1.5; // Minimum distance between mines, relative to their size
+pub const MINE_ATTEMPTS: u32 = 1000; // Tries to scatter a mine at random before giving up
 pub const MAX_BOMBS: u32 = 5;
 pub const MAX_NUKES: u32 = 1;
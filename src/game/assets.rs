@@ -21,7 +21,7 @@ impl WorldAssets {
     pub fn get_image(&self, name: &str) -> Handle<Image> {
         self.images
             .get(name)
-            .expect(&format!("No entry for {}", name))
+            .unwrap_or_else(|| panic!("No entry for {}", name))
             .clone_weak()
     }
 
@@ -46,7 +46,9 @@ impl FromWorld for WorldAssets {
             ("refiner", assets.load("icons/refiner.png")),
             ("constructor", assets.load("icons/constructor.png")),
             ("scientist", assets.load("icons/scientist.png")),
+            ("farmer", assets.load("icons/farmer.png")),
             ("idle", assets.load("icons/idle.png")),
+            ("wounded", assets.load("icons/wounded.png")),
             ("population", assets.load("icons/population.png")),
             ("wall", assets.load("icons/wall.png")),
             ("fence", assets.load("icons/fence.png")),
@@ -62,6 +64,8 @@ impl FromWorld for WorldAssets {
             ("gasoline", assets.load("icons/gasoline.png")),
             ("materials", assets.load("icons/materials.png")),
             ("technology", assets.load("icons/technology.png")),
+            ("food", assets.load("icons/food.png")),
+            ("morale", assets.load("icons/heart.png")),
             ("up-arrow", assets.load("icons/up-arrow.png")),
             ("repair", assets.load("icons/repair.png")),
             ("spots", assets.load("icons/spots.png")),
@@ -75,14 +79,19 @@ impl FromWorld for WorldAssets {
             ("cross", assets.load("icons/cross.png")),
             ("hourglass", assets.load("icons/hourglass.png")),
             ("clock", assets.load("icons/clock.png")),
+            ("chevron", assets.load("icons/chevron.png")),
             // Weapons
             ("aaa", assets.load("weapon/aaa.png")),
             ("artillery", assets.load("weapon/artillery.png")),
             ("canon", assets.load("weapon/canon.png")),
+            ("cryo", assets.load("weapon/cryo.png")),
+            ("drone", assets.load("weapon/drone.png")),
             ("flamethrower", assets.load("weapon/flamethrower.png")),
             ("machine-gun", assets.load("weapon/machine-gun.png")),
             ("mortar", assets.load("weapon/mortar.png")),
             ("ml", assets.load("weapon/missile-launcher.png")),
+            ("sniper", assets.load("weapon/sniper.png")),
+            ("tesla", assets.load("weapon/tesla.png")),
             ("turret", assets.load("weapon/turret.png")),
             ("mine-shop", assets.load("weapon/mine.png")),
             ("bomb-shop", assets.load("weapon/bomb.png")),
@@ -113,6 +122,7 @@ impl FromWorld for WorldAssets {
             // Sprite sheets
             ("flashes", assets.load("weapon/flashes.png")),
             ("flame", assets.load("weapon/flame.png")),
+            ("frost", assets.load("weapon/frost.png")),
             ("explosion1", assets.load("weapon/explosion1.png")),
             ("explosion2", assets.load("weapon/explosion2.png")),
             ("explosion3", assets.load("weapon/explosion3.png")),
@@ -121,6 +131,8 @@ impl FromWorld for WorldAssets {
             ("refinery", assets.load("map/refinery.png")),
             ("factory", assets.load("map/factory.png")),
             ("laboratory", assets.load("map/laboratory.png")),
+            ("housing", assets.load("map/housing.png")),
+            ("hospital", assets.load("map/hospital.png")),
             ("wall-shop", assets.load("map/wall-shop.png")),
             ("fence-shop", assets.load("map/fence-shop.png")),
             ("game_over", assets.load("map/game-over.png")),
@@ -137,6 +149,7 @@ impl FromWorld for WorldAssets {
         let cone_flash = TextureAtlasLayout::from_grid(UVec2::new(107, 105), 4, 6, None, None);
         let wide_flash = TextureAtlasLayout::from_grid(UVec2::new(97, 150), 4, 4, None, None);
         let flame = TextureAtlasLayout::from_grid(UVec2::new(124, 50), 1, 12, None, None);
+        let frost = TextureAtlasLayout::from_grid(UVec2::new(124, 50), 1, 12, None, None);
         let explosion1 = TextureAtlasLayout::from_grid(UVec2::new(256, 256), 8, 6, None, None);
         let explosion2 = TextureAtlasLayout::from_grid(UVec2::new(257, 251), 8, 6, None, None);
         let explosion3 = TextureAtlasLayout::from_grid(UVec2::new(181, 205), 4, 4, None, None);
@@ -197,6 +210,17 @@ impl FromWorld for WorldAssets {
                     last_index: 12,
                 },
             ),
+            (
+                "frost",
+                AtlasInfo {
+                    image: images["frost"].clone_weak(),
+                    texture: TextureAtlas {
+                        layout: texture.add(frost),
+                        index: 1,
+                    },
+                    last_index: 12,
+                },
+            ),
             (
                 "explosion1",
                 AtlasInfo {
@@ -3,6 +3,7 @@ use bevy::prelude::*;
 use bevy::prelude::{Resource, Vec2};
 use rand::distributions::{Distribution, WeightedIndex};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Component)]
 pub struct EnemyHealth;
@@ -25,11 +26,26 @@ pub struct Enemy {
     pub size: Size,
     pub armor: f32,
     pub speed: f32,
+    /// Fraction of the speed lost by being frozen
+    pub slow: f32,
+    /// Seconds until the enemy thaws
+    pub slow_timer: f32,
+    /// Spot of the weapon that dealt the last hit (credited with the kill)
+    pub last_hit: Option<Uuid>,
+    /// Whether the sprite is tinted as frozen
+    pub frozen: bool,
     pub flies: bool,
     pub damage: f32,
     pub strength: f32,
 }
 
+impl Enemy {
+    /// Speed after applying the slow
+    pub fn current_speed(&self) -> f32 {
+        self.speed * (1. - self.slow)
+    }
+}
+
 #[derive(Resource)]
 pub struct EnemyManager {
     pub list: Vec<Enemy>,
@@ -69,6 +85,25 @@ impl EnemyManager {
 
 impl Default for EnemyManager {
     fn default() -> Self {
+        // Runtime state every enemy starts with
+        let base = Enemy {
+            name: "",
+            image: "",
+            max_health: 0.,
+            health: 0.,
+            dim: Vec2::ZERO,
+            size: Size::Medium,
+            armor: 0.,
+            speed: 0.,
+            slow: 0.,
+            slow_timer: 0.,
+            last_hit: None,
+            frozen: false,
+            flies: false,
+            damage: 0.,
+            strength: 0.,
+        };
+
         let enemies = vec![
             Enemy {
                 name: "Dartling",
@@ -82,6 +117,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 10.,
                 strength: 1.,
+                ..base
             },
             Enemy {
                 name: "Skitterling",
@@ -95,6 +131,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 5.,
                 strength: 1.,
+                ..base
             },
             Enemy {
                 name: "Shellback",
@@ -108,6 +145,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 20.,
                 strength: 2.,
+                ..base
             },
             Enemy {
                 name: "Grub",
@@ -121,6 +159,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 20.,
                 strength: 3.,
+                ..base
             },
             Enemy {
                 name: "Quickstrike",
@@ -134,6 +173,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 25.,
                 strength: 4.,
+                ..base
             },
             Enemy {
                 name: "Chiton",
@@ -147,6 +187,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 15.,
                 strength: 5.,
+                ..base
             },
             Enemy {
                 name: "Thornbiter",
@@ -160,6 +201,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 30.,
                 strength: 6.,
+                ..base
             },
             Enemy {
                 name: "Needler",
@@ -173,6 +215,7 @@ impl Default for EnemyManager {
                 flies: true,
                 damage: 30.,
                 strength: 7.,
+                ..base
             },
             Enemy {
                 name: "Blightcraw",
@@ -186,6 +229,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 55.,
                 strength: 8.,
+                ..base
             },
             Enemy {
                 name: "Shellfist",
@@ -199,6 +243,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 120.,
                 strength: 9.,
+                ..base
             },
             Enemy {
                 name: "Shellwarden",
@@ -212,6 +257,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 140.,
                 strength: 10.,
+                ..base
             },
             Enemy {
                 name: "Hiveborn",
@@ -225,6 +271,7 @@ impl Default for EnemyManager {
                 flies: true,
                 damage: 45.,
                 strength: 11.,
+                ..base
             },
             Enemy {
                 name: "Hornet",
@@ -238,6 +285,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 65.,
                 strength: 12.,
+                ..base
             },
             Enemy {
                 name: "Crawler",
@@ -251,6 +299,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 75.,
                 strength: 13.,
+                ..base
             },
             Enemy {
                 name: "Breaker",
@@ -264,6 +313,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 155.,
                 strength: 14.,
+                ..base
             },
             Enemy {
                 name: "Weevil",
@@ -277,6 +327,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 145.,
                 strength: 15.,
+                ..base
             },
             Enemy {
                 name: "Mothroach",
@@ -290,6 +341,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 175.,
                 strength: 16.,
+                ..base
             },
             Enemy {
                 name: "Dartmite",
@@ -303,6 +355,7 @@ impl Default for EnemyManager {
                 flies: true,
                 damage: 95.,
                 strength: 17.,
+                ..base
             },
             Enemy {
                 name: "Nestling",
@@ -316,6 +369,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 255.,
                 strength: 18.,
+                ..base
             },
             Enemy {
                 name: "Gargantula",
@@ -329,6 +383,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 360.,
                 strength: 19.,
+                ..base
             },
             Enemy {
                 name: "Ironclaw",
@@ -342,6 +397,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 250.,
                 strength: 20.,
+                ..base
             },
             Enemy {
                 name: "Reaper",
@@ -355,6 +411,7 @@ impl Default for EnemyManager {
                 flies: false,
                 damage: 580.,
                 strength: 21.,
+                ..base
             },
         ];
 
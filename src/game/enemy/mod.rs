@@ -1,5 +1,5 @@
 pub mod components;
-mod systems;
+pub mod systems;
 pub mod utils;
 
 use super::{AppState, GameState};
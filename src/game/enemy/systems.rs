@@ -1,18 +1,20 @@
 use super::components::*;
-use crate::constants::{SpriteQ, ENEMY_Z, RESOURCES_PANEL_SIZE, SIZE, WEAPONS_PANEL_SIZE};
-use crate::game::resources::{EnemyStatus, GameSettings, NightStats, Player};
-use crate::game::weapon::components::{FenceComponent, WallComponent};
+use crate::constants::{
+    SpriteQ, ENEMY_Z, MORALE_BREACH, RESOURCES_PANEL_SIZE, SIZE, STRUCTURE_WEAPON_DAMAGE,
+    WEAPONS_PANEL_SIZE, WOUNDED_PROB,
+};
+use crate::game::enemy::utils::spawn_enemy;
+use crate::game::map::utils::collision;
+use crate::game::resources::{EnemyStatus, GameSettings, MoraleCause, NightStats, Player};
+use crate::game::weapon::components::{FenceComponent, Mount, Soldier, WallComponent, Weapon};
 use crate::game::weapon::utils::get_structure_top;
 use crate::game::AppState;
 use crate::messages::Messages;
-use crate::utils::scale_duration;
-use bevy::color::{
-    palettes::basic::{BLACK, LIME},
-    Color,
-};
+use crate::utils::{scale_duration, NameFromEnum};
 use bevy::prelude::*;
 use rand::prelude::*;
 
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_enemies(
     mut commands: Commands,
     enemy_q: Query<&Enemy>,
@@ -48,41 +50,12 @@ pub fn spawn_enemies(
                 (-SIZE.x + enemy.dim.x) * 0.5..=(SIZE.x - enemy.dim.x) * 0.5 - WEAPONS_PANEL_SIZE.x,
             );
 
-            commands
-                .spawn((
-                    Sprite {
-                        image: asset_server.load(enemy.image),
-                        custom_size: Some(enemy.dim),
-                        ..default()
-                    },
-                    Transform::from_xyz(x, SIZE.y * 0.5, ENEMY_Z),
-                    enemy.clone(),
-                ))
-                .with_children(|parent| {
-                    parent
-                        .spawn((
-                            Sprite {
-                                color: Color::from(BLACK),
-                                custom_size: Some(Vec2::new(enemy.dim.x * 0.8, enemy.dim.y * 0.1)),
-                                ..default()
-                            },
-                            Transform::from_xyz(0., enemy.dim.y * 0.5 - 5.0, 0.1),
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn((
-                                Sprite {
-                                    color: Color::from(LIME),
-                                    custom_size: Some(Vec2::new(
-                                        enemy.dim.x * 0.78,
-                                        enemy.dim.y * 0.08,
-                                    )),
-                                    ..default()
-                                },
-                                Transform::from_xyz(0., 0., 0.2),
-                                EnemyHealth,
-                            ));
-                        });
-                });
+            spawn_enemy(
+                &mut commands,
+                enemy,
+                Vec3::new(x, SIZE.y * 0.5, ENEMY_Z),
+                &asset_server,
+            );
 
             night_stats
                 .enemies
@@ -96,10 +69,13 @@ pub fn spawn_enemies(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn move_enemies(
-    mut enemy_q: Query<(&mut Transform, &mut Enemy)>,
+    mut enemy_q: Query<(&mut Transform, &mut Sprite, &mut Enemy)>,
     fence_q: Query<SpriteQ, (With<FenceComponent>, Without<Enemy>)>,
     wall_q: Query<SpriteQ, (With<WallComponent>, Without<Enemy>)>,
+    mut weapon_q: Query<(&Transform, &mut Weapon), Without<Enemy>>,
+    soldier_q: Query<&Soldier>,
     mut next_state: ResMut<NextState<AppState>>,
     mut player: ResMut<Player>,
     mut night_stats: ResMut<NightStats>,
@@ -107,11 +83,33 @@ pub fn move_enemies(
     game_settings: Res<GameSettings>,
     time: Res<Time>,
 ) {
-    for (mut enemy_t, mut enemy) in enemy_q.iter_mut() {
+    for (mut enemy_t, mut enemy_s, mut enemy) in enemy_q.iter_mut() {
+        // Frozen enemies thaw over time
+        if enemy.slow_timer > 0. {
+            enemy.slow_timer -= game_settings.speed * time.delta_secs();
+            if enemy.slow_timer <= 0. {
+                enemy.slow = 0.;
+                enemy.slow_timer = 0.;
+            }
+        }
+
+        // Only tint the sprite when the enemy freezes or thaws
+        let frozen = enemy.slow > 0.;
+        if frozen != enemy.frozen {
+            enemy.frozen = frozen;
+            enemy_s.color = if frozen {
+                Color::srgb(0.6, 0.8, 1.)
+            } else {
+                Color::WHITE
+            };
+        }
+
         let mut new_pos =
-            enemy_t.translation.y - enemy.speed * game_settings.speed * time.delta_secs();
+            enemy_t.translation.y - enemy.current_speed() * game_settings.speed * time.delta_secs();
 
         if !enemy.flies {
+            let mut attacked = None;
+
             if let Some(fence_y) = get_structure_top(fence_q.get_single()) {
                 if new_pos < fence_y {
                     new_pos = fence_y;
@@ -121,58 +119,94 @@ pub fn move_enemies(
                         enemy.health -=
                             (player.fence.damage * game_settings.speed * time.delta_secs())
                                 .min(enemy.health);
+                        enemy.last_hit = None;
                     }
 
-                    player.fence.health -= (enemy.damage * game_settings.speed * time.delta_secs())
-                        .min(player.fence.health);
+                    let damage = enemy.damage * game_settings.speed * time.delta_secs();
+                    player.fence.health -= damage.min(player.fence.health);
+                    attacked = Some((Mount::Fence, damage));
                 }
             } else if let Some(wall_y) = get_structure_top(wall_q.get_single()) {
                 if new_pos < wall_y {
                     new_pos = wall_y;
 
-                    player.wall.health -= (enemy.damage * game_settings.speed * time.delta_secs())
-                        .min(player.wall.health);
+                    let damage = enemy.damage * game_settings.speed * time.delta_secs();
+                    player.wall.health -= damage.min(player.wall.health);
+                    attacked = Some((Mount::Wall, damage));
+                }
+            }
+
+            // Part of the damage hits the weapons on the attacked section of the structure
+            if let Some((mount, damage)) = attacked {
+                for (_, mut weapon) in weapon_q.iter_mut().filter(|(t, w)| {
+                    w.mount == mount
+                        && (t.translation.x - enemy_t.translation.x).abs()
+                            < (w.dim.x + enemy.dim.x) * 0.5
+                }) {
+                    if weapon.take_damage(damage * STRUCTURE_WEAPON_DAMAGE) {
+                        messages.error(format!("A {} was destroyed", weapon.name.name()));
+                    }
+                }
+            }
+        } else {
+            // Flying enemies dive at the weapons they fly over
+            for (_, mut weapon) in weapon_q.iter_mut().filter(|(t, w)| {
+                collision(&t.translation, &w.dim, &enemy_t.translation, &enemy.dim)
+            }) {
+                if weapon.take_damage(enemy.damage * game_settings.speed * time.delta_secs()) {
+                    messages.error(format!("A {} was destroyed", weapon.name.name()));
                 }
             }
         }
         if new_pos < -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y - enemy.dim.y * 0.5 {
             messages.error("A bug entered the fortress");
 
+            let day = player.day;
+            player.morale.add(day, MoraleCause::Breach, -MORALE_BREACH);
+
             enemy.health = 0.; // Is despawned in update_game
+            enemy.last_hit = None; // Not a kill
             let mut damage = enemy.damage as u32;
+            let soldier_damage = player.get_soldier_damage();
+            let deployed = soldier_q.iter().count() as u32;
+            let population = &mut player.population;
+            let casualties = &mut night_stats.population;
+
+            // First subtract damage from the soldiers that aren't on a sortie
+            while population.soldier > deployed && damage > 0 {
+                population.soldier -= 1;
+                if random::<f32>() < WOUNDED_PROB {
+                    population.wounded += 1;
+                    casualties.wounded += 1;
+                } else {
+                    casualties.soldier += 1;
+                }
 
-            // First subtract damage from the soldiers
-            while player.population.soldier > 0 && damage > 0 {
-                player.population.soldier -= 1;
-                night_stats.population.soldier += 1;
-
-                damage -= player.get_soldier_damage().min(damage);
+                damage -= soldier_damage.min(damage);
             }
 
-            while damage > 0 && player.population.total() > 0 {
+            while damage > 0 && population.available() > population.soldier {
                 // Then randomly over the rest of the population
-                match thread_rng().gen_range(0..=3) {
-                    0 if player.population.armorer > 0 => {
-                        player.population.armorer -= 1;
-                        night_stats.population.armorer += 1;
-                        damage -= 1;
-                    }
-                    1 if player.population.refiner > 0 => {
-                        player.population.refiner -= 1;
-                        night_stats.population.refiner += 1;
-                        damage -= 1;
+                let (job, killed) = match thread_rng().gen_range(0..=6) {
+                    0 => (&mut population.armorer, &mut casualties.armorer),
+                    1 => (&mut population.refiner, &mut casualties.refiner),
+                    2 => (&mut population.constructor, &mut casualties.constructor),
+                    3 => (&mut population.scientist, &mut casualties.scientist),
+                    4 => (&mut population.farmer, &mut casualties.farmer),
+                    5 => (&mut population.idle, &mut casualties.idle),
+                    _ => (&mut population.recruit, &mut casualties.soldier),
+                };
+
+                if *job > 0 {
+                    *job -= 1;
+                    if random::<f32>() < WOUNDED_PROB {
+                        population.wounded += 1;
+                        casualties.wounded += 1;
+                    } else {
+                        *killed += 1;
                     }
-                    2 if player.population.constructor > 0 => {
-                        player.population.constructor -= 1;
-                        night_stats.population.constructor += 1;
-                        damage -= 1;
-                    }
-                    3 if player.population.scientist > 0 => {
-                        player.population.scientist -= 1;
-                        night_stats.population.scientist += 1;
-                        damage -= 1;
-                    }
-                    _ => (),
+
+                    damage -= 1;
                 }
             }
 
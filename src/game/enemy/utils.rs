@@ -1,20 +1,66 @@
 use crate::constants::SpriteQ;
+use crate::game::enemy::components::{Enemy, EnemyHealth};
 use crate::game::weapon::utils::get_structure_top;
+use bevy::color::{
+    palettes::basic::{BLACK, LIME},
+    Color,
+};
 use bevy::ecs::query::QuerySingleError;
 use bevy::math::Vec3;
+use bevy::prelude::*;
 
-/// Calculate the future position of an enemy relative to a bullet.
+/// Spawn an enemy with its health bar at the given position
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    enemy: &Enemy,
+    translation: Vec3,
+    asset_server: &AssetServer,
+) {
+    commands
+        .spawn((
+            Sprite {
+                image: asset_server.load(enemy.image),
+                custom_size: Some(enemy.dim),
+                ..default()
+            },
+            Transform::from_translation(translation),
+            *enemy,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Sprite {
+                        color: Color::from(BLACK),
+                        custom_size: Some(Vec2::new(enemy.dim.x * 0.8, enemy.dim.y * 0.1)),
+                        ..default()
+                    },
+                    Transform::from_xyz(0., enemy.dim.y * 0.5 - 5.0, 0.1),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Sprite {
+                            color: Color::from(LIME),
+                            custom_size: Some(Vec2::new(enemy.dim.x * 0.78, enemy.dim.y * 0.08)),
+                            ..default()
+                        },
+                        Transform::from_xyz(0., 0., 0.2),
+                        EnemyHealth,
+                    ));
+                });
+        });
+}
+
+/// Calculate the future position of an enemy after the bullet's `flight_time`.
 pub fn get_future_position(
     enemy_t: Vec3,
     enemy_speed: f32,
-    bullet_t: Vec3,
-    bullet_speed: f32,
+    flight_time: f32,
     fence_q: Result<SpriteQ, QuerySingleError>,
     wall_q: Result<SpriteQ, QuerySingleError>,
 ) -> Vec3 {
     // No need to take game speed into account since
     // the effect cancels out on enemy and bullet speed
-    let yt = enemy_speed * enemy_t.distance(bullet_t) / bullet_speed;
+    let yt = enemy_speed * flight_time;
     let mut future_t = enemy_t - Vec3::new(0., yt, 0.);
 
     // If there's a structure, stop movement there
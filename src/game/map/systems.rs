@@ -3,10 +3,12 @@ use crate::constants::*;
 use crate::game::assets::WorldAssets;
 use crate::game::enemy::components::{Enemy, EnemyHealth, EnemyManager, Size};
 use crate::game::enemy::utils::get_future_position;
-use crate::game::map::utils::{collision, is_visible, toggle, CustomUi};
+use crate::game::map::utils::{collision, is_visible, resource_color, toggle, CustomUi};
 use crate::game::resources::*;
 use crate::game::weapon::components::*;
-use crate::game::weapon::systems::{spawn_fence, spawn_spots, spawn_wall};
+use crate::game::weapon::systems::{
+    spawn_airstrike, spawn_fence, spawn_sortie, spawn_spots, spawn_wall,
+};
 use crate::game::{AppState, AudioState, GameState};
 use crate::messages::Messages;
 use crate::utils::*;
@@ -19,9 +21,7 @@ use bevy_egui::egui::{
 };
 use bevy_egui::{egui, EguiContexts};
 use egui_dnd::dnd;
-use std::f32::consts::PI;
 use strum::IntoEnumIterator;
-use uuid::Uuid;
 
 pub fn set_style(mut contexts: EguiContexts) {
     let context = contexts.ctx_mut();
@@ -107,6 +107,7 @@ pub fn draw_map(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn menu_panel(
     mut commands: Commands,
     mut contexts: EguiContexts,
@@ -183,6 +184,7 @@ pub fn menu_panel(
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn resources_panel(
     mut contexts: EguiContexts,
     app_state: Res<State<AppState>>,
@@ -197,12 +199,14 @@ pub fn resources_panel(
     let night_texture = contexts.add_image(assets.get_image("night"));
     let soldier_texture = contexts.add_image(assets.get_image("soldier"));
     let population_texture = contexts.add_image(assets.get_image("population"));
+    let morale_texture = contexts.add_image(assets.get_image("morale"));
     let wall_texture = contexts.add_image(assets.get_image("wall"));
     let fence_texture = contexts.add_image(assets.get_image("fence"));
     let bullets_texture = contexts.add_image(assets.get_image("bullets"));
     let gasoline_texture = contexts.add_image(assets.get_image("gasoline"));
     let materials_texture = contexts.add_image(assets.get_image("materials"));
     let technology_texture = contexts.add_image(assets.get_image("technology"));
+    let food_texture = contexts.add_image(assets.get_image("food"));
     let hourglass_texture = contexts.add_image(assets.get_image("hourglass"));
     let clock_texture = contexts.add_image(assets.get_image("clock"));
 
@@ -232,6 +236,36 @@ pub fn resources_panel(
 
                 ui.add_space(10.);
 
+                let morale = &player.morale;
+                ui.add_image(morale_texture, [20., 20.])
+                    .on_hover_text("Morale");
+                ui.add(egui::Label::new(format!("{:.0}", morale.value)))
+                    .on_hover_ui(|ui| {
+                        ui.strong(format!("Morale: {:.0} / {}", morale.value, MAX_MORALE));
+                        ui.label(format!(
+                            "Productivity and soldier damage: {:+.0}%",
+                            morale.modifier() * 100.
+                        ));
+                        if morale.value < DESERTION_MORALE {
+                            ui.colored_label(Color32::RED, "The population is deserting!");
+                        }
+
+                        ui.add_space(5.);
+                        if morale.changes.is_empty() {
+                            ui.label("No recent changes.");
+                        }
+                        for change in morale.changes.iter().rev() {
+                            ui.label(format!(
+                                "Day {}: {} {:+.0}",
+                                change.day,
+                                change.cause.label(),
+                                change.value
+                            ));
+                        }
+                    });
+
+                ui.add_space(10.);
+
                 ui.add_image(wall_texture, [20., 20.])
                     .on_hover_text("Fortress strength");
                 ui.add(
@@ -301,6 +335,15 @@ pub fn resources_panel(
                     player.resources.technology,
                 )));
 
+                ui.add_space(10.);
+
+                ui.add_image(food_texture, [20., 20.])
+                    .on_hover_text("Food");
+                ui.add(egui::Label::new(format!(
+                    "{:.0}",
+                    player.resources.food,
+                )));
+
                 ui.scope_builder(
                     UiBuilder {
                         invisible: *app_state.get() != AppState::Night,
@@ -344,6 +387,7 @@ pub fn resources_panel(
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn weapons_panel(
     mut commands: Commands,
     mut contexts: EguiContexts,
@@ -355,8 +399,9 @@ pub fn weapons_panel(
     mut player: ResMut<Player>,
     mut messages: ResMut<Messages>,
     mut night_stats: ResMut<NightStats>,
-    app_state: Res<State<AppState>>,
-    game_state: Res<State<GameState>>,
+    (mut drone_q, mut soldier_q): (Query<&mut Drone>, Query<&mut Soldier>),
+    mut game_settings: ResMut<GameSettings>,
+    (app_state, game_state): (Res<State<AppState>>, Res<State<GameState>>),
     weapons: Res<WeaponManager>,
     assets: Local<WorldAssets>,
     asset_server: Res<AssetServer>,
@@ -371,6 +416,9 @@ pub fn weapons_panel(
     let bulb_texture = contexts.add_image(assets.get_image("bulb"));
     let bullets_texture = contexts.add_image(assets.get_image("bullets"));
     let gasoline_texture = contexts.add_image(assets.get_image("gasoline"));
+    let drone_texture = contexts.add_image(assets.get_image("drone"));
+    let soldier_texture = contexts.add_image(assets.get_image("soldier"));
+    let chevron_texture = contexts.add_image(assets.get_image("chevron"));
 
     egui::SidePanel::right("Weapons panel")
         .exact_width(WEAPONS_PANEL_SIZE.x)
@@ -392,182 +440,142 @@ pub fn weapons_panel(
                 ui.separator();
 
                 // Store the old settings to only update weapons that changed
-                let old_s = player.weapons.settings.clone();
+                let old_s = player
+                    .weapons
+                    .spots
+                    .iter()
+                    .map(|s| (s.id, s.settings.clone()))
+                    .collect::<HashMap<_, _>>();
+
+                for weapon in WeaponName::iter() {
+                    let spots = player
+                        .weapons
+                        .spots
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, s)| (s.weapon == Some(weapon)).then_some(i))
+                        .collect::<Vec<_>>();
+
+                    if spots.is_empty() {
+                        continue;
+                    }
 
-                // Machine gun
-                if player.weapons.spots.iter().any(|w| match w.weapon {
-                    Some(w) => w == WeaponName::MachineGun,
-                    None => false,
-                }) {
                     ui.add_space(7.);
-                    ui.horizontal(|ui| {
-                        let label = ui.add(egui::Label::new(format!("{:?}: ", WeaponName::MachineGun))).on_hover_cursor(CursorIcon::PointingHand);
 
-                        ui.add(egui::Slider::new(&mut player.weapons.settings.machine_gun, 0..=MAX_MACHINE_GUN_FIRE_RATE))
-                            .on_hover_text("Shoot N bullets per second.");
+                    // Special case => the turret is charged over time and fired manually
+                    if weapon == WeaponName::Turret {
+                        let power = player.weapons.spots[spots[0]].settings.turret;
 
-                        if label.clicked() {
-                            player.weapons.settings.machine_gun = if player.weapons.settings.machine_gun > 0 {
-                                0
-                            } else {
-                                MAX_MACHINE_GUN_FIRE_RATE
-                            };
-                        }
-                    });
-                }
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Label::new(format!("{:?}: ", WeaponName::Turret)));
+                            ui.add(
+                                egui::ProgressBar::new(power / MAX_TURRET_POWER)
+                                    .desired_width(120.)
+                                    .desired_height(20.)
+                                    .show_percentage()
+                            );
 
-                // Canon
-                if player.weapons.spots.iter().any(|w| match w.weapon {
-                    Some(w) => w == WeaponName::Canon,
-                    None => false,
-                }) {
-                    ui.add_space(7.);
-                    ui.horizontal(|ui| {
-                        ui.add(egui::Label::new(format!("{:?}: ", WeaponName::Canon)));
-                        ui.selectable_value(&mut player.weapons.settings.canon, AirFireStrategy::None, AirFireStrategy::None.name())
-                            .on_hover_text("Don't fire.");
-                        ui.selectable_value(&mut player.weapons.settings.canon, AirFireStrategy::Grounded, AirFireStrategy::Grounded.name())
-                            .on_hover_text("Fire only at grounded (non-flying) enemies.");
-                        ui.selectable_value(&mut player.weapons.settings.canon, AirFireStrategy::Airborne, AirFireStrategy::Airborne.name())
-                            .on_hover_text("Fire only at flying enemies.");
-                    });
-                }
+                            let locked = weapon_q.iter().any(|w| w.name == WeaponName::Turret && w.fire_strategy == FireStrategy::Strongest);
+                            ui.add_enabled_ui(!locked && power > MAX_TURRET_POWER * 0.2 && *game_state.get() == GameState::Running, |ui| {
+                                let button = ui.add_sized([50., 20.], egui::Button::new("Fire!"))
+                                    .on_hover_text("Fire at the strongest enemy.");
 
-                // Flamethrower
-                if player.weapons.spots.iter().any(|w| match w.weapon {
-                    Some(w) => w == WeaponName::Flamethrower,
-                    None => false,
-                }) {
-                    ui.add_space(7.);
-                    ui.horizontal(|ui| {
-                        let label = ui.add(egui::Label::new(format!("{:?}: ", WeaponName::Flamethrower))).on_hover_cursor(CursorIcon::PointingHand);
+                                if button.clicked() {
+                                    if let Some(mut turret) = weapon_q.iter_mut().find(|w| w.name == WeaponName::Turret) {
+                                        turret.fire_strategy = FireStrategy::Strongest;
+                                    }
+                                }
 
-                        ui.add(egui::Slider::new(&mut player.weapons.settings.flamethrower, 0..=MAX_FLAMETHROWER_POWER))
-                            .on_hover_text("More power means more range, but costs more.");
+                                let aiming = game_settings.targeting == Some(Targeting::Turret);
+                                if ui.selectable_label(aiming, "Aim").on_hover_text("Click on the map to select the enemy to fire at.").clicked() {
+                                    game_settings.targeting = (!aiming).then_some(Targeting::Turret);
+                                }
+                            });
 
-                        if label.clicked() {
-                            player.weapons.settings.flamethrower = if player.weapons.settings.flamethrower > 0 {
-                                0
-                            } else {
-                                MAX_FLAMETHROWER_POWER
-                            };
-                        }
-                    });
-                }
+                            ui.add_rank(&player.weapons.spots[spots[0]], chevron_texture);
+                        });
 
-                // Artillery
-                if player.weapons.spots.iter().any(|w| match w.weapon {
-                    Some(w) => w == WeaponName::Artillery,
-                    None => false,
-                }) {
-                    ui.add_space(7.);
-                    ui.horizontal(|ui| {
-                        ui.add(egui::Label::new(format!("{:?}: ", WeaponName::Artillery)));
-                        ui.selectable_value(&mut player.weapons.settings.artillery, FireStrategy::None, FireStrategy::None.name())
-                            .on_hover_text("Don't fire.");
-                        ui.selectable_value(&mut player.weapons.settings.artillery, FireStrategy::Closest, FireStrategy::Closest.name())
-                            .on_hover_text("Fire on the closest enemy.");
-                        ui.selectable_value(&mut player.weapons.settings.artillery, FireStrategy::Strongest, FireStrategy::Strongest.name())
-                            .on_hover_text("Fire on the strongest enemy.");
-                    });
-                }
+                        continue;
+                    }
 
-                // AAA
-                if player.weapons.spots.iter().any(|w| match w.weapon {
-                    Some(w) => w == WeaponName::AAA,
-                    None => false,
-                }) {
-                    ui.add_space(7.);
-                    ui.horizontal(|ui| {
-                        ui.add(egui::Label::new(format!("{:?}: ", WeaponName::AAA)));
-                        ui.selectable_value(&mut player.weapons.settings.aaa, AirFireStrategy::None, AirFireStrategy::None.name())
-                            .on_hover_text("Don't fire.");
-                        ui.selectable_value(&mut player.weapons.settings.aaa, AirFireStrategy::All, AirFireStrategy::All.name())
-                            .on_hover_text("Fire at all enemies dealing reduced damage.");
-                        ui.selectable_value(&mut player.weapons.settings.aaa, AirFireStrategy::Airborne, AirFireStrategy::Airborne.name())
-                            .on_hover_text("Fire only at flying enemies, dealing more damage.");
-                    });
-                }
+                    // Change the settings of all weapons of this type at once
+                    let mut settings = player.weapons.spots[spots[0]].settings.clone();
+                    ui.add_weapon_settings(&format!("{:?}: ", weapon), weapon, &mut settings);
+                    if settings != player.weapons.spots[spots[0]].settings {
+                        for i in spots.iter() {
+                            player.weapons.spots[*i].settings.copy_from(&settings, weapon);
+                        }
+                    }
 
-                // Mortar
-                if player.weapons.spots.iter().any(|w| match w.weapon {
-                    Some(w) => w == WeaponName::Mortar,
-                    None => false,
-                }) {
-                    ui.add_space(7.);
-                    ui.horizontal(|ui| {
-                        ui.add(egui::Label::new(format!("{:?}: ", WeaponName::Mortar)));
-                        ui.selectable_value(&mut player.weapons.settings.mortar, MortarShell::None, MortarShell::None.name())
-                            .on_hover_text("Don't fire.");
-                        ui.selectable_value(&mut player.weapons.settings.mortar, MortarShell::Light, MortarShell::Light.name())
-                            .on_hover_text("Light shells do standard damage and don't damage structures.");
-                        ui.selectable_value(&mut player.weapons.settings.mortar, MortarShell::Heavy, MortarShell::Heavy.name())
-                            .on_hover_text("Heavy shells do more damage, but cost more and damage structures.");
-                    });
-                }
-                
-                // Turret
-                if player.weapons.spots.iter().any(|w| match w.weapon {
-                    Some(w) => w == WeaponName::Turret,
-                    None => false,
-                }) {
-                    ui.add_space(7.);
-                    ui.horizontal(|ui| {
-                        ui.add(egui::Label::new(format!("{:?}: ", WeaponName::Turret)));
-                        ui.add(
-                            egui::ProgressBar::new(player.weapons.settings.turret / MAX_TURRET_POWER)
-                                .desired_width(120.)
-                                .desired_height(20.)
-                                .show_percentage()
-                        );
+                    // Change the settings of every weapon individually
+                    if spots.len() > 1 {
+                        egui::CollapsingHeader::new("Per spot")
+                            .id_salt(weapon.name())
+                            .show(ui, |ui| {
+                                for &i in spots.iter() {
+                                    ui.add_weapon_settings(
+                                        &format!("Spot {}: ", i + 1),
+                                        weapon,
+                                        &mut player.weapons.spots[i].settings,
+                                    );
+                                }
+                            });
+                    }
 
-                        let locked = weapon_q.iter().any(|w| w.name == WeaponName::Turret && w.fire_strategy == FireStrategy::Strongest);
-                        ui.add_enabled_ui(!locked && player.weapons.settings.turret > MAX_TURRET_POWER * 0.2 && *game_state.get() == GameState::Running, |ui| {
-                            let button = ui.add_sized([50., 20.], egui::Button::new("Fire!"));
+                    // Show the rank, magazine and heat of every weapon
+                    for i in spots {
+                        let Some(w) = weapon_q.iter().find(|w| w.spot == player.weapons.spots[i].id) else {
+                            continue;
+                        };
 
-                            if button.clicked() {
-                                if let Some(mut turret) = weapon_q.iter_mut().find(|w| w.name == WeaponName::Turret) {
-                                    turret.fire_strategy = FireStrategy::Strongest;
-                                }
-                            }
-                        });
-                    });
-                }
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Label::new(format!("Spot {}: ", i + 1)));
+                            ui.add_rank(&player.weapons.spots[i], chevron_texture);
+
+                            if let Some(magazine) = &w.magazine {
+                                let text = if magazine.is_reloading() {
+                                    "Reloading".to_string()
+                                } else {
+                                    format!("{}/{}", magazine.rounds, magazine.capacity)
+                                };
 
-                // Missile launcher
-                if player.weapons.spots.iter().any(|w| match w.weapon {
-                    Some(w) => w == WeaponName::MissileLauncher,
-                    None => false,
-                }) {
-                    ui.add_space(7.);
-                    ui.horizontal(|ui| {
-                        let label = ui.add(egui::Label::new(format!("{:?}: ", WeaponName::MissileLauncher))).on_hover_cursor(CursorIcon::PointingHand);
+                                ui.add(
+                                    egui::ProgressBar::new(magazine.fraction())
+                                        .desired_width(90.)
+                                        .desired_height(20.)
+                                        .fill(egui::Color32::from_rgb(180, 150, 30))
+                                        .text(text)
+                                ).on_hover_text("Rounds left in the magazine.");
+                            }
 
-                        ui.add(egui::Slider::new(&mut player.weapons.settings.missile_launcher, 0..=MAX_MISSILE_LAUNCHER_SHELLS))
-                            .on_hover_text("Shoot N shells per firing round.");
+                            if let Some(overheat) = &w.overheat {
+                                let text = if overheat.overheated {
+                                    "Overheated".to_string()
+                                } else {
+                                    format!("{:.0}%", overheat.fraction() * 100.)
+                                };
 
-                        if label.clicked() {
-                            player.weapons.settings.missile_launcher = if player.weapons.settings.missile_launcher > 0 {
-                                0
-                            } else {
-                                MAX_MISSILE_LAUNCHER_SHELLS
-                            };
-                        }
-                    });
+                                ui.add(
+                                    egui::ProgressBar::new(overheat.fraction())
+                                        .desired_width(90.)
+                                        .desired_height(20.)
+                                        .fill(egui::Color32::from_rgb(200, 90, 0))
+                                        .text(text)
+                                ).on_hover_text("Heat of the weapon. When overheated, it stops firing until cooled down.");
+                            }
+                        });
+                    }
                 }
 
                 // Update weapons with the changed settings
                 weapon_q
                     .iter_mut()
-                    .filter(|w| match w.name {
-                        WeaponName::AAA => old_s.aaa != player.weapons.settings.aaa,
-                        WeaponName::Artillery => old_s.artillery != player.weapons.settings.artillery,
-                        WeaponName::Canon => old_s.canon != player.weapons.settings.canon,
-                        WeaponName::MachineGun => old_s.machine_gun != player.weapons.settings.machine_gun,
-                        WeaponName::Flamethrower => old_s.flamethrower != player.weapons.settings.flamethrower,
-                        WeaponName::Mortar => old_s.mortar != player.weapons.settings.mortar,
-                        WeaponName::Turret => old_s.turret != player.weapons.settings.turret,
-                        WeaponName::MissileLauncher => old_s.missile_launcher != player.weapons.settings.missile_launcher,
+                    .filter(|w| {
+                        player
+                            .weapons
+                            .spots
+                            .iter()
+                            .any(|s| s.id == w.spot && old_s.get(&s.id) != Some(&s.settings))
                     })
                     .for_each(|mut w| w.as_mut().update(&player));
 
@@ -581,11 +589,11 @@ pub fn weapons_panel(
                         ui.horizontal(|ui| {
                             ui.add_image(mine_texture, [20., 15.]);
                             ui.add(egui::Label::new(format!("Mine ({}): ", player.weapons.mines)));
-                            ui.selectable_value(&mut player.weapons.settings.mine, Size::Small, Size::Small.name())
+                            ui.selectable_value(&mut player.weapons.explosives.mine, Size::Small, Size::Small.name())
                                 .on_hover_text("Detonate for all enemies.");
-                            ui.selectable_value(&mut player.weapons.settings.mine, Size::Medium, Size::Medium.name())
+                            ui.selectable_value(&mut player.weapons.explosives.mine, Size::Medium, Size::Medium.name())
                                 .on_hover_text("Detonate for medium and large enemies.");
-                            ui.selectable_value(&mut player.weapons.settings.mine, Size::Large, Size::Large.name())
+                            ui.selectable_value(&mut player.weapons.explosives.mine, Size::Large, Size::Large.name())
                                 .on_hover_text("Detonate only for large enemies.");
                         });
                     }
@@ -600,13 +608,20 @@ pub fn weapons_panel(
                                     .on_hover_text("Launch!")
                             });
 
-                            ui.selectable_value(&mut player.weapons.settings.bomb, FireStrategy::Density, FireStrategy::Density.name())
+                            ui.selectable_value(&mut player.weapons.explosives.bomb, FireStrategy::Density, FireStrategy::Density.name())
                                 .on_hover_text("Launch at highest enemy density location.");
-                            ui.selectable_value(&mut player.weapons.settings.bomb, FireStrategy::Strongest, FireStrategy::Strongest.name())
+                            ui.selectable_value(&mut player.weapons.explosives.bomb, FireStrategy::Strongest, FireStrategy::Strongest.name())
                                 .on_hover_text("Launch at strongest enemy.");
 
+                            ui.add_enabled_ui(*game_state.get() == GameState::Running, |ui| {
+                                let aiming = game_settings.targeting == Some(Targeting::Bomb);
+                                if ui.selectable_label(aiming, "Aim").on_hover_text("Click on the map to select the detonation location.").clicked() {
+                                    game_settings.targeting = (!aiming).then_some(Targeting::Bomb);
+                                }
+                            });
+
                             if label.inner.clicked() {
-                                let mut bomb = weapons.bomb.clone();
+                                let bomb = &weapons.bomb;
 
                                 let explosion = match &bomb.impact {
                                     Impact::Explosion(e) => e,
@@ -618,7 +633,7 @@ pub fn weapons_panel(
                                     .filter(|(_, enemy_t, enemy)| is_visible(fow_q.get_single().unwrap(), enemy_t, enemy))
                                     .collect::<Vec<_>>();
 
-                                if let Some((_, enemy_t, enemy)) = match player.weapons.settings.bomb {
+                                if let Some((_, enemy_t, enemy)) = match player.weapons.explosives.bomb {
                                     FireStrategy::Strongest => {
                                         visible_enemies
                                             .iter()
@@ -653,33 +668,19 @@ pub fn weapons_panel(
                                     let start = Vec3::new(enemy_t.translation.x, SIZE.y * 0.5, WEAPON_Z);
 
                                     // Calculate the detonation's position
-                                    bomb.movement = Movement::Location(if player.has_tech(TechnologyName::Aimbot) {
+                                    let target = if player.has_tech(TechnologyName::Aimbot) {
                                         get_future_position(
                                             enemy_t.translation,
-                                            enemy.speed,
-                                            start,
-                                            bomb.speed,
+                                            enemy.current_speed(),
+                                            bomb.flight_time(start, enemy_t.translation),
                                             fence_q.get_single(),
                                             wall_q.get_single(),
                                         )
                                     } else {
                                         enemy_t.translation
-                                    });
-
-                                    commands.spawn((
-                                        Sprite {
-                                            image: asset_server.load(bomb.image),
-                                            custom_size: Some(bomb.dim),
-                                            ..default()
-                                        },
-                                        Transform {
-                                            translation: start,
-                                            rotation: Quat::from_rotation_z(-PI * 0.5),
-                                            ..default()
-                                        },
-                                        bomb,
-                                    ));
+                                    };
 
+                                    spawn_airstrike(&mut commands, bomb.clone(), target.truncate(), &asset_server);
                                     player.weapons.bombs -= 1;
                                 }
                             }
@@ -694,28 +695,108 @@ pub fn weapons_panel(
                             ui.horizontal(|ui| {
                                 ui.add_image(nuke_texture, [20., 20.]);
                                 ui.add(egui::Label::new(format!("Nuke ({}): ", player.weapons.nuke)));
-                                let button = ui.add_sized([60., 20.], egui::Button::new("Launch!"));
+                                let button = ui.add_sized([60., 20.], egui::Button::new("Launch!"))
+                                    .on_hover_text("Launch at the center of the map.");
 
                                 if button.clicked() {
-                                    let nuke = weapons.nuke.clone();
                                     messages.info("A nuke is launched");
 
-                                    commands.spawn((
-                                        Sprite {
-                                            image: asset_server.load(nuke.image),
-                                            custom_size: Some(nuke.dim),
-                                            ..default()
-                                        },
-                                        Transform {
-                                            translation: Vec3::new(-WEAPONS_PANEL_SIZE.x * 0.5, SIZE.y * 0.5, WEAPON_Z),
-                                            rotation: Quat::from_rotation_z(-PI * 0.5),
-                                            ..default()
-                                        },
-                                        nuke,
-                                    ));
-
+                                    spawn_airstrike(
+                                        &mut commands,
+                                        weapons.nuke.clone(),
+                                        Vec2::new(-WEAPONS_PANEL_SIZE.x * 0.5, SIZE.y * 0.5 - MAP_SIZE.y * 0.5),
+                                        &asset_server,
+                                    );
                                     player.weapons.nuke -= 1;
                                 }
+
+                                let aiming = game_settings.targeting == Some(Targeting::Nuke);
+                                if ui.selectable_label(aiming, "Aim").on_hover_text("Click on the map to select the detonation location.").clicked() {
+                                    game_settings.targeting = (!aiming).then_some(Targeting::Nuke);
+                                }
+                            });
+                        });
+                    }
+
+                    ui.add_space(7.);
+                }
+
+                if !drone_q.is_empty() {
+                    ui.separator();
+                    ui.add_space(7.);
+
+                    ui.horizontal(|ui| {
+                        ui.add_image(drone_texture, [20., 20.]);
+                        ui.add(egui::Label::new("Patrol: "));
+                        ui.add(egui::Slider::new(&mut player.weapons.drone_patrol, 0.2..=0.9).show_value(false))
+                            .on_hover_text("Height of the band the drones patrol.");
+                    });
+
+                    for (i, mut drone) in drone_q.iter_mut().enumerate() {
+                        ui.add_space(5.);
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Label::new(format!("Drone {}: ", i + 1)));
+                            ui.add(
+                                egui::ProgressBar::new(drone.health / drone.max_health)
+                                    .desired_width(70.)
+                                    .desired_height(20.)
+                            ).on_hover_text("Health of the drone.");
+                            ui.label(format!("{}/{}", drone.ammo, drone.max_ammo)).on_hover_text("Rounds left.");
+
+                            if drone.status == DroneStatus::Recalled {
+                                if ui.add_sized([60., 20.], egui::Button::new("Launch")).on_hover_text("Send the drone back on patrol.").clicked() {
+                                    drone.status = DroneStatus::Patrolling;
+                                }
+                            } else if ui.add_sized([60., 20.], egui::Button::new("Recall")).on_hover_text("Return the drone to its bay.").clicked() {
+                                drone.status = DroneStatus::Recalled;
+                            }
+                        });
+                    }
+
+                    ui.add_space(7.);
+                }
+
+                if *app_state.get() == AppState::Night && player.population.soldier > 0 {
+                    ui.separator();
+                    ui.add_space(7.);
+
+                    ui.horizontal(|ui| {
+                        ui.add_image(soldier_texture, [20., 20.]);
+                        ui.add(egui::Label::new("Line: "));
+                        ui.add(egui::Slider::new(&mut player.weapons.sortie_line, 0.15..=0.6).show_value(false))
+                            .on_hover_text("Height of the line the soldiers hold during a sortie.");
+                    });
+
+                    ui.add_space(5.);
+                    if soldier_q.is_empty() {
+                        ui.horizontal(|ui| {
+                            let max_squad = player.population.soldier.min(MAX_SORTIE);
+                            game_settings.sortie = game_settings.sortie.clamp(1, max_squad);
+
+                            ui.add(egui::Label::new("Squad: "));
+                            ui.add(egui::Slider::new(&mut game_settings.sortie, 1..=max_squad))
+                                .on_hover_text("Number of soldiers sent on the sortie.");
+
+                            if ui.add_sized([60., 20.], egui::Button::new("Sortie")).on_hover_text("Send the soldiers out to fight the bugs in front of the wall.").clicked() {
+                                spawn_sortie(&mut commands, game_settings.sortie, &player, &weapons, &asset_server);
+                            }
+                        });
+                    } else {
+                        let (health, max_health) = soldier_q.iter().fold((0., 0.), |(h, m), s| (h + s.health, m + s.max_health));
+                        let retreating = soldier_q.iter().all(|s| s.status == SortieStatus::Retreating);
+
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Label::new(format!("Squad ({}): ", soldier_q.iter().count())));
+                            ui.add(
+                                egui::ProgressBar::new(health / max_health)
+                                    .desired_width(70.)
+                                    .desired_height(20.)
+                            ).on_hover_text("Health of the squad.");
+
+                            ui.add_enabled_ui(!retreating, |ui| {
+                                if ui.add_sized([60., 20.], egui::Button::new("Retreat")).on_hover_text("Order the soldiers back into the fortress.").clicked() {
+                                    soldier_q.iter_mut().for_each(|mut s| s.status = SortieStatus::Retreating);
+                                }
                             });
                         });
                     }
@@ -781,81 +862,50 @@ pub fn weapons_panel(
                     ui.add_space(7.);
                 }
 
-                ui.separator();
-                ui.add_space(7.);
-
-                ui.horizontal(|ui| {
-                    ui.add_image(bullets_texture, [20., 25.]);
-                    ui.add_enabled_ui(player.resources.materials >= 300., |ui| {
-                        let bullet_button_100 = ui.add_sized([30., 30.], egui::Button::new("+100"))
-                            .on_hover_text("Buy 100 bullets for 300 materials.");
-                        if bullet_button_100.clicked() {
-                            let cost = Resources {
-                                bullets: 100.,
-                                materials: -300.,
-                                ..default()
-                            };
-                            player.resources += &cost;
-                            night_stats.resources += &cost;
-                            night_stats.warnings.no_bullets = false;
-                        }
-                    });
-
-                    ui.add_enabled_ui(player.resources.materials >= 1500., |ui| {
-                        let bullet_button_500 = ui.add_sized([30., 30.], egui::Button::new("+500"))
-                            .on_hover_text("Buy 500 bullets for 1500 materials.");
-                        if bullet_button_500.clicked() {
-                            let cost = Resources {
-                                bullets: 500.,
-                                materials: -1500.,
-                                ..default()
-                            };
-                            player.resources += &cost;
-                            night_stats.resources += &cost;
-                            night_stats.warnings.no_bullets = false;
-
-                        }
-                    });
-
-                    ui.add_space(13.);
-
-                    ui.add_image(gasoline_texture, [20., 20.]);
-                    ui.add_enabled_ui(player.resources.materials >= 300., |ui| {
-                        let gasoline_button = ui.add_sized([30., 30.], egui::Button::new("+100"))
-                            .on_hover_text("Buy 100 gasoline for 300 materials.");
-                        if gasoline_button.clicked() {
-                            let cost = Resources {
-                                gasoline: 100.,
-                                materials: -300.,
-                                ..default()
-                            };
-
-                            player.resources += &cost;
-                            night_stats.resources += &cost;
-                            night_stats.warnings.no_gasoline = false;
-                        }
-                    });
+                // Emergency trades are only possible during the night
+                if *app_state.get() == AppState::Night {
+                    ui.separator();
+                    ui.add_space(7.);
 
-                    ui.add_enabled_ui(player.resources.materials >= 1500., |ui| {
-                        let gasoline_button_500 = ui.add_sized([30., 30.], egui::Button::new("+500"))
-                            .on_hover_text("Buy 500 gasoline for 1500 materials.");
-                        if gasoline_button_500.clicked() {
-                            let cost = Resources {
-                                gasoline: 500.,
-                                materials: -1500.,
-                                ..default()
-                            };
+                    ui.horizontal(|ui| {
+                        for (i, (name, texture, size)) in [
+                            (ResourceName::Bullets, bullets_texture, [20., 25.]),
+                            (ResourceName::Gasoline, gasoline_texture, [20., 20.]),
+                        ].into_iter().enumerate() {
+                            if i > 0 {
+                                ui.add_space(13.);
+                            }
 
-                            player.resources += &cost;
-                            night_stats.resources += &cost;
-                            night_stats.warnings.no_gasoline = false;
+                            ui.add_image(texture, size);
+                            for amount in [100., 500.] {
+                                let cost = (amount / player.market.rate(ResourceName::Materials, name, NIGHT_MARKUP)).ceil();
+                                ui.add_enabled_ui(player.resources.materials >= cost, |ui| {
+                                    let button = ui.add_sized([30., 30.], egui::Button::new(format!("+{amount}")))
+                                        .on_hover_text(format!(
+                                            "Buy {amount} {} for {cost:.0} materials (emergency markup of {:.0}%).",
+                                            name.name().to_lowercase(),
+                                            NIGHT_MARKUP * 100.,
+                                        ));
+                                    if button.clicked() {
+                                        // The trade already moves the market's rates, so it's
+                                        // not counted with the resources spent during the night
+                                        if player.trade(ResourceName::Materials, name, cost, NIGHT_MARKUP).is_some() {
+                                            match name {
+                                                ResourceName::Bullets => night_stats.warnings.no_bullets = false,
+                                                _ => night_stats.warnings.no_gasoline = false,
+                                            }
+                                        }
+                                    }
+                                });
+                            }
                         }
                     });
-                });
+                }
             });
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn day_panel(
     mut commands: Commands,
     fence_q: Query<SpriteQ, With<FenceComponent>>,
@@ -873,6 +923,58 @@ pub fn day_panel(
 ) {
     let window_size = window.single().size();
 
+    // While placing mines, only show a small window so the map is visible
+    if game_settings.targeting == Some(Targeting::Mine) {
+        egui::Window::new("mine placement")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, (-WEAPONS_PANEL_SIZE.x * 0.5, MENU_PANEL_SIZE.y + 10.))
+            .show(contexts.ctx_mut(), |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.strong(format!(
+                        "Mines placed: {}/{}",
+                        player.weapons.mine_positions.len(),
+                        player.weapons.mines
+                    ));
+                    ui.label("Click on the map to place a mine. Click on a placed mine to pick it up.");
+                    ui.add_space(5.);
+                    if ui.button("Done").clicked() {
+                        game_settings.targeting = None;
+                    }
+                });
+            });
+
+        return;
+    }
+
+    // While placing weapons, only show a small window so the wall is visible
+    if game_settings.targeting == Some(Targeting::Weapons) {
+        egui::Window::new("weapon placement")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, (-WEAPONS_PANEL_SIZE.x * 0.5, MENU_PANEL_SIZE.y + 10.))
+            .show(contexts.ctx_mut(), |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.label("Drag the weapons to place them on the wall or the fence. Weapons can't overlap.");
+                    ui.add_space(5.);
+                    ui.horizontal(|ui| {
+                        if ui.button("Reset").on_hover_text("Space the weapons evenly on the wall.").clicked() {
+                            player.weapons.spots.iter_mut().for_each(|s| {
+                                s.x = None;
+                                s.mount = Mount::Wall;
+                            });
+                            spawn_spots(&mut commands, &weapon_q, &player, &weapons, &asset_server);
+                        }
+                        if ui.button("Done").clicked() {
+                            game_settings.targeting = None;
+                        }
+                    });
+                });
+            });
+
+        return;
+    }
+
     let population_texture = contexts.add_image(assets.get_image("population"));
     let soldier_texture = contexts.add_image(assets.get_image("soldier"));
     let combat_texture = contexts.add_image(assets.get_image("combat"));
@@ -884,6 +986,9 @@ pub fn day_panel(
     let materials_texture = contexts.add_image(assets.get_image("materials"));
     let scientist_texture = contexts.add_image(assets.get_image("scientist"));
     let technology_texture = contexts.add_image(assets.get_image("technology"));
+    let farmer_texture = contexts.add_image(assets.get_image("farmer"));
+    let wounded_texture = contexts.add_image(assets.get_image("wounded"));
+    let food_texture = contexts.add_image(assets.get_image("food"));
     let up_texture = contexts.add_image(assets.get_image("up-arrow"));
     let repair_texture = contexts.add_image(assets.get_image("repair"));
     let spots_texture = contexts.add_image(assets.get_image("spots"));
@@ -897,20 +1002,27 @@ pub fn day_panel(
     let cross_texture = contexts.add_image(assets.get_image("cross"));
     let idle_texture = contexts.add_image(assets.get_image("idle"));
     let clock_texture = contexts.add_image(assets.get_image("clock"));
+    let hourglass_texture = contexts.add_image(assets.get_image("hourglass"));
     let armory_texture = contexts.add_image(assets.get_image("armory"));
     let refinery_texture = contexts.add_image(assets.get_image("refinery"));
     let factory_texture = contexts.add_image(assets.get_image("factory"));
     let laboratory_texture = contexts.add_image(assets.get_image("laboratory"));
+    let housing_texture = contexts.add_image(assets.get_image("housing"));
+    let hospital_texture = contexts.add_image(assets.get_image("hospital"));
     let wall_texture = contexts.add_image(assets.get_image("wall-shop"));
     let fence_texture = contexts.add_image(assets.get_image("fence-shop"));
     let lightning_texture = contexts.add_image(assets.get_image("lightning"));
+    let drone_texture = contexts.add_image(assets.get_image("drone"));
     let aaa_texture = contexts.add_image(assets.get_image("aaa"));
     let artillery_texture = contexts.add_image(assets.get_image("artillery"));
     let canon_texture = contexts.add_image(assets.get_image("canon"));
+    let cryo_texture = contexts.add_image(assets.get_image("cryo"));
     let flamethrower_texture = contexts.add_image(assets.get_image("flamethrower"));
     let machine_gun_texture = contexts.add_image(assets.get_image("machine-gun"));
     let missile_launcher_texture = contexts.add_image(assets.get_image("ml"));
     let mortar_texture = contexts.add_image(assets.get_image("mortar"));
+    let sniper_texture = contexts.add_image(assets.get_image("sniper"));
+    let tesla_texture = contexts.add_image(assets.get_image("tesla"));
     let turret_texture = contexts.add_image(assets.get_image("turret"));
     let mine_texture = contexts.add_image(assets.get_image("mine-shop"));
     let bomb_texture = contexts.add_image(assets.get_image("bomb-shop"));
@@ -962,6 +1074,12 @@ pub fn day_panel(
                         DayTabs::Expeditions.name(),
                     );
                 }
+                ui.add_space(5.);
+                ui.selectable_value(
+                    &mut game_settings.day_tab,
+                    DayTabs::Market,
+                    DayTabs::Market.name(),
+                );
             });
 
             ui.separator();
@@ -984,6 +1102,58 @@ pub fn day_panel(
                         );
                     });
 
+                    if let Some(event) = player.day_events.get(&player.day).cloned() {
+                        ui.add_scroll("event", MAP_SIZE.x * 0.1, |ui| {
+                            ui.add_space(10.);
+                            ui.label(event.description());
+                            if let Some(choice) = event.choice {
+                                ui.strong(format!("You chose: {}.", event.choices()[choice].label.to_lowercase()));
+                            }
+                        });
+
+                        if event.choice.is_none() {
+                            egui::Modal::new(egui::Id::new("day event")).show(ui.ctx(), |ui| {
+                                ui.set_width(MAP_SIZE.x * 0.4);
+                                ui.vertical_centered(|ui| {
+                                    ui.heading(event.name.label());
+                                    ui.add_space(10.);
+                                    ui.label(event.description());
+                                    ui.add_space(15.);
+
+                                    for (i, choice) in event.choices().iter().enumerate() {
+                                        ui.add_enabled_ui(player.resources >= choice.price, |ui| {
+                                            ui.horizontal(|ui| {
+                                                ui.add_space(MAP_SIZE.x * 0.1);
+                                                if ui.button(choice.label).clicked() {
+                                                    let free = player.free_housing();
+                                                    if event.name == DayEventName::Refugees && i == 0 && free < event.amount {
+                                                        messages.warning(format!("Only {free} refugees fit in the fortress, the rest is turned away."));
+                                                    }
+                                                    player.resolve_day_event(i);
+                                                }
+
+                                                for (price, texture) in [
+                                                    (choice.price.bullets, bullets_texture),
+                                                    (choice.price.gasoline, gasoline_texture),
+                                                    (choice.price.materials, materials_texture),
+                                                ] {
+                                                    if price > 0. {
+                                                        ui.add_image(texture, [20., 20.]);
+                                                        ui.label(format!("{:.0}", price));
+                                                    }
+                                                }
+                                            });
+                                        })
+                                        .response
+                                        .on_disabled_hover_text("Not enough resources.");
+
+                                        ui.add_space(5.);
+                                    }
+                                });
+                            });
+                        }
+                    }
+
                     ui.add_night_stats(&player, player.day - 1);
 
                     ui.with_layout(Layout::right_to_left(Align::RIGHT), |ui| {
@@ -993,8 +1163,10 @@ pub fn day_panel(
                             .add_button(format!("   Continue to night {}   ", player.day))
                             .clicked()
                         {
-                            if player.population.idle == 0 {
-                                player.resolve_expedition();
+                            if player.pending_day_event().is_some() {
+                                messages.error("Decide on today's event first!");
+                            } else if player.population.idle == 0 {
+                                while player.resolve_expedition() {}
                                 next_state.set(AppState::Night);
                             } else {
                                 messages.error("You have idle population!");
@@ -1008,33 +1180,42 @@ pub fn day_panel(
                         ui.label(
                             "Distribute the population over the resources. The number of \
                             workers on a specific trait determines the amount of that resource \
-                            you will get tomorrow. Make sure to not let anyone idle!",
+                            you will get tomorrow. Make sure to not let anyone idle, and that \
+                            the farmers produce enough food for everyone!",
                         );
                         ui.add_space(15.);
                     });
 
                     ui.add_scroll("res2", MAP_SIZE.x * 0.125, |ui| {
-                        let soldiers = ui
+                        let (soldiers, recruits) = ui
                             .horizontal_centered(|ui| {
                                 ui.add_image(soldier_texture, [40., 40.]);
                                 let label = ui
                                     .add_text("Soldiers", 100.)
                                     .on_hover_cursor(CursorIcon::PointingHand);
-                                let mut soldiers = player.population.soldier.clone();
-                                ui.add(egui::Slider::new(
-                                    &mut soldiers,
-                                    0..=player.population.soldier + player.population.idle,
-                                ))
-                                .on_hover_text("Assign the population to produce bullets.");
+
+                                // Soldiers can be dismissed at once, but new ones need a day of training
+                                let max_soldiers = player.population.soldier
+                                    + (player.population.recruit + player.population.idle).min(player.max_recruits());
+                                let mut soldiers = player.population.soldier + player.population.recruit;
+                                ui.add(egui::Slider::new(&mut soldiers, 0..=max_soldiers))
+                                    .on_hover_text(format!("Train the population to fight. Training takes a day and costs {TRAINING_COST} bullets per soldier."));
                                 ui.add_space(10.);
                                 ui.add_image(combat_texture, [20., 20.]).on_hover_text("Combat strength.");
                                 ui.label(format!("x{}", player.get_soldier_damage()));
 
                                 if label.clicked() {
-                                    soldiers = player.population.soldier + player.population.idle;
+                                    soldiers = max_soldiers;
                                 }
 
-                                soldiers
+                                let recruits = soldiers.saturating_sub(player.population.soldier);
+                                if recruits > 0 {
+                                    ui.add_space(10.);
+                                    ui.label(format!("({} in training)", recruits))
+                                        .on_hover_text(format!("Costs {:.0} bullets tomorrow.", recruits as f32 * TRAINING_COST));
+                                }
+
+                                (soldiers - recruits, recruits)
                             })
                             .inner;
 
@@ -1046,7 +1227,7 @@ pub fn day_panel(
                                 let label = ui
                                     .add_text("Armorers", 100.)
                                     .on_hover_cursor(CursorIcon::PointingHand);
-                                let mut armorers = player.population.armorer.clone();
+                                let mut armorers = player.population.armorer;
                                 ui.add(egui::Slider::new(
                                     &mut armorers,
                                     0..=player.population.armorer + player.population.idle,
@@ -1072,7 +1253,7 @@ pub fn day_panel(
                                 let label = ui
                                     .add_text("Refiners", 100.)
                                     .on_hover_cursor(CursorIcon::PointingHand);
-                                let mut refiners = player.population.refiner.clone();
+                                let mut refiners = player.population.refiner;
                                 ui.add(egui::Slider::new(
                                     &mut refiners,
                                     0..=player.population.refiner + player.population.idle,
@@ -1098,7 +1279,7 @@ pub fn day_panel(
                                 let label = ui
                                     .add_text("Constructors", 100.)
                                     .on_hover_cursor(CursorIcon::PointingHand);
-                                let mut constructors = player.population.constructor.clone();
+                                let mut constructors = player.population.constructor;
                                 ui.add(egui::Slider::new(
                                     &mut constructors,
                                     0..=player.population.constructor + player.population.idle,
@@ -1125,7 +1306,7 @@ pub fn day_panel(
                                 let label = ui
                                     .add_text("Scientists", 100.)
                                     .on_hover_cursor(CursorIcon::PointingHand);
-                                let mut scientists = player.population.scientist.clone();
+                                let mut scientists = player.population.scientist;
                                 ui.add(egui::Slider::new(
                                     &mut scientists,
                                     0..=player.population.scientist + player.population.idle,
@@ -1146,356 +1327,529 @@ pub fn day_panel(
 
                         ui.add_space(15.);
 
+                        let farmers = ui
+                            .horizontal_centered(|ui| {
+                                ui.add_image(farmer_texture, [40., 40.]);
+                                let label = ui
+                                    .add_text("Farmers", 100.)
+                                    .on_hover_cursor(CursorIcon::PointingHand);
+                                let mut farmers = player.population.farmer;
+                                ui.add(egui::Slider::new(
+                                    &mut farmers,
+                                    0..=player.population.farmer + player.population.idle,
+                                ))
+                                .on_hover_text("Assign the population to produce food.");
+                                ui.add_space(10.);
+                                ui.add_image(food_texture, [20., 20.]);
+                                ui.label(format!("+{:.0}", new_resources.food))
+                                    .on_hover_text(format!("The population eats {:.0} food per day.", player.food_consumption()));
+
+                                if label.clicked() {
+                                    farmers = player.population.farmer + player.population.idle;
+                                }
+
+                                farmers
+                            })
+                            .inner;
+
+                        ui.add_space(15.);
+
                         ui.horizontal_centered(|ui| {
                             ui.add_image(idle_texture, [40., 40.]);
                             ui.add_text("Idle", 100.);
                             ui.strong(format!("{}", player.population.idle));
                         });
 
+                        if player.population.wounded > 0 {
+                            ui.add_space(15.);
+
+                            ui.horizontal_centered(|ui| {
+                                ui.add_image(wounded_texture, [40., 40.]);
+                                ui.add_text("Wounded", 100.);
+                                ui.strong(format!("{}", player.population.wounded))
+                                    .on_hover_text("The wounded can't work until they recover in the hospital.");
+                            });
+                        }
+
+                        if player.population.sick > 0 {
+                            ui.add_space(15.);
+
+                            ui.horizontal_centered(|ui| {
+                                ui.add_image(wounded_texture, [40., 40.]);
+                                ui.add_text("Sick", 100.);
+                                ui.strong(format!("{}", player.population.sick))
+                                    .on_hover_text("The sick are in quarantine and return to work tomorrow.");
+                            });
+                        }
+
                         //Resolve population choices
-                        if soldiers + armorers + refiners + constructors + scientists
-                            <= player.population.total()
+                        if soldiers + recruits + armorers + refiners + constructors + scientists + farmers
+                            <= player.population.available()
                         {
                             player.population = Population {
                                 soldier: soldiers,
+                                recruit: recruits,
                                 armorer: armorers,
                                 refiner: refiners,
                                 constructor: constructors,
                                 scientist: scientists,
-                                idle: player.population.total()
+                                farmer: farmers,
+                                idle: player.population.available()
                                     - soldiers
+                                    - recruits
                                     - armorers
                                     - refiners
                                     - constructors
-                                    - scientists,
+                                    - scientists
+                                    - farmers,
+                                wounded: player.population.wounded,
+                                sick: player.population.sick,
                             };
                         }
                     });
                 }
                 DayTabs::Constructions => {
-                    ui.add_space(5.);
-                    ui.horizontal(|ui| {
-                        ui.add_space(20.);
-                        ui.heading("Resources");
-                    });
-                    ui.add_space(15.);
-
-                    let frame = egui::Frame::none()
-                        .fill(Color32::from_black_alpha(190))
-                        .rounding(5.0)
-                        .inner_margin(egui::vec2(10., 5.));
-
-                    ui.horizontal(|ui| {
-                        ui.add_space(30.);
-                        ui.add_image(armory_texture, [130., 130.]);
-                        ui.add_space(20.);
-                        ui.vertical(|ui| {
-                            let cost = ((player.constructions.armory + 1) * 100) as f32;
-
-                            ui.strong("Armory");
-                            ui.label(format!("Level: {}", player.constructions.armory));
-                            ui.label(format!("Armorers: {}", player.population.armorer));
-                            ui.label(format!("Production: +{:.0}", new_resources.bullets)).on_hover_text("Production of bullets per night.");
-                            ui.add_space(10.);
+                    ScrollArea::vertical()
+                        .id_salt("constructions")
+                        .max_width(MAP_SIZE.x * 0.6)
+                        .max_height(MAP_SIZE.y * 0.65)
+                        .show(ui, |ui| {
+                            ui.add_space(5.);
                             ui.horizontal(|ui| {
-                                let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to increase bullet production.");
-                                ui.strong(format!("{}", cost));
-                                ui.add_image(materials_texture, [20., 20.]);
-                                if button.clicked() {
-                                    if player.resources.materials >= cost {
-                                        player.resources.materials -= cost;
-                                        player.constructions.armory += 1;
-                                    } else {
-                                        messages.error("Not enough materials.");
-                                    }
-                                }
+                                ui.add_space(20.);
+                                ui.heading("Resources");
                             });
-                        });
-
-                        ui.add_space(40.);
+                            ui.add_space(15.);
 
-                        ui.add_image(refinery_texture, [130., 130.]);
-                        ui.add_space(20.);
-                        ui.vertical(|ui| {
-                            let cost = ((player.constructions.refinery + 1) * 100) as f32;
+                            let frame = egui::Frame::none()
+                                .fill(Color32::from_black_alpha(190))
+                                .rounding(5.0)
+                                .inner_margin(egui::vec2(10., 5.));
 
-                            ui.strong("Refinery");
-                            ui.label(format!("Level: {}", player.constructions.refinery));
-                            ui.label(format!("Refiners: {}", player.population.refiner));
-                            ui.label(format!("Production: +{:.0}", new_resources.gasoline)).on_hover_text("Production of gasoline per night.");
-                            ui.add_space(10.);
                             ui.horizontal(|ui| {
-                                let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to increase gasoline production.");
-                                ui.strong(format!("{}", cost));
-                                ui.add_image(materials_texture, [20., 20.]);
-                                if button.clicked() {
-                                    if player.resources.materials >= cost {
-                                        player.resources.materials -= cost;
-                                        player.constructions.refinery += 1;
-                                    } else {
-                                        messages.error("Not enough materials.");
-                                    }
-                                }
-                            });
-                        });
-                    });
-
-                    ui.add_space(35.);
+                                ui.add_space(30.);
+                                ui.add_image(armory_texture, [130., 130.]);
+                                ui.add_space(20.);
+                                ui.vertical(|ui| {
+                                    let cost = ((player.constructions.armory + 1) * 100) as f32;
+
+                                    ui.strong("Armory");
+                                    ui.label(format!("Level: {}", player.constructions.armory));
+                                    ui.label(format!("Armorers: {}", player.population.armorer));
+                                    ui.label(format!("Production: +{:.0}", new_resources.bullets)).on_hover_text("Production of bullets per night.");
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to increase bullet production.");
+                                        ui.strong(format!("{}", cost));
+                                        ui.add_image(materials_texture, [20., 20.]);
+                                        if button.clicked() {
+                                            if player.resources.materials >= cost {
+                                                player.resources.materials -= cost;
+                                                player.constructions.armory += 1;
+                                            } else {
+                                                messages.error("Not enough materials.");
+                                            }
+                                        }
+                                    });
+                                });
 
-                    ui.horizontal(|ui| {
-                        ui.add_space(30.);
-                        ui.add_image(factory_texture, [130., 130.]);
-                        ui.add_space(20.);
-                        ui.vertical(|ui| {
-                            let cost = ((player.constructions.factory + 1) * 100) as f32;
+                                ui.add_space(40.);
 
-                            ui.strong("Factory");
-                            ui.label(format!("Level: {}", player.constructions.factory));
-                            ui.label(format!("Constructors: {}", player.population.constructor));
-                            ui.label(format!("Production: +{:.0}", new_resources.materials)).on_hover_text("Production of materials per night.");
-                            ui.add_space(10.);
-                            ui.horizontal(|ui| {
-                                let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to increase materials production.");
-                                ui.strong(format!("{}", cost));
-                                ui.add_image(materials_texture, [20., 20.]);
-                                if button.clicked() {
-                                    if player.resources.materials >= cost {
-                                        player.resources.materials -= cost;
-                                        player.constructions.factory += 1;
-                                    } else {
-                                        messages.error("Not enough materials.");
-                                    }
-                                }
+                                ui.add_image(refinery_texture, [130., 130.]);
+                                ui.add_space(20.);
+                                ui.vertical(|ui| {
+                                    let cost = ((player.constructions.refinery + 1) * 100) as f32;
+
+                                    ui.strong("Refinery");
+                                    ui.label(format!("Level: {}", player.constructions.refinery));
+                                    ui.label(format!("Refiners: {}", player.population.refiner));
+                                    ui.label(format!("Production: +{:.0}", new_resources.gasoline)).on_hover_text("Production of gasoline per night.");
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to increase gasoline production.");
+                                        ui.strong(format!("{}", cost));
+                                        ui.add_image(materials_texture, [20., 20.]);
+                                        if button.clicked() {
+                                            if player.resources.materials >= cost {
+                                                player.resources.materials -= cost;
+                                                player.constructions.refinery += 1;
+                                            } else {
+                                                messages.error("Not enough materials.");
+                                            }
+                                        }
+                                    });
+                                });
                             });
-                        });
 
-                        ui.add_space(40.);
-
-                        ui.add_image(laboratory_texture, [130., 130.]);
-                        ui.add_space(20.);
-                        ui.vertical(|ui| {
-                            let cost = ((player.constructions.laboratory + 1) * 100) as f32;
+                            ui.add_space(35.);
 
-                            ui.strong("Laboratory");
-                            ui.label(format!("Level: {}", player.constructions.laboratory));
-                            ui.label(format!("Scientists: {}", player.population.scientist));
-                            ui.label(format!("Production: +{:.0}", new_resources.technology)).on_hover_text("Production of technology per night.");
-                            ui.add_space(10.);
                             ui.horizontal(|ui| {
-                                let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to increase technology production.");
-                                ui.strong(format!("{}", cost));
-                                ui.add_image(materials_texture, [20., 20.]);
-                                if button.clicked() {
-                                    if player.resources.materials >= cost {
-                                        player.resources.materials -= cost;
-                                        player.constructions.laboratory += 1;
-                                    } else {
-                                        messages.error("Not enough materials.");
-                                    }
-                                }
-                            });
-                        });
-                    });
-
-                    ui.add_space(35.);
-
-                    ui.horizontal(|ui| {
-                        ui.add_space(20.);
-                        ui.heading("Defense");
-                    });
-                    ui.add_space(15.);
-
-                    ui.horizontal(|ui| {
-                        ui.add_space(30.);
-                        ui.vertical(|ui| {
-                            ui.add_space(20.);
-                            ui.add_image(wall_texture, [130., 130.]);
+                                ui.add_space(30.);
+                                ui.add_image(factory_texture, [130., 130.]);
+                                ui.add_space(20.);
+                                ui.vertical(|ui| {
+                                    let cost = ((player.constructions.factory + 1) * 100) as f32;
+
+                                    ui.strong("Factory");
+                                    ui.label(format!("Level: {}", player.constructions.factory));
+                                    ui.label(format!("Constructors: {}", player.population.constructor));
+                                    ui.label(format!("Production: +{:.0}", new_resources.materials)).on_hover_text("Production of materials per night.");
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to increase materials production.");
+                                        ui.strong(format!("{}", cost));
+                                        ui.add_image(materials_texture, [20., 20.]);
+                                        if button.clicked() {
+                                            if player.resources.materials >= cost {
+                                                player.resources.materials -= cost;
+                                                player.constructions.factory += 1;
+                                            } else {
+                                                messages.error("Not enough materials.");
+                                            }
+                                        }
+                                    });
+                                });
 
-                            ui.add_space(-30.);
-                            ui.horizontal(|ui| {
-                                frame.show(ui, |ui| {
-                                    ui.add_image(spots_texture, [25., 25.]);
-                                    ui.strong(format!("{} / {}", player.weapons.spots.len(), MAX_SPOTS));
-                                }).response.on_hover_text("Available spots / maximum spots.");
-                            });
-                        });
-                        ui.add_space(20.);
-                        ui.vertical(|ui| {
-                            ui.strong("Wall");
-                            ui.add_space(10.);
-                            ui.horizontal(|ui| {
-                                let cost = player.wall.max_health * 0.5;
-                                let increase = 1000.;
+                                ui.add_space(40.);
 
-                                let button = ui.add_upgrade_button(up_texture).on_hover_text(format!("Increase the max health with {increase}."));
-                                ui.strong(format!("{}", cost));
-                                ui.add_image(materials_texture, [20., 20.]);
-                                if button.clicked() {
-                                    if player.resources.materials >= cost {
-                                        player.resources.materials -= cost;
-                                        player.wall.health += increase;
-                                        player.wall.max_health += increase;
-                                    } else {
-                                        messages.error("Not enough materials.");
-                                    }
-                                }
+                                ui.add_image(laboratory_texture, [130., 130.]);
+                                ui.add_space(20.);
+                                ui.vertical(|ui| {
+                                    let cost = ((player.constructions.laboratory + 1) * 100) as f32;
+
+                                    ui.strong("Laboratory");
+                                    ui.label(format!("Level: {}", player.constructions.laboratory));
+                                    ui.label(format!("Scientists: {}", player.population.scientist));
+                                    ui.label(format!("Production: +{:.0}", new_resources.technology)).on_hover_text("Production of technology per night.");
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to increase technology production.");
+                                        ui.strong(format!("{}", cost));
+                                        ui.add_image(materials_texture, [20., 20.]);
+                                        if button.clicked() {
+                                            if player.resources.materials >= cost {
+                                                player.resources.materials -= cost;
+                                                player.constructions.laboratory += 1;
+                                            } else {
+                                                messages.error("Not enough materials.");
+                                            }
+                                        }
+                                    });
+                                });
                             });
-                            ui.add_space(10.);
-                            ui.horizontal(|ui| {
-                                ui.add_enabled_ui(player.wall.health < player.wall.max_health, |ui| {
-                                    let cost = 100.;
-                                    let increase = 500.;
 
-                                    let button = ui.add_upgrade_button(repair_texture)
-                                        .on_hover_text(format!("Add {increase} health to the wall. Double click to repair to full health."));
-                                    ui.strong(format!("{}", cost));
-                                    ui.add_image(materials_texture, [20., 20.]);
-                                    if button.clicked() {
-                                        if player.resources.materials >= cost {
-                                            player.resources.materials -= cost;
-                                            player.wall.health += increase;
-                                        } else {
-                                            messages.error("Not enough materials.");
-                                        }
-                                    }
+                            ui.add_space(35.);
 
-                                    // Double-click to repair to full health
-                                    if button.double_clicked() {
-                                        let cost = ((player.wall.max_health - player.wall.health) as u32 + 499 / 5) as f32;
-                                        if player.resources.materials >= cost {
-                                            player.resources.materials -= cost;
-                                            player.wall.health = player.wall.max_health;
-                                        } else {
-                                            messages.error("Not enough materials.");
+                            ui.horizontal(|ui| {
+                                ui.add_space(30.);
+                                ui.add_image(housing_texture, [130., 130.]);
+                                ui.add_space(20.);
+                                ui.vertical(|ui| {
+                                    let cost = ((player.constructions.housing + 1) * 100) as f32;
+
+                                    ui.strong("Housing");
+                                    ui.label(format!("Level: {}", player.constructions.housing));
+                                    ui.label(format!("Population: {}", player.population.total()));
+                                    ui.label(format!("Capacity: {}", player.housing_capacity())).on_hover_text("Survivors that arrive when the fortress is full are turned away.");
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to house more population.");
+                                        ui.strong(format!("{}", cost));
+                                        ui.add_image(materials_texture, [20., 20.]);
+                                        if button.clicked() {
+                                            if player.resources.materials >= cost {
+                                                player.resources.materials -= cost;
+                                                player.constructions.housing += 1;
+                                            } else {
+                                                messages.error("Not enough materials.");
+                                            }
                                         }
-                                    }
+                                    });
                                 });
-                            });
-                            ui.add_space(10.);
-                            ui.add_enabled_ui(player.weapons.spots.len() < MAX_SPOTS as usize, |ui| {
-                                ui.horizontal(|ui| {
-                                    let cost = 500.;
 
-                                    let button = ui.add_upgrade_button(spots_texture)
-                                        .on_hover_text("Add an extra weapon on the wall.")
-                                        .on_disabled_hover_text("Maximum number of weapons reached.");
-                                    ui.strong(format!("{}", cost));
-                                    ui.add_image(technology_texture, [20., 20.]);
-                                    if button.clicked() {
-                                        if player.resources.technology >= cost {
-                                            player.resources.technology -= cost;
-                                            player.weapons.spots.push(Spot { id: Uuid::new_v4(), weapon: None });
-                                        } else {
-                                            messages.error("Not enough technology.");
+                                ui.add_space(40.);
+
+                                ui.add_image(hospital_texture, [130., 130.]);
+                                ui.add_space(20.);
+                                ui.vertical(|ui| {
+                                    let cost = ((player.constructions.hospital + 1) * 150) as f32;
+
+                                    ui.strong("Hospital");
+                                    ui.label(format!("Level: {}", player.constructions.hospital));
+                                    ui.label(format!("Wounded: {}", player.population.wounded));
+                                    ui.label(format!("Capacity: {}", player.hospital_capacity())).on_hover_text("Wounded that don't fit in the hospital don't recover, and some of them die every day.");
+                                    ui.label(format!("Recovery: {:.0}%", (HOSPITAL_RECOVERY * player.constructions.hospital as f32).min(1.) * 100.)).on_hover_text("Fraction of the treated wounded that recover per day.");
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        let button = ui.add_upgrade_button(up_texture).on_hover_text("Upgrade to treat more wounded and recover faster.");
+                                        ui.strong(format!("{}", cost));
+                                        ui.add_image(materials_texture, [20., 20.]);
+                                        if button.clicked() {
+                                            if player.resources.materials >= cost {
+                                                player.resources.materials -= cost;
+                                                player.constructions.hospital += 1;
+                                            } else {
+                                                messages.error("Not enough materials.");
+                                            }
                                         }
-                                    }
+                                    });
                                 });
                             });
 
-                            spawn_wall(&mut commands, &wall_q, &player, &asset_server);
+                            ui.add_space(35.);
 
-                            if player.wall.health > player.wall.max_health {
-                                player.wall.health = player.wall.max_health;
-                            }
-                        });
+                            ui.horizontal(|ui| {
+                                ui.add_space(20.);
+                                ui.heading("Defense");
+                            });
+                            ui.add_space(15.);
 
-                        ui.add_space(40.);
+                            ui.horizontal(|ui| {
+                                ui.add_space(30.);
+                                ui.vertical(|ui| {
+                                    ui.add_space(20.);
+                                    ui.add_image(wall_texture, [130., 130.]);
+
+                                    ui.add_space(-30.);
+                                    ui.horizontal(|ui| {
+                                        frame.show(ui, |ui| {
+                                            ui.add_image(spots_texture, [25., 25.]);
+                                            ui.strong(format!("{} / {}", player.weapons.spots.len(), MAX_SPOTS));
+                                        }).response.on_hover_text("Available spots / maximum spots.");
+                                    });
+                                });
+                                ui.add_space(20.);
+                                ui.vertical(|ui| {
+                                    ui.strong("Wall");
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        let cost = player.wall.max_health * 0.5;
+                                        let increase = 1000.;
+
+                                        let button = ui.add_upgrade_button(up_texture).on_hover_text(format!("Increase the max health with {increase}."));
+                                        ui.strong(format!("{}", cost));
+                                        ui.add_image(materials_texture, [20., 20.]);
+                                        if button.clicked() {
+                                            if player.resources.materials >= cost {
+                                                player.resources.materials -= cost;
+                                                player.wall.health += increase;
+                                                player.wall.max_health += increase;
+                                            } else {
+                                                messages.error("Not enough materials.");
+                                            }
+                                        }
+                                    });
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        ui.add_enabled_ui(player.wall.health < player.wall.max_health, |ui| {
+                                            let cost = 100.;
+                                            let increase = 500.;
+
+                                            let button = ui.add_upgrade_button(repair_texture)
+                                                .on_hover_text(format!("Add {increase} health to the wall. Double click to repair to full health."));
+                                            ui.strong(format!("{}", cost));
+                                            ui.add_image(materials_texture, [20., 20.]);
+                                            if button.clicked() {
+                                                if player.resources.materials >= cost {
+                                                    player.resources.materials -= cost;
+                                                    player.wall.health += increase;
+                                                } else {
+                                                    messages.error("Not enough materials.");
+                                                }
+                                            }
 
-                        ui.vertical(|ui| {
-                            ui.add_space(20.);
-                            ui.add_image(fence_texture, [130., 130.]);
+                                            // Double-click to repair to full health
+                                            if button.double_clicked() {
+                                                let cost = ((player.wall.max_health - player.wall.health) as u32 + 499 / 5) as f32;
+                                                if player.resources.materials >= cost {
+                                                    player.resources.materials -= cost;
+                                                    player.wall.health = player.wall.max_health;
+                                                } else {
+                                                    messages.error("Not enough materials.");
+                                                }
+                                            }
+                                        });
+                                    });
+                                    ui.add_space(10.);
+                                    ui.add_enabled_ui(player.weapons.spots.len() < MAX_SPOTS as usize, |ui| {
+                                        ui.horizontal(|ui| {
+                                            let cost = 500.;
+
+                                            let button = ui.add_upgrade_button(spots_texture)
+                                                .on_hover_text("Add an extra weapon on the wall.")
+                                                .on_disabled_hover_text("Maximum number of weapons reached.");
+                                            ui.strong(format!("{}", cost));
+                                            ui.add_image(technology_texture, [20., 20.]);
+                                            if button.clicked() {
+                                                if player.resources.technology >= cost {
+                                                    player.resources.technology -= cost;
+                                                    player.weapons.spots.push(Spot::new(None));
+                                                } else {
+                                                    messages.error("Not enough technology.");
+                                                }
+                                            }
+                                        });
+                                    });
 
-                            if player.fence.max_health > 0. && player.has_tech(TechnologyName::Electricity) {
-                                ui.add_space(-30.);
-                                ui.horizontal(|ui| {
-                                    frame.show(ui, |ui| {
-                                        ui.add_image(lightning_texture, [25., 25.]);
-                                        ui.strong(format!("x{}", player.fence.damage));
-                                    }).response.on_hover_text("Damage to adjacent enemies.");
-                                });
-                            }
-                        });
-                        ui.add_space(20.);
-                        ui.vertical(|ui| {
-                            ui.strong("Fence");
-                            ui.add_space(10.);
-                            ui.horizontal(|ui| {
-                                let cost = 100. + player.fence.max_health;
-                                let increase = cost.min(1000.);
+                                    spawn_wall(&mut commands, &wall_q, &player, &asset_server);
 
-                                let button = ui.add_upgrade_button(up_texture).on_hover_text(format!("Increase the max health with {increase}."));
-                                ui.strong(format!("{}", cost));
-                                ui.add_image(materials_texture, [20., 20.]);
-                                if button.clicked() {
-                                    if player.resources.materials >= cost {
-                                        player.resources.materials -= cost;
-                                        player.fence.health += increase;
-                                        player.fence.max_health += increase;
-                                    } else {
-                                        messages.error("Not enough materials.");
+                                    if player.wall.health > player.wall.max_health {
+                                        player.wall.health = player.wall.max_health;
                                     }
-                                }
-                            });
-                            ui.add_space(10.);
-                            ui.horizontal(|ui| {
-                                ui.add_enabled_ui(player.fence.health < player.fence.max_health, |ui| {
-                                    let cost = 100.;
-                                    let increase = 300.;
+                                });
 
-                                    let button = ui.add_upgrade_button(repair_texture)
-                                        .on_hover_text(format!("Add {increase} health to the fence. Double click to repair to full health."));
-                                    ui.strong(format!("{}", cost));
-                                    ui.add_image(materials_texture, [20., 20.]);
-                                    if button.clicked() {
-                                        if player.resources.materials >= cost {
-                                            player.resources.materials -= cost;
-                                            player.fence.health += increase;
-                                        } else {
-                                            messages.error("Not enough materials.");
-                                        }
-                                    }
+                                ui.add_space(40.);
 
-                                    // Double-click to repair to full health
-                                    if button.double_clicked() {
-                                        let cost = ((player.fence.max_health - player.fence.health) as u32 + 299 / 3) as f32;
-                                        if player.resources.materials >= cost {
-                                            player.resources.materials -= cost;
-                                            player.fence.health = player.fence.max_health;
-                                        } else {
-                                            messages.error("Not enough materials.");
-                                        }
+                                ui.vertical(|ui| {
+                                    ui.add_space(20.);
+                                    ui.add_image(fence_texture, [130., 130.]);
+
+                                    if player.fence.max_health > 0. && player.has_tech(TechnologyName::Electricity) {
+                                        ui.add_space(-30.);
+                                        ui.horizontal(|ui| {
+                                            frame.show(ui, |ui| {
+                                                ui.add_image(lightning_texture, [25., 25.]);
+                                                ui.strong(format!("x{}", player.fence.damage));
+                                            }).response.on_hover_text("Damage to adjacent enemies.");
+                                        });
                                     }
                                 });
-                            });
-                            ui.add_space(10.);
-                            ui.add_enabled_ui(player.fence.max_health > 0. && player.has_tech(TechnologyName::Electricity), |ui| {
-                                ui.horizontal(|ui| {
-                                    let cost = player.fence.damage * 20.;
-                                    let increase = 5.;
-
-                                    let button = ui.add_upgrade_button(lightning_texture)
-                                        .on_hover_text(format!("Increase the fence's damage with {increase}."))
-                                        .on_disabled_hover_text("Requires the electricity technology.");
-                                    ui.strong(format!("{}", cost));
-                                    ui.add_image(technology_texture, [20., 20.]);
-                                    if button.clicked() {
-                                        if player.resources.technology >= cost {
-                                            player.resources.technology -= cost;
-                                            player.fence.damage += increase;
-                                        } else {
-                                            messages.error("Not enough technology.");
+                                ui.add_space(20.);
+                                ui.vertical(|ui| {
+                                    ui.strong("Fence");
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        let cost = 100. + player.fence.max_health;
+                                        let increase = cost.min(1000.);
+
+                                        let button = ui.add_upgrade_button(up_texture).on_hover_text(format!("Increase the max health with {increase}."));
+                                        ui.strong(format!("{}", cost));
+                                        ui.add_image(materials_texture, [20., 20.]);
+                                        if button.clicked() {
+                                            if player.resources.materials >= cost {
+                                                player.resources.materials -= cost;
+                                                player.fence.health += increase;
+                                                player.fence.max_health += increase;
+                                            } else {
+                                                messages.error("Not enough materials.");
+                                            }
                                         }
+                                    });
+                                    ui.add_space(10.);
+                                    ui.horizontal(|ui| {
+                                        ui.add_enabled_ui(player.fence.health < player.fence.max_health, |ui| {
+                                            let cost = 100.;
+                                            let increase = 300.;
+
+                                            let button = ui.add_upgrade_button(repair_texture)
+                                                .on_hover_text(format!("Add {increase} health to the fence. Double click to repair to full health."));
+                                            ui.strong(format!("{}", cost));
+                                            ui.add_image(materials_texture, [20., 20.]);
+                                            if button.clicked() {
+                                                if player.resources.materials >= cost {
+                                                    player.resources.materials -= cost;
+                                                    player.fence.health += increase;
+                                                } else {
+                                                    messages.error("Not enough materials.");
+                                                }
+                                            }
+
+                                            // Double-click to repair to full health
+                                            if button.double_clicked() {
+                                                let cost = ((player.fence.max_health - player.fence.health) as u32 + 299 / 3) as f32;
+                                                if player.resources.materials >= cost {
+                                                    player.resources.materials -= cost;
+                                                    player.fence.health = player.fence.max_health;
+                                                } else {
+                                                    messages.error("Not enough materials.");
+                                                }
+                                            }
+                                        });
+                                    });
+                                    ui.add_space(10.);
+                                    ui.add_enabled_ui(player.fence.max_health > 0. && player.has_tech(TechnologyName::Electricity), |ui| {
+                                        ui.horizontal(|ui| {
+                                            let cost = player.fence.damage * 20.;
+                                            let increase = 5.;
+
+                                            let button = ui.add_upgrade_button(lightning_texture)
+                                                .on_hover_text(format!("Increase the fence's damage with {increase}."))
+                                                .on_disabled_hover_text("Requires the electricity technology.");
+                                            ui.strong(format!("{}", cost));
+                                            ui.add_image(technology_texture, [20., 20.]);
+                                            if button.clicked() {
+                                                if player.resources.technology >= cost {
+                                                    player.resources.technology -= cost;
+                                                    player.fence.damage += increase;
+                                                } else {
+                                                    messages.error("Not enough technology.");
+                                                }
+                                            }
+                                        });
+                                    });
+
+                                    spawn_fence(&mut commands, &fence_q, &player, &asset_server);
+
+                                    if player.fence.health > player.fence.max_health {
+                                        player.fence.health = player.fence.max_health;
                                     }
                                 });
                             });
 
-                            spawn_fence(&mut commands, &fence_q, &player, &asset_server);
+                            ui.add_space(35.);
 
-                            if player.fence.health > player.fence.max_health {
-                                player.fence.health = player.fence.max_health;
-                            }
+                            ui.horizontal(|ui| {
+                                ui.add_space(30.);
+                                ui.add_image(drone_texture, [130., 130.]);
+                                ui.add_space(20.);
+                                ui.vertical(|ui| {
+                                    ui.strong("Drone bay");
+                                    ui.label(format!("Level: {}", player.constructions.drone_bay));
+                                    ui.label(format!("Drones: {} / {}", player.weapons.drones, player.constructions.drone_bay))
+                                        .on_hover_text("Drones patrol the map during the night and return to their bay to rearm.");
+                                    ui.add_space(10.);
+                                    ui.add_enabled_ui(player.constructions.drone_bay < MAX_DRONES, |ui| {
+                                        ui.horizontal(|ui| {
+                                            let cost = ((player.constructions.drone_bay + 1) * 300) as f32;
+
+                                            let button = ui.add_upgrade_button(up_texture)
+                                                .on_hover_text("Upgrade to house an extra drone.")
+                                                .on_disabled_hover_text("Maximum level reached.");
+                                            ui.strong(format!("{}", cost));
+                                            ui.add_image(materials_texture, [20., 20.]);
+                                            if button.clicked() {
+                                                if player.resources.materials >= cost {
+                                                    player.resources.materials -= cost;
+                                                    player.constructions.drone_bay += 1;
+                                                } else {
+                                                    messages.error("Not enough materials.");
+                                                }
+                                            }
+                                        });
+                                    });
+                                    ui.add_space(10.);
+                                    ui.add_enabled_ui(player.weapons.drones < player.constructions.drone_bay, |ui| {
+                                        ui.horizontal(|ui| {
+                                            let cost = 200.;
+
+                                            let button = ui.add_upgrade_button(drone_texture)
+                                                .on_hover_text("Build a drone.")
+                                                .on_disabled_hover_text("Upgrade the drone bay to house more drones.");
+                                            ui.strong(format!("{}", cost));
+                                            ui.add_image(technology_texture, [20., 20.]);
+                                            if button.clicked() {
+                                                if player.resources.technology >= cost {
+                                                    player.resources.technology -= cost;
+                                                    player.weapons.drones += 1;
+                                                } else {
+                                                    messages.error("Not enough technology.");
+                                                }
+                                            }
+                                        });
+                                    });
+                                });
+                            });
                         });
-                    });
                 }
                 DayTabs::Armory => {
                     let frame = egui::Frame::none()
@@ -1506,8 +1860,11 @@ pub fn day_panel(
                     let textures: HashMap<&str, TextureId> = HashMap::from([
                         ("MachineGun", machine_gun_texture),
                         ("Canon", canon_texture),
+                        ("Cryo", cryo_texture),
                         ("Flamethrower", flamethrower_texture),
                         ("MissileLauncher", missile_launcher_texture),
+                        ("Sniper", sniper_texture),
+                        ("Tesla", tesla_texture),
                         ("Turret", turret_texture),
                         ("Artillery", artillery_texture),
                         ("AAA", aaa_texture),
@@ -1521,6 +1878,9 @@ pub fn day_panel(
                         ("range", range_texture),
                         ("penetration", penetration_texture),
                         ("targets", targets_texture),
+                        ("bullets", bullets_texture),
+                        ("clock", clock_texture),
+                        ("hourglass", hourglass_texture),
                         ("materials", materials_texture),
                         ("technology", technology_texture),
                     ]);
@@ -1568,6 +1928,21 @@ pub fn day_panel(
                                 });
                             }).response.on_disabled_hover_text("Requires the homing technology.");
 
+                            ui.add_space(30.);
+                            ui.horizontal(|ui| {
+                                ui.add_space(70.);
+                                ui.add_weapon(&textures, &mut weapons.sniper, &mut player, &mut messages);
+                                ui.add_space(70.);
+                                ui.add_enabled_ui(player.has_tech(TechnologyName::Electricity), |ui| {
+                                    ui.add_weapon(&textures, &mut weapons.tesla, &mut player, &mut messages);
+                                }).response.on_disabled_hover_text("Requires the electricity technology.");
+                            });
+                            ui.add_space(30.);
+                            ui.horizontal(|ui| {
+                                ui.add_space(70.);
+                                ui.add_weapon(&textures, &mut weapons.cryo, &mut player, &mut messages);
+                            });
+
                             ui.add_space(35.);
                             ui.horizontal(|ui| {
                                 ui.add_space(20.);
@@ -1583,9 +1958,9 @@ pub fn day_panel(
                                         ui.add_space(20.);
                                         ui.add_image(mine_texture, [50., 30.])
                                             .on_hover_text("\
-                                                Small explosive placed at random locations. Explodes when an \
-                                                enemy walks over (is never triggered by flying enemies). You \
-                                                can decide at which enemy size to detonate. Maximum of 25 allowed.")
+                                                Small explosive placed on the map. Explodes when an enemy \
+                                                walks over (is never triggered by flying enemies). You can \
+                                                decide at which enemy size to detonate. Maximum of 25 allowed.")
                                             .on_disabled_hover_text("Requires the explosives technology.");
 
                                         ui.add_space(-10.);
@@ -1620,6 +1995,15 @@ pub fn day_panel(
                                                 }
                                             }
                                         });
+
+                                        ui.add_space(5.);
+                                        if ui
+                                            .add_enabled(player.weapons.mines > 0, egui::Button::new("Place"))
+                                            .on_hover_text("Place the mines on the map. Mines that aren't placed are scattered at random locations at night.")
+                                            .clicked()
+                                        {
+                                            game_settings.targeting = Some(Targeting::Mine);
+                                        }
                                     });
                                     ui.vertical(|ui| {
                                         ui.strong("Mine");
@@ -1745,6 +2129,14 @@ pub fn day_panel(
                 ui.horizontal(|ui| {
                     ui.add_space(20.);
                     ui.heading("Wall");
+                    ui.add_space(10.);
+                    if ui
+                        .button("Place")
+                        .on_hover_text("Drag the weapons on the map to choose their position on the wall or the fence.")
+                        .clicked()
+                    {
+                        game_settings.targeting = Some(Targeting::Weapons);
+                    }
                 });
 
                 ui.add_space(15.);
@@ -1757,64 +2149,152 @@ pub fn day_panel(
                                     Some(WeaponName::AAA) => aaa_texture,
                                     Some(WeaponName::Artillery) => artillery_texture,
                                     Some(WeaponName::Canon) => canon_texture,
+                                    Some(WeaponName::Cryo) => cryo_texture,
                                     Some(WeaponName::Flamethrower) => flamethrower_texture,
                                     Some(WeaponName::MachineGun) => machine_gun_texture,
                                     Some(WeaponName::MissileLauncher) => missile_launcher_texture,
                                     Some(WeaponName::Mortar) => mortar_texture,
+                                    Some(WeaponName::Sniper) => sniper_texture,
+                                    Some(WeaponName::Tesla) => tesla_texture,
                                     Some(WeaponName::Turret) => turret_texture,
                                     None => cross_texture,
                                 };
 
                                 let response = ui.add_image(texture, [50., 50.]);
                                 if let Some(w) = item.weapon {
-                                    response.on_hover_text(w.name());
+                                    let max_health = weapons.get(&w).max_health;
+                                    response.on_hover_text(format!(
+                                        "{}\nHealth: {:.0}/{:.0}\nRank: {} ({} kills)",
+                                        w.name(),
+                                        max_health - item.damage,
+                                        max_health,
+                                        item.rank(),
+                                        item.kills
+                                    ));
                                 }
                             });
 
                             ui.add_space(10.);
                         });
 
+                        // Repair all the damaged weapons at once
+                        let damage = player.weapons.spots.iter().map(|s| s.damage).sum::<f32>();
+                        ui.add_enabled_ui(damage > 0., |ui| {
+                            let cost = (damage * WEAPON_REPAIR_COST).ceil();
+
+                            let button = ui.add_upgrade_button(repair_texture)
+                                .on_hover_text("Repair the weapons on the wall. Destroyed weapons don't fire until repaired.");
+                            ui.strong(format!("{}", cost));
+                            ui.add_image(materials_texture, [20., 20.]);
+                            if button.clicked() {
+                                if player.resources.materials >= cost {
+                                    player.resources.materials -= cost;
+                                    player.weapons.spots.iter_mut().for_each(|s| s.damage = 0.);
+                                } else {
+                                    messages.error("Not enough materials.");
+                                }
+                            }
+                        });
+
                         spawn_spots(&mut commands, &weapon_q, &player, &weapons, &asset_server);
                     });
                 }
                 DayTabs::Technology => {
-                    for category in TechnologyCategory::iter() {
-                        ui.add_space(5.);
+                    ui.add_space(10.);
+
+                    ui.add_scroll("tech", MAP_SIZE.x * 0.02, |ui| {
                         ui.horizontal(|ui| {
-                            ui.add_space(20.);
-                            ui.heading(category.name());
+                            ui.strong("Research queue:");
+                            if player.research.queue.is_empty() {
+                                ui.label("Nothing is being researched.");
+                            }
+                            for tech in player.research.queue.iter() {
+                                ui.label(format!(
+                                    "{} ({} days)",
+                                    tech.name(),
+                                    player.research_eta(*tech).unwrap_or_default()
+                                ));
+                            }
                         });
-                        ui.add_space(15.);
+                        ui.label(format!(
+                            "Scientists produce {:.0} research points per day. Click on a queued technology to cancel it.",
+                            player.research_rate()
+                        ));
 
-                        ui.horizontal(|ui| {
-                            for t in Technology::iter().filter(|t| t.category == category) {
-                                ui.add_space(10.);
+                        for category in TechnologyCategory::iter() {
+                            ui.add_space(5.);
+                            ui.horizontal(|ui| {
+                                ui.add_space(20.);
+                                ui.heading(category.name());
+                            });
+                            ui.add_space(15.);
 
-                                ui.add_enabled_ui(!player.has_tech(t.name), |ui| {
-                                    let response = ui.add_technology(
-                                        &t,
-                                        &player,
-                                        technology_texture,
-                                        tick_texture,
-                                    );
-                                    if response.clicked() {
-                                        if player.resources.technology >= t.price {
-                                            player.resources.technology -= t.price;
-                                            player.technology.insert(t.name);
-                                            messages.info(format!(
-                                                "Technology {} researched.",
-                                                t.name.name()
-                                            ));
-                                        } else {
-                                            messages.error("Not enough resources!");
+                            // Reserve a shape behind the cards to draw the prerequisite lines
+                            let lines = ui.painter().add(egui::Shape::Noop);
+                            let mut rects = HashMap::new();
+
+                            ui.horizontal(|ui| {
+                                let technologies: Vec<_> = Technology::iter().filter(|t| t.category == category).collect();
+                                let tiers = technologies.iter().map(|t| t.tier).max().unwrap_or_default();
+
+                                for tier in 1..=tiers {
+                                    ui.add_space(if tier == 1 { 10. } else { 50. });
+
+                                    ui.vertical(|ui| {
+                                        for t in technologies.iter().filter(|t| t.tier == tier) {
+                                            let enabled = !player.has_tech(t.name) && (player.is_researching(t.name) || player.can_research(t.name));
+                                            let requires = t.requires.iter().map(|t| t.name()).collect::<Vec<_>>().join(" and ");
+
+                                            let response = ui.add_enabled_ui(enabled, |ui| {
+                                                ui.add_technology(
+                                                    t,
+                                                    &player,
+                                                    technology_texture,
+                                                    tick_texture,
+                                                )
+                                            });
+
+                                            if !player.has_tech(t.name) {
+                                                response.response.on_disabled_hover_text(format!("Requires {requires}."));
+                                            }
+                                            rects.insert(t.name, response.inner.rect);
+
+                                            if response.inner.clicked() {
+                                                if player.is_researching(t.name) {
+                                                    player.cancel_research(t.name);
+                                                    messages.info(format!("Research of {} cancelled.", t.name.name()));
+                                                } else if player.resources.technology >= t.price {
+                                                    player.resources.technology -= t.price;
+                                                    player.research.queue.push_back(t.name);
+                                                    messages.info(format!("Technology {} added to the research queue.", t.name.name()));
+                                                } else {
+                                                    messages.error("Not enough resources!");
+                                                }
+                                            }
+
+                                            ui.add_space(10.);
                                         }
-                                    }
-                                });
-                            }
-                        });
+                                    });
+                                }
+                            });
 
-                        ui.add_space(15.);
-                    }
+                            let stroke = egui::Stroke::new(2., Color32::GRAY);
+                            let shapes = Technology::iter()
+                                .filter(|t| t.category == category)
+                                .flat_map(|t| {
+                                    let to = rects[&t.name];
+                                    t.requires
+                                        .iter()
+                                        .filter_map(|r| rects.get(r))
+                                        .map(|from| egui::Shape::line_segment([from.right_center(), to.left_center()], stroke))
+                                        .collect::<Vec<_>>()
+                                })
+                                .collect();
+                            ui.painter().set(lines, egui::Shape::Vec(shapes));
+
+                            ui.add_space(15.);
+                        }
+                    });
                 }
                 DayTabs::Expeditions => {
                     ui.add_space(10.);
@@ -1823,57 +2303,223 @@ pub fn day_panel(
                         ui.add_space(15.);
                         ui.label(
                             "\
-                            Send expeditions to explore the surroundings. The larger the \
-                            expedition, the more resources it costs and the longer it takes \
-                            to return, but the larger the possible rewards. But be aware, \
-                            some expeditions never return...",
+                            Send expeditions to explore the surroundings. Every destination \
+                            has its own risks and rewards. The larger the escort, the more it \
+                            costs but the more likely it returns with a larger reward. Soldiers \
+                            protect the expedition, but they can't defend the fortress while \
+                            away. Be aware, some expeditions never return...",
                         );
-                        ui.add_space(25.);
+                        ui.add_space(15.);
                     });
 
-                    let textures = HashMap::from([
-                        ("population", population_texture),
-                        ("gasoline", gasoline_texture),
-                        ("materials", materials_texture),
-                        ("clock", clock_texture),
-                    ]);
+                    ui.horizontal(|ui| {
+                        ui.add_space(20.);
+
+                        if let Some(name) = ui.add_expedition_map(&player, game_settings.expedition.destination) {
+                            game_settings.expedition.destination = name;
+                        }
+
+                        ui.add_space(20.);
+
+                        ui.vertical(|ui| {
+                            let destination = Destination::get(game_settings.expedition.destination);
+                            let population = player.population.idle;
+                            let soldiers = player.population.soldier;
+
+                            ui.heading(destination.name.label());
+                            ui.add_space(5.);
+
+                            ui.horizontal(|ui| {
+                                ui.add_image(clock_texture, [20., 20.]);
+                                ui.label(destination.duration);
+                            });
+
+                            ui.add_space(10.);
+
+                            let expedition = &mut game_settings.expedition;
+                            expedition.population = expedition.population.clamp(1, population.max(1));
+                            expedition.soldiers = expedition.soldiers.min(soldiers);
+
+                            ui.horizontal(|ui| {
+                                ui.add_image(population_texture, [20., 20.])
+                                    .on_hover_text(format!("Size of the escort. Recommended: {}.", destination.population));
+                                ui.add(egui::Slider::new(&mut expedition.population, 1..=population.max(1)));
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.add_image(soldier_texture, [20., 20.])
+                                    .on_hover_text("Soldiers protecting the expedition.");
+                                ui.add(egui::Slider::new(&mut expedition.soldiers, 0..=soldiers));
+                            });
+
+                            ui.add_space(5.);
+
+                            let price = expedition.price();
+                            ui.horizontal(|ui| {
+                                ui.add_image(gasoline_texture, [20., 20.]);
+                                ui.label(format!("{:.0}", price.gasoline));
+                                ui.add_space(10.);
+                                ui.add_image(materials_texture, [20., 20.]);
+                                ui.label(format!("{:.0}", price.materials));
+                            });
+
+                            ui.label(format!("Chance to return per day: {:.0}%", expedition.return_prob() * 100.));
+
+                            ui.add_space(10.);
+
+                            if ui.add_button("Send").clicked() {
+                                if population == 0 || population < expedition.population {
+                                    messages.error("Not enough idle population!");
+                                } else if player.resources < price {
+                                    messages.error("Not enough resources!");
+                                } else {
+                                    player.population.idle -= expedition.population;
+                                    player.population.soldier -= expedition.soldiers;
+                                    player.resources -= &price;
+                                    player.expeditions.push(Expedition::new(
+                                        expedition.destination,
+                                        expedition.population,
+                                        expedition.soldiers,
+                                    ));
 
-                    if let Some(expedition) = &player.expedition {
+                                    messages.info(format!(
+                                        "Expedition to the {} send.",
+                                        expedition.destination.label().to_lowercase()
+                                    ));
+                                }
+                            }
+                        });
+                    });
+
+                    if !player.expeditions.is_empty() {
                         ui.add_scroll("exp2", MAP_SIZE.x * 0.1, |ui| {
                             ui.add_space(15.);
-                            ui.strong(format!(
-                                "A {} expedition was send {} days ago. We haven't heard from them since...",
-                                expedition.name.name().to_lowercase(),
-                                expedition.day)
-                            );
-                            ui.add_space(15.);
+                            for expedition in player.expeditions.iter() {
+                                ui.strong(format!(
+                                    "An expedition of {} people and {} soldiers was send to the {} {} days ago. \
+                                    We haven't heard from them since...",
+                                    expedition.population,
+                                    expedition.soldiers,
+                                    expedition.destination.label().to_lowercase(),
+                                    expedition.day,
+                                ));
+                            }
                         });
-                    } else {
-                        ui.horizontal(|ui| {
-                            for expedition in Expedition::iter() {
-                                ui.add_space(20.);
-                                let response = ui.add_expedition(&expedition, &textures);
-                                if response.clicked() {
-                                    if player.population.idle >= expedition.population {
-                                        if player.resources >= expedition.price {
-                                            player.population.idle -= expedition.population;
-                                            player.resources -= &expedition.price;
-                                            player.expedition = Some(expedition.clone());
-
-                                            messages.info(format!(
-                                                "{} expedition send.",
-                                                expedition.name.name()
-                                            ));
-                                        } else {
-                                            messages.error("Not enough resources!");
-                                        }
-                                    } else {
-                                        messages.error("Not enough idle population!");
+                    }
+                }
+                DayTabs::Market => {
+                    ui.add_space(10.);
+
+                    ui.add_scroll("market", MAP_SIZE.x * 0.1, |ui| {
+                        ui.add_space(15.);
+                        ui.label(
+                            "\
+                            Exchange resources with the survivors outside the fortress. The \
+                            prices change every day with the supply and demand, and react to your \
+                            own trades: selling a resource lowers its price, and buying one raises \
+                            it. During the night, only emergency trades at a markup are possible.",
+                        );
+                        ui.add_space(15.);
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(20.);
+
+                        ui.vertical(|ui| {
+                            ui.add_market_chart(&player.market)
+                                .on_hover_text(format!("Prices of the last {MARKET_HISTORY} days, relative to their normal price."));
+
+                            ui.add_space(10.);
+
+                            let market = &player.market;
+                            let yesterday = market.history.iter().rev().nth(1).unwrap_or(&market.prices);
+                            egui::Grid::new("market prices")
+                                .num_columns(3)
+                                .spacing([20.0, 4.0])
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.strong("Resource");
+                                    ui.strong("Price").on_hover_text("Price in materials.");
+                                    ui.strong("Change").on_hover_text("Change of the price since yesterday.");
+                                    ui.end_row();
+
+                                    for name in ResourceName::iter() {
+                                        let price = market.prices.get(name);
+                                        ui.colored_label(resource_color(name), name.name());
+                                        ui.label(format!("{:.2}", price));
+                                        ui.label(format!("{:+.0}%", (price / yesterday.get(name) - 1.) * 100.));
+                                        ui.end_row();
                                     }
+                                });
+                        });
+
+                        ui.add_space(40.);
+
+                        ui.vertical(|ui| {
+                            let trade = &mut game_settings.trade;
+
+                            ui.heading("Trade");
+                            ui.add_space(10.);
+
+                            ui.horizontal(|ui| {
+                                ui.add_text("Sell", 40.);
+                                egui::ComboBox::from_id_salt("trade from")
+                                    .selected_text(trade.from.name())
+                                    .show_ui(ui, |ui| {
+                                        for name in ResourceName::iter() {
+                                            ui.selectable_value(&mut trade.from, name, name.name());
+                                        }
+                                    });
+                            });
+
+                            let stock = player.resources.get(trade.from).max(0.).floor();
+                            trade.amount = trade.amount.min(stock);
+                            ui.horizontal(|ui| {
+                                ui.add_text("", 40.);
+                                ui.add(egui::Slider::new(&mut trade.amount, 0.0..=stock).step_by(10.));
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.add_text("Buy", 40.);
+                                egui::ComboBox::from_id_salt("trade to")
+                                    .selected_text(trade.to.name())
+                                    .show_ui(ui, |ui| {
+                                        for name in ResourceName::iter() {
+                                            ui.selectable_value(&mut trade.to, name, name.name());
+                                        }
+                                    });
+                            });
+
+                            ui.add_space(10.);
+
+                            let rate = player.market.rate(trade.from, trade.to, 0.);
+                            ui.label(format!("Rate: {:.2} per unit", rate))
+                                .on_hover_text(format!("Includes a {:.0}% trading fee.", MARKET_SPREAD * 100.));
+                            ui.strong(format!(
+                                "You receive {:.0} {}.",
+                                trade.amount * rate,
+                                trade.to.name().to_lowercase()
+                            ));
+
+                            ui.add_space(10.);
+
+                            if ui.add_button("Trade").clicked() {
+                                if trade.from == trade.to {
+                                    messages.error("Choose two different resources!");
+                                } else if let Some(change) = player.trade(trade.from, trade.to, trade.amount, 0.) {
+                                    messages.info(format!(
+                                        "Traded {:.0} {} for {:.0} {}.",
+                                        -change.get(trade.from),
+                                        trade.from.name().to_lowercase(),
+                                        change.get(trade.to),
+                                        trade.to.name().to_lowercase(),
+                                    ));
+                                } else {
+                                    messages.error("Not enough resources!");
                                 }
                             }
                         });
-                    }
+                    });
                 }
             }
 
@@ -2028,6 +2674,8 @@ pub fn enemy_info_panel(
 pub fn expedition_panel(
     mut contexts: EguiContexts,
     mut player: ResMut<Player>,
+    events: Res<ExpeditionEvents>,
+    app_state: Res<State<AppState>>,
     assets: Local<WorldAssets>,
     window: Query<&Window>,
 ) {
@@ -2035,14 +2683,11 @@ pub fn expedition_panel(
     let bullets_texture = contexts.add_image(assets.get_image("bullets"));
     let gasoline_texture = contexts.add_image(assets.get_image("gasoline"));
     let materials_texture = contexts.add_image(assets.get_image("materials"));
+    let technology_texture = contexts.add_image(assets.get_image("technology"));
     let mine_texture = contexts.add_image(assets.get_image("mine"));
     let bomb_texture = contexts.add_image(assets.get_image("bomb"));
 
-    if let Some(expedition) = player.expedition.clone() {
-        if matches!(expedition.status, ExpeditionStatus::Ongoing) {
-            return;
-        }
-
+    if let Some(expedition) = player.expeditions.iter().find(|e| e.is_finished()).cloned() {
         let window_size = window.single().size();
 
         egui::Window::new("expedition panel")
@@ -2062,16 +2707,17 @@ pub fn expedition_panel(
                             ui.add_space(20.);
                             ui.add_scroll("exp", 50., |ui| {
                                 ui.label(format!(
-                                    "A {} expedition was send out {} days ago. \
+                                    "An expedition to the {} was send out {} days ago. \
                                     There is no longer any hope of them returning...",
-                                    expedition.name.name().to_lowercase(),
+                                    expedition.destination.label().to_lowercase(),
                                     expedition.day
                                 ));
                             });
                         }
                         ExpeditionStatus::Returned(reward) => {
                             ui.heading(format!(
-                                "The expedition returned after {} days!",
+                                "The expedition to the {} returned after {} days!",
+                                expedition.destination.label().to_lowercase(),
                                 expedition.day
                             ));
                             ui.add_space(20.);
@@ -2116,6 +2762,18 @@ pub fn expedition_panel(
                                 });
                             }
 
+                            if reward.resources.technology > 0. {
+                                ui.add_space(10.);
+                                ui.horizontal(|ui| {
+                                    ui.add_space(160.);
+                                    ui.add_image(technology_texture, [20., 20.]);
+                                    ui.label(format!(
+                                        "Technology: {:.0}",
+                                        reward.resources.technology
+                                    ));
+                                });
+                            }
+
                             if reward.mines > 0 {
                                 ui.add_space(10.);
                                 ui.horizontal(|ui| {
@@ -2145,18 +2803,87 @@ pub fn expedition_panel(
                     }
                 });
 
+                ui.add_space(10.);
+            });
+    } else if let Some(i) = player.expeditions.iter().position(|e| e.event.is_some()) {
+        if *app_state.get() != AppState::Day {
+            return;
+        }
+
+        let expedition = &player.expeditions[i];
+        let Some(event) = expedition.event.as_ref().and_then(|id| events.get(id)) else {
+            // The event no longer exists in the data file
+            player.expeditions[i].event = None;
+            return;
+        };
+
+        let destination = expedition.destination;
+        let window_size = window.single().size();
+
+        egui::Window::new("expedition event")
+            .title_bar(false)
+            .fixed_size((MAP_SIZE.x * 0.4, MAP_SIZE.y * 0.4))
+            .fixed_pos((
+                (window_size.x - WEAPONS_PANEL_SIZE.x) * 0.5 - MAP_SIZE.x * 0.2,
+                (window_size.y - RESOURCES_PANEL_SIZE.y) * 0.5 - MAP_SIZE.y * 0.2,
+            ))
+            .show(contexts.ctx_mut(), |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(10.);
+                    ui.heading(format!(
+                        "News from the expedition to the {}",
+                        destination.label().to_lowercase()
+                    ));
+                    ui.add_space(20.);
+                    ui.add_scroll("event", 50., |ui| {
+                        ui.label(&event.text);
+                    });
+                    ui.add_space(20.);
+                });
+
+                for (j, choice) in event.choices.iter().enumerate() {
+                    ui.add_enabled_ui(player.resources >= choice.price, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add_space(50.);
+                            if ui.button(&choice.label).clicked() {
+                                player.resources -= &choice.price;
+                                player.expeditions[i].decide(event, j);
+                            }
+
+                            for (price, texture) in [
+                                (choice.price.bullets, bullets_texture),
+                                (choice.price.gasoline, gasoline_texture),
+                                (choice.price.materials, materials_texture),
+                                (choice.price.technology, technology_texture),
+                            ] {
+                                if price > 0. {
+                                    ui.add_image(texture, [20., 20.]);
+                                    ui.label(format!("{:.0}", price));
+                                }
+                            }
+                        });
+                    })
+                    .response
+                    .on_disabled_hover_text("Not enough resources.");
+
+                    ui.add_space(5.);
+                }
+
                 ui.add_space(10.);
             });
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run_animations(
     mut commands: Commands,
     mut animation_q: Query<(Entity, &Transform, &mut AnimationComponent, &mut Sprite)>,
     mut enemy_q: Query<(Entity, &Transform, &mut Enemy)>,
     fence_q: Query<&Transform, With<FenceComponent>>,
     wall_q: Query<&Transform, With<WallComponent>>,
+    mut weapon_q: Query<(&Transform, &mut Weapon)>,
     mut player: ResMut<Player>,
+    mut messages: ResMut<Messages>,
     game_settings: Res<GameSettings>,
     time: Res<Time>,
 ) {
@@ -2193,6 +2920,17 @@ pub fn run_animations(
                                 player.wall.health -= damage.penetration.min(player.wall.health);
                             }
                         }
+                        for (weapon_t, mut weapon) in weapon_q.iter_mut() {
+                            if collision(
+                                &animation_t.translation,
+                                &Vec2::splat(2. * radius),
+                                &weapon_t.translation,
+                                &weapon.dim,
+                            ) && weapon.take_damage(damage.penetration)
+                            {
+                                messages.error(format!("A {} was destroyed", weapon.name.name()));
+                            }
+                        }
 
                         // Resolve the impact on all enemies in radius
                         enemy_q
@@ -2206,7 +2944,8 @@ pub fn run_animations(
                                 )
                             })
                             .for_each(|(_, _, ref mut enemy)| {
-                                enemy.health -= damage.calculate(enemy).min(enemy.health)
+                                enemy.health -= damage.calculate(enemy).min(enemy.health);
+                                enemy.last_hit = damage.source;
                             });
                     }
                 } else if atlas.index == animation.last_index {
@@ -2217,9 +2956,10 @@ pub fn run_animations(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_game(
     mut commands: Commands,
-    weapon_q: Query<&Weapon>,
+    mut weapon_q: Query<&mut Weapon>,
     enemy_q: Query<EnemyQ, (With<Enemy>, Without<EnemyHealth>)>,
     children_q: Query<&Children>,
     fence_q: Query<Entity, With<FenceComponent>>,
@@ -2232,8 +2972,15 @@ pub fn update_game(
     time: Res<Time>,
 ) {
     // Update turret's power
-    if let Some(turret) = weapon_q.iter().find(|w| w.name == WeaponName::Turret) {
-        if player.weapons.settings.turret < MAX_TURRET_POWER {
+    if let Some((turret, i)) = weapon_q.iter().find_map(|w| {
+        player
+            .weapons
+            .spots
+            .iter()
+            .position(|s| s.id == w.spot && w.name == WeaponName::Turret && !w.is_destroyed())
+            .map(|i| (w, i))
+    }) {
+        if player.weapons.spots[i].settings.turret < MAX_TURRET_POWER {
             let cost = TURRET_POWER_COST * game_settings.speed * time.delta_secs();
             if player.resources.gasoline > cost {
                 player.resources.gasoline -= cost;
@@ -2242,7 +2989,7 @@ pub fn update_game(
                 // The default is to power-up in 10 seconds, but
                 // this decreases with the fire_timer's duration
                 let timer = turret.fire_timer.clone().unwrap().duration().as_secs_f32();
-                player.weapons.settings.turret += MAX_TURRET_POWER / DEFAULT_TURRET_POWER_TIME
+                player.weapons.spots[i].settings.turret += MAX_TURRET_POWER / DEFAULT_TURRET_POWER_TIME
                     * timer.powf(-1.)
                     * game_settings.speed
                     * time.delta_secs()
@@ -2250,9 +2997,21 @@ pub fn update_game(
         }
     }
 
+    // Store the damage of the weapons on their spots to repair it during the day
+    for weapon in weapon_q.iter() {
+        if let Some(spot) = player.weapons.spots.iter_mut().find(|s| s.id == weapon.spot) {
+            spot.damage = weapon.max_health - weapon.health;
+
+            // Destroyed weapons lose their rank
+            if weapon.is_destroyed() {
+                spot.kills = 0;
+            }
+        }
+    }
+
     // Update resources
     if player.fence.enabled {
-        let fence_cost = &player.fence.cost * game_settings.speed * time.delta_secs();
+        let fence_cost = player.cost(&player.fence.cost) * game_settings.speed * time.delta_secs();
         if player.resources >= fence_cost {
             player.resources -= &fence_cost;
             night_stats.resources += &fence_cost;
@@ -2261,7 +3020,7 @@ pub fn update_game(
         }
     }
 
-    let spotlight_cost = &player.spotlight.cost
+    let spotlight_cost = player.cost(&player.spotlight.cost)
         * player.spotlight.power as f32
         * game_settings.speed
         * time.delta_secs();
@@ -2296,6 +3055,12 @@ pub fn update_game(
         if player.fence.health == 0. {
             messages.warning("The fence is broken");
             commands.entity(fence_e).try_despawn();
+
+            // The weapons mounted on the fence fall with it
+            weapon_q
+                .iter_mut()
+                .filter(|w| w.mount == Mount::Fence)
+                .for_each(|mut w| w.health = 0.);
         }
     }
 
@@ -2316,6 +3081,30 @@ pub fn update_game(
                     .enemies
                     .entry(enemy.name.to_string())
                     .and_modify(|status| status.killed += 1);
+
+                // Credit the kill to the weapon that dealt the last hit
+                if let Some((i, spot)) = player
+                    .weapons
+                    .spots
+                    .iter_mut()
+                    .enumerate()
+                    .find(|(_, s)| Some(s.id) == enemy.last_hit)
+                {
+                    let rank = spot.rank();
+                    spot.kills += 1;
+
+                    if spot.rank() > rank {
+                        if let Some(mut weapon) = weapon_q.iter_mut().find(|w| w.spot == spot.id) {
+                            weapon.rank = spot.rank();
+                            messages.info(format!(
+                                "The {} on spot {} reached rank {}",
+                                weapon.name.name(),
+                                i + 1,
+                                weapon.rank
+                            ));
+                        }
+                    }
+                }
             } else {
                 for child in children_q.iter_descendants(enemy_e) {
                     if let Ok((mut sprite_t, mut sprite)) = health_q.get_mut(child) {
@@ -2331,15 +3120,32 @@ pub fn update_game(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn clear_map(
     mut commands: Commands,
     animation_q: Query<Entity, With<AnimationComponent>>,
     bullet_q: Query<Entity, (With<Bullet>, Without<Mine>)>,
+    lightning_q: Query<Entity, With<Lightning>>,
+    drone_q: Query<Entity, With<Drone>>,
+    soldier_q: Query<Entity, With<Soldier>>,
+    shadow_q: Query<Entity, With<Shadow>>,
     enemy_q: Query<Entity, With<Enemy>>,
 ) {
     animation_q
         .iter()
         .for_each(|a| commands.entity(a).try_despawn());
+    lightning_q
+        .iter()
+        .for_each(|l| commands.entity(l).try_despawn());
+    drone_q
+        .iter()
+        .for_each(|d| commands.entity(d).try_despawn());
+    soldier_q
+        .iter()
+        .for_each(|s| commands.entity(s).try_despawn());
+    shadow_q
+        .iter()
+        .for_each(|s| commands.entity(s).try_despawn());
     bullet_q
         .iter()
         .for_each(|b| commands.entity(b).try_despawn());
@@ -2348,15 +3154,32 @@ pub fn clear_map(
         .for_each(|e| commands.entity(e).despawn_recursive());
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn clear_all(
     mut commands: Commands,
     animation_q: Query<Entity, With<AnimationComponent>>,
     bullet_q: Query<Entity, With<Bullet>>,
+    lightning_q: Query<Entity, With<Lightning>>,
+    drone_q: Query<Entity, With<Drone>>,
+    soldier_q: Query<Entity, With<Soldier>>,
+    shadow_q: Query<Entity, With<Shadow>>,
     enemy_q: Query<Entity, With<Enemy>>,
 ) {
     animation_q
         .iter()
         .for_each(|a| commands.entity(a).try_despawn());
+    lightning_q
+        .iter()
+        .for_each(|l| commands.entity(l).try_despawn());
+    drone_q
+        .iter()
+        .for_each(|d| commands.entity(d).try_despawn());
+    soldier_q
+        .iter()
+        .for_each(|s| commands.entity(s).try_despawn());
+    shadow_q
+        .iter()
+        .for_each(|s| commands.entity(s).try_despawn());
     bullet_q
         .iter()
         .for_each(|b| commands.entity(b).try_despawn());
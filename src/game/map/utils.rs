@@ -1,9 +1,15 @@
-use crate::constants::{FOW_SIZE, MAP_SIZE, MAX_UPGRADE_LEVEL};
+use crate::constants::{
+    FOW_SIZE, MAP_SIZE, MARKET_HISTORY, MAX_CRYO_POWER, MAX_FLAMETHROWER_POWER,
+    MAX_MACHINE_GUN_FIRE_RATE, MAX_MISSILE_LAUNCHER_SHELLS, MAX_UPGRADE_LEVEL, RANK_KILLS,
+};
 use crate::game::enemy::components::Enemy;
 use crate::game::resources::{
-    Expedition, NightInfo, Player, Population, Resources, Spot, Technology,
+    Destination, DestinationName, Market, NightInfo, Player, Population, ResourceName, Resources,
+    Spot, Technology, WeaponSettings, BASE_PRICES,
+};
+use crate::game::weapon::components::{
+    AirFireStrategy, FireStrategy, MortarShell, UpgradeNode, Weapon, WeaponName,
 };
-use crate::game::weapon::components::Weapon;
 use crate::messages::Messages;
 use crate::utils::NameFromEnum;
 use bevy::prelude::{Transform, Vec2 as BVec2, Vec3};
@@ -11,11 +17,33 @@ use bevy::utils::HashMap;
 use bevy_egui::egui::load::SizedTexture;
 use bevy_egui::egui::*;
 use std::hash::Hash;
-use uuid::Uuid;
+use strum::IntoEnumIterator;
+
+/// Color of a resource in the market chart
+pub fn resource_color(name: ResourceName) -> Color32 {
+    match name {
+        ResourceName::Bullets => Color32::GOLD,
+        ResourceName::Gasoline => Color32::LIGHT_RED,
+        ResourceName::Materials => Color32::LIGHT_GRAY,
+        ResourceName::Technology => Color32::LIGHT_BLUE,
+        ResourceName::Food => Color32::LIGHT_GREEN,
+    }
+}
 
 /// Whether an enemy is behind the fog of war
 pub fn is_visible(fow_t: &Transform, enemy_t: &Transform, enemy: &Enemy) -> bool {
-    fow_t.translation.y - FOW_SIZE.y * 0.45 >= enemy_t.translation.y - enemy.dim.y * 0.5
+    is_visible_through(fow_t, enemy_t, enemy, 0.)
+}
+
+/// Whether an enemy is visible when seeing `depth` (fraction of
+/// the fog's height) into the fog of war
+pub fn is_visible_through(
+    fow_t: &Transform,
+    enemy_t: &Transform,
+    enemy: &Enemy,
+    depth: f32,
+) -> bool {
+    fow_t.translation.y - FOW_SIZE.y * (0.45 - depth) >= enemy_t.translation.y - enemy.dim.y * 0.5
 }
 
 /// AABB collision detection
@@ -29,12 +57,32 @@ pub fn collision(pos1: &Vec3, size1: &BVec2, pos2: &Vec3, size2: &BVec2) -> bool
     p1_max.x > p2_min.x && p1_min.x < p2_max.x && p1_max.y > p2_min.y && p1_min.y < p2_max.y
 }
 
+/// Combo box to select the weapon's fire strategy from `options`
+fn add_fire_strategy<'a>(
+    ui: &mut Ui,
+    id: impl Hash,
+    strategy: &mut FireStrategy,
+    options: impl Iterator<Item = &'a FireStrategy>,
+) {
+    ComboBox::from_id_salt(id)
+        .selected_text(strategy.name())
+        .show_ui(ui, |ui| {
+            for s in options {
+                ui.selectable_value(strategy, s.clone(), s.name())
+                    .on_hover_text(s.description());
+            }
+        })
+        .response
+        .on_hover_text(strategy.description());
+}
+
 /// Custom syntactic sugar for repetitive UI elements
 pub trait CustomUi {
     fn add_button(&mut self, text: impl Into<WidgetText>) -> Response;
     fn add_upgrade_button(&mut self, texture: impl Into<TextureId>) -> Response;
     fn add_image(&mut self, id: impl Into<TextureId>, size: impl Into<Vec2>) -> Response;
     fn add_text(&mut self, text: impl Into<WidgetText>, width: f32) -> Response;
+    fn add_rank(&mut self, spot: &Spot, texture: impl Into<TextureId>) -> Response;
     fn add_scroll<R>(
         &mut self,
         id: impl Hash,
@@ -48,6 +96,12 @@ pub trait CustomUi {
         player: &mut Player,
         messages: &mut Messages,
     );
+    fn add_weapon_settings(
+        &mut self,
+        label: &str,
+        weapon: WeaponName,
+        settings: &mut WeaponSettings,
+    );
     fn add_night_stats(&mut self, player: &Player, day: u32);
     fn add_technology(
         &mut self,
@@ -57,11 +111,12 @@ pub trait CustomUi {
         tech_texture: TextureId,
     ) -> Response;
 
-    fn add_expedition(
+    fn add_expedition_map(
         &mut self,
-        expedition: &Expedition,
-        textures: &HashMap<&str, TextureId>,
-    ) -> Response;
+        player: &Player,
+        selected: DestinationName,
+    ) -> Option<DestinationName>;
+    fn add_market_chart(&mut self, market: &Market) -> Response;
 }
 
 impl CustomUi for Ui {
@@ -84,6 +139,24 @@ impl CustomUi for Ui {
         self.add_sized([width, self.available_height()], Label::new(text))
     }
 
+    fn add_rank(&mut self, spot: &Spot, texture: impl Into<TextureId>) -> Response {
+        let texture = texture.into();
+        let rank = spot.rank();
+        let next = RANK_KILLS
+            .get(rank as usize)
+            .map(|k| format!(" Next rank at {k} kills."))
+            .unwrap_or_default();
+
+        self.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.;
+            for _ in 0..rank {
+                ui.add_image(texture, [14., 14.]);
+            }
+        })
+        .response
+        .on_hover_text(format!("Rank {rank}: {} kills.{next}", spot.kills))
+    }
+
     fn add_scroll<R>(
         &mut self,
         id: impl Hash,
@@ -145,10 +218,7 @@ impl CustomUi for Ui {
                     .on_hover_text(format!("Remove a {} from the wall.", weapon.name.name()));
 
                 if button.clicked() {
-                    player.weapons.spots[pos.unwrap()] = Spot {
-                        id: Uuid::new_v4(),
-                        weapon: None,
-                    };
+                    player.weapons.spots[pos.unwrap()] = Spot::new(None);
                 }
 
                 let button = ui
@@ -169,12 +239,8 @@ impl CustomUi for Ui {
                     )
                     .on_hover_text(format!("Place a {} on the wall.", weapon.name.name()));
 
-                if button.clicked() {
-                    if let Some(pos) = player.weapons.spots.iter().position(|w| w.weapon == None) {
-                        player.weapons.spots[pos].weapon = Some(weapon.name);
-                    } else {
-                        messages.error("No spots available on the wall.");
-                    }
+                if button.clicked() && !player.weapons.place(weapon.name) {
+                    messages.error("No spots available on the wall.");
                 }
             });
         });
@@ -231,6 +297,97 @@ impl CustomUi for Ui {
                 .on_disabled_hover_text("Maximum upgrade level reached.");
             }
 
+            // Every row of the upgrade tree is one level deeper than the previous
+            let depth = |node: &UpgradeNode| {
+                let mut depth = 0;
+                let mut parent = node.parent;
+                while let Some(p) = parent {
+                    depth += 1;
+                    parent = weapon
+                        .tree
+                        .iter()
+                        .find(|n| n.name == p)
+                        .and_then(|n| n.parent);
+                }
+                depth
+            };
+
+            ui.label("Upgrade tree:");
+
+            // Reserve a shape behind the nodes to draw the branches
+            let lines = ui.painter().add(Shape::Noop);
+            let mut rects = HashMap::new();
+
+            for d in 0..=weapon.tree.iter().map(depth).max().unwrap_or_default() {
+                ui.horizontal(|ui| {
+                    ui.add_space(10.);
+                    for node in weapon.tree.iter().filter(|n| depth(n) == d) {
+                        let bought = player.weapons.has_node(weapon.name, node.name);
+                        let locked = weapon.locked_reason(node, player);
+                        let enabled = bought || locked.is_none();
+
+                        ui.vertical(|ui| {
+                            ui.set_width(100.);
+                            ui.add_enabled_ui(enabled, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.vertical(|ui| {
+                                        let button = ui
+                                            .add_upgrade_button(textures[node.texture])
+                                            .on_hover_text(format!(
+                                                "{}: {}",
+                                                node.name.name(),
+                                                node.description
+                                            ));
+                                        rects.insert(node.name, button.rect);
+
+                                        if button.clicked() && !bought {
+                                            if player.resources >= node.price {
+                                                player.resources -= &node.price;
+                                                player
+                                                    .weapons
+                                                    .nodes
+                                                    .entry(weapon.name)
+                                                    .or_default()
+                                                    .push(node.name);
+                                            } else {
+                                                messages.error("Not enough resources.");
+                                            }
+                                        }
+
+                                        if bought {
+                                            ui.add_space(-25.);
+                                            frame.show(ui, |ui| ui.strong("✔"));
+                                        }
+                                    });
+
+                                    if !bought {
+                                        ui.strong(format!("{}", node.price.technology));
+                                        ui.add_image(textures["technology"], [20., 20.]);
+                                    }
+                                });
+                            })
+                            .response
+                            .on_disabled_hover_text(locked.unwrap_or_default());
+                        });
+                    }
+                });
+            }
+
+            let stroke = Stroke::new(2., Color32::GRAY);
+            let shapes = weapon
+                .tree
+                .iter()
+                .filter_map(|n| {
+                    let from = rects.get(&n.parent?)?;
+                    let to = rects.get(&n.name)?;
+                    Some(Shape::line_segment(
+                        [from.center_bottom(), to.center_top()],
+                        stroke,
+                    ))
+                })
+                .collect();
+            ui.painter().set(lines, Shape::Vec(shapes));
+
             ui.horizontal(|ui| {
                 let button = ui
                     .add_upgrade_button(textures["up"])
@@ -239,7 +396,7 @@ impl CustomUi for Ui {
                 ui.add_image(textures["materials"], [20., 20.]);
 
                 if button.clicked() {
-                    if owned + 1 <= weapon.maximum {
+                    if owned < weapon.maximum {
                         if player.resources >= weapon.price {
                             player.resources -= &weapon.price;
                             player
@@ -250,11 +407,7 @@ impl CustomUi for Ui {
                                 .or_insert(1);
 
                             // If there is a spot available, place it directly on the wall
-                            if let Some(pos) =
-                                player.weapons.spots.iter().position(|w| w.weapon == None)
-                            {
-                                player.weapons.spots[pos].weapon = Some(weapon.name);
-                            }
+                            player.weapons.place(weapon.name);
                         } else {
                             messages.error("Not enough resources.");
                         }
@@ -269,12 +422,220 @@ impl CustomUi for Ui {
         });
     }
 
+    fn add_weapon_settings(
+        &mut self,
+        label: &str,
+        weapon: WeaponName,
+        settings: &mut WeaponSettings,
+    ) {
+        self.horizontal(|ui| match weapon {
+            WeaponName::AAA => {
+                ui.add(Label::new(label));
+                ui.selectable_value(
+                    &mut settings.aaa,
+                    AirFireStrategy::None,
+                    AirFireStrategy::None.name(),
+                )
+                .on_hover_text("Don't fire.");
+                ui.selectable_value(
+                    &mut settings.aaa,
+                    AirFireStrategy::All,
+                    AirFireStrategy::All.name(),
+                )
+                .on_hover_text("Fire at all enemies dealing reduced damage.");
+                ui.selectable_value(
+                    &mut settings.aaa,
+                    AirFireStrategy::Airborne,
+                    AirFireStrategy::Airborne.name(),
+                )
+                .on_hover_text("Fire only at flying enemies, dealing more damage.");
+            }
+            WeaponName::Artillery => {
+                ui.add(Label::new(label));
+                add_fire_strategy(
+                    ui,
+                    (weapon, label),
+                    &mut settings.artillery,
+                    // The shells can't hit flying enemies, so don't prioritize them
+                    [FireStrategy::None].iter().chain(
+                        FireStrategy::TARGETING
+                            .iter()
+                            .filter(|s| **s != FireStrategy::FlyingFirst),
+                    ),
+                );
+            }
+            WeaponName::Canon => {
+                ui.add(Label::new(label));
+                ui.selectable_value(
+                    &mut settings.canon,
+                    AirFireStrategy::None,
+                    AirFireStrategy::None.name(),
+                )
+                .on_hover_text("Don't fire.");
+                ui.selectable_value(
+                    &mut settings.canon,
+                    AirFireStrategy::Grounded,
+                    AirFireStrategy::Grounded.name(),
+                )
+                .on_hover_text("Fire only at grounded (non-flying) enemies.");
+                ui.selectable_value(
+                    &mut settings.canon,
+                    AirFireStrategy::Airborne,
+                    AirFireStrategy::Airborne.name(),
+                )
+                .on_hover_text("Fire only at flying enemies.");
+            }
+            WeaponName::Cryo => {
+                let label = ui
+                    .add(Label::new(label))
+                    .on_hover_cursor(CursorIcon::PointingHand);
+
+                ui.add(Slider::new(&mut settings.cryo, 0..=MAX_CRYO_POWER))
+                    .on_hover_text("More power means more range, but costs more.");
+
+                if label.clicked() {
+                    settings.cryo = if settings.cryo > 0 { 0 } else { MAX_CRYO_POWER };
+                }
+            }
+            WeaponName::Flamethrower => {
+                let label = ui
+                    .add(Label::new(label))
+                    .on_hover_cursor(CursorIcon::PointingHand);
+
+                ui.add(Slider::new(
+                    &mut settings.flamethrower,
+                    0..=MAX_FLAMETHROWER_POWER,
+                ))
+                .on_hover_text("More power means more range, but costs more.");
+
+                if label.clicked() {
+                    settings.flamethrower = if settings.flamethrower > 0 {
+                        0
+                    } else {
+                        MAX_FLAMETHROWER_POWER
+                    };
+                }
+            }
+            WeaponName::MachineGun => {
+                let label = ui
+                    .add(Label::new(label))
+                    .on_hover_cursor(CursorIcon::PointingHand);
+
+                ui.add(Slider::new(
+                    &mut settings.machine_gun,
+                    0..=MAX_MACHINE_GUN_FIRE_RATE,
+                ))
+                .on_hover_text("Shoot N bullets per second.");
+
+                if label.clicked() {
+                    settings.machine_gun = if settings.machine_gun > 0 {
+                        0
+                    } else {
+                        MAX_MACHINE_GUN_FIRE_RATE
+                    };
+                }
+            }
+            WeaponName::MissileLauncher => {
+                let label = ui
+                    .add(Label::new(label))
+                    .on_hover_cursor(CursorIcon::PointingHand);
+
+                ui.add(Slider::new(
+                    &mut settings.missile_launcher,
+                    0..=MAX_MISSILE_LAUNCHER_SHELLS,
+                ))
+                .on_hover_text("Shoot N shells per firing round.");
+
+                if label.clicked() {
+                    settings.missile_launcher = if settings.missile_launcher > 0 {
+                        0
+                    } else {
+                        MAX_MISSILE_LAUNCHER_SHELLS
+                    };
+                }
+            }
+            WeaponName::Mortar => {
+                ui.add(Label::new(label));
+                ui.selectable_value(
+                    &mut settings.mortar,
+                    MortarShell::None,
+                    MortarShell::None.name(),
+                )
+                .on_hover_text("Don't fire.");
+                ui.selectable_value(
+                    &mut settings.mortar,
+                    MortarShell::Light,
+                    MortarShell::Light.name(),
+                )
+                .on_hover_text("Light shells do standard damage and don't damage structures.");
+                ui.selectable_value(
+                    &mut settings.mortar,
+                    MortarShell::Heavy,
+                    MortarShell::Heavy.name(),
+                )
+                .on_hover_text("Heavy shells do more damage, but cost more and damage structures.");
+            }
+            WeaponName::Sniper => {
+                ui.add(Label::new(label));
+                add_fire_strategy(
+                    ui,
+                    (weapon, label),
+                    &mut settings.sniper,
+                    [FireStrategy::None]
+                        .iter()
+                        .chain(FireStrategy::TARGETING.iter()),
+                );
+            }
+            WeaponName::Tesla => {
+                ui.add(Label::new(label));
+                add_fire_strategy(
+                    ui,
+                    (weapon, label),
+                    &mut settings.tesla,
+                    [FireStrategy::None]
+                        .iter()
+                        .chain(FireStrategy::TARGETING.iter()),
+                );
+            }
+            // The turret is charged and fired from the weapons panel
+            WeaponName::Turret => (),
+        });
+
+        // Select the target of weapons whose settings don't already imply one
+        let strategy = match weapon {
+            WeaponName::MachineGun => {
+                Some((settings.machine_gun > 0, &mut settings.machine_gun_strategy))
+            }
+            WeaponName::MissileLauncher => Some((
+                settings.missile_launcher > 0,
+                &mut settings.missile_launcher_strategy,
+            )),
+            _ => None,
+        };
+
+        if let Some((enabled, strategy)) = strategy {
+            self.add_enabled_ui(enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add_space(20.);
+                    ui.add(Label::new("Target: "));
+                    add_fire_strategy(
+                        ui,
+                        (weapon, label),
+                        strategy,
+                        FireStrategy::TARGETING.iter(),
+                    );
+                });
+            });
+        }
+    }
+
     fn add_night_stats(&mut self, player: &Player, day: u32) {
         let default = NightInfo {
             day,
             enemies: HashMap::new(),
             resources: Resources::default(),
             population: Population::default(),
+            critical_hits: 0,
         };
         let stats = player.stats.get(&day).unwrap_or(&default);
 
@@ -298,6 +659,12 @@ impl CustomUi for Ui {
                             ui.label(format!("{} / {}", v.killed, v.spawned));
                             ui.end_row();
                         });
+
+                        if stats.critical_hits > 0 {
+                            ui.label("Critical hits");
+                            ui.label(format!("{}", stats.critical_hits));
+                            ui.end_row();
+                        }
                     });
 
                 Grid::new("resources stats")
@@ -360,6 +727,26 @@ impl CustomUi for Ui {
                             stats.population.scientist,
                             player.population.scientist + stats.population.scientist
                         ));
+                        ui.end_row();
+                        ui.label("Farmers");
+                        ui.label(format!(
+                            "{} / {}",
+                            stats.population.farmer,
+                            player.population.farmer + stats.population.farmer
+                        ));
+                        ui.end_row();
+                        ui.label("Idle");
+                        ui.label(format!(
+                            "{} / {}",
+                            stats.population.idle,
+                            player.population.idle + stats.population.idle
+                        ));
+                        ui.end_row();
+                        ui.strong("Killed");
+                        ui.strong(format!("{}", stats.population.available()));
+                        ui.end_row();
+                        ui.strong("Wounded");
+                        ui.strong(format!("{}", stats.population.wounded));
                     });
             });
         });
@@ -409,7 +796,26 @@ impl CustomUi for Ui {
                                 .selectable(false)
                                 .ui(ui);
 
-                            ui.add_space(25.);
+                            ui.add_space(5.);
+
+                            // Show the research progress or the expected duration
+                            let status = if player.has_tech(technology.name) {
+                                String::new()
+                            } else if let Some(eta) = player.research_eta(technology.name) {
+                                if player.research.queue.front() == Some(&technology.name) {
+                                    format!("Researching: {eta} days")
+                                } else {
+                                    format!("Queued: {eta} days")
+                                }
+                            } else {
+                                let rate = player.research_rate().max(1.);
+                                format!("{:.0} days", (technology.research / rate).ceil())
+                            };
+                            Label::new(RichText::new(status).small())
+                                .selectable(false)
+                                .ui(ui);
+
+                            ui.add_space(5.);
 
                             ui.with_layout(Layout::right_to_left(Align::RIGHT), |ui| {
                                 ui.horizontal(|ui| {
@@ -429,77 +835,133 @@ impl CustomUi for Ui {
         .on_hover_cursor(CursorIcon::PointingHand)
     }
 
-    fn add_expedition(
+    fn add_expedition_map(
         &mut self,
-        expedition: &Expedition,
-        textures: &HashMap<&str, TextureId>,
-    ) -> Response {
-        self.scope_builder(
-            UiBuilder::new()
-                .id_salt(expedition.name.name())
-                .sense(Sense::click()),
-            |ui| {
-                let response = ui.response();
-                let visuals = ui.style().interact(&response);
-
-                Frame::canvas(ui.style())
-                    .fill(visuals.bg_fill.gamma_multiply(0.3))
-                    .stroke(visuals.bg_stroke)
-                    .inner_margin(ui.spacing().menu_margin)
-                    .show(ui, |ui| {
-                        ui.set_width(180.);
-
-                        ui.vertical_centered(|ui| {
-                            ui.add_space(5.);
-                            Label::new(RichText::new(expedition.name.name()).strong())
-                                .selectable(false)
-                                .ui(ui);
-
-                            ui.add_space(50.);
-
-                            ui.horizontal(|ui| {
-                                ui.add_space(50.);
-                                ui.add_image(textures["population"], [25., 25.]);
-                                Label::new(expedition.population.to_string())
-                                    .selectable(false)
-                                    .ui(ui);
-                            });
+        player: &Player,
+        selected: DestinationName,
+    ) -> Option<DestinationName> {
+        let (rect, _) = self.allocate_exact_size(vec2(320., 240.), Sense::hover());
+        let painter = self.painter_at(rect);
+        painter.rect_filled(rect, 15., Color32::from_black_alpha(190));
+
+        let to_pos = |(x, y): (f32, f32)| rect.min + vec2(x * rect.width(), y * rect.height());
+        let fortress = to_pos((0.5, 0.85));
+
+        let mut clicked = None;
+        for destination in Destination::iter() {
+            let pos = to_pos(destination.position);
+
+            painter.extend(Shape::dashed_line(
+                &[fortress, pos],
+                Stroke::new(1., Color32::GRAY),
+                6.,
+                4.,
+            ));
+
+            // Draw the expeditions on their way to the destination
+            for expedition in player
+                .expeditions
+                .iter()
+                .filter(|e| e.destination == destination.name)
+            {
+                let t = ((expedition.day + 1) as f32 / destination.max_day as f32).min(1.);
+                painter.circle_filled(fortress.lerp(pos, t), 4., Color32::YELLOW);
+            }
 
-                            ui.add_space(10.);
+            let response = self
+                .interact(
+                    Rect::from_center_size(pos, vec2(24., 24.)),
+                    self.id().with(destination.name),
+                    Sense::click(),
+                )
+                .on_hover_text(destination.description)
+                .on_hover_cursor(CursorIcon::PointingHand);
+
+            let color = if destination.name == selected {
+                Color32::GOLD
+            } else if response.hovered() {
+                Color32::WHITE
+            } else {
+                Color32::LIGHT_GRAY
+            };
+
+            painter.circle_filled(pos, 10., color);
+            painter.text(
+                pos + vec2(0., 14.),
+                Align2::CENTER_TOP,
+                destination.name.label(),
+                FontId::proportional(14.),
+                color,
+            );
+
+            if response.clicked() {
+                clicked = Some(destination.name);
+            }
+        }
 
-                            ui.horizontal(|ui| {
-                                ui.add_space(50.);
-                                ui.add_image(textures["gasoline"], [25., 25.]);
-                                Label::new(expedition.price.gasoline.to_string())
-                                    .selectable(false)
-                                    .ui(ui);
-                            });
+        painter.rect_filled(
+            Rect::from_center_size(fortress, vec2(30., 14.)),
+            3.,
+            Color32::BROWN,
+        );
+        painter.text(
+            fortress + vec2(0., 10.),
+            Align2::CENTER_TOP,
+            "Fortress",
+            FontId::proportional(14.),
+            Color32::WHITE,
+        );
+
+        clicked
+    }
 
-                            ui.add_space(10.);
+    fn add_market_chart(&mut self, market: &Market) -> Response {
+        let (rect, response) = self.allocate_exact_size(vec2(320., 240.), Sense::hover());
+        let painter = self.painter_at(rect);
+        painter.rect_filled(rect, 15., Color32::from_black_alpha(190));
 
-                            ui.horizontal(|ui| {
-                                ui.add_space(50.);
-                                ui.add_image(textures["materials"], [25., 25.]);
-                                Label::new(expedition.price.materials.to_string())
-                                    .selectable(false)
-                                    .ui(ui);
-                            });
+        // Prices are shown relative to their base price
+        let plot = rect.shrink2(vec2(30., 20.));
+        let max = market
+            .history
+            .iter()
+            .flat_map(|p| ResourceName::iter().map(|n| p.get(n) / BASE_PRICES.get(n)))
+            .fold(2f32, f32::max);
+
+        let step = plot.width() / (MARKET_HISTORY - 1) as f32;
+        let to_pos = |i: usize, value: f32| {
+            pos2(
+                plot.min.x + i as f32 * step,
+                plot.max.y - value / max * plot.height(),
+            )
+        };
 
-                            ui.add_space(10.);
+        painter.extend(Shape::dashed_line(
+            &[to_pos(0, 1.), to_pos(MARKET_HISTORY - 1, 1.)],
+            Stroke::new(1., Color32::GRAY),
+            6.,
+            4.,
+        ));
+        painter.text(
+            to_pos(0, 1.) - vec2(5., 0.),
+            Align2::RIGHT_CENTER,
+            "1x",
+            FontId::proportional(12.),
+            Color32::GRAY,
+        );
+
+        for name in ResourceName::iter() {
+            let points: Vec<_> = market
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, p)| to_pos(i, p.get(name) / BASE_PRICES.get(name)))
+                .collect();
 
-                            ui.horizontal(|ui| {
-                                ui.add_space(50.);
-                                ui.add_image(textures["clock"], [25., 25.]);
-                                Label::new(&expedition.duration).selectable(false).ui(ui);
-                            });
+            painter.add(Shape::line(points, Stroke::new(2., resource_color(name))));
+        }
 
-                            ui.add_space(50.);
-                        });
-                    });
-            },
-        )
-        .response
-        .on_hover_cursor(CursorIcon::PointingHand)
+        response
     }
 }
 
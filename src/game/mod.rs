@@ -8,7 +8,7 @@ pub mod weapon;
 use crate::game::enemy::EnemyPlugin;
 use crate::game::map::systems::clear_all;
 use crate::game::map::MapPlugin;
-use crate::game::resources::{GameSettings, NightStats};
+use crate::game::resources::{ExpeditionEvents, GameSettings, NightStats};
 use crate::game::systems::*;
 use crate::game::weapon::systems::spawn_weapons;
 use crate::game::weapon::WeaponPlugin;
@@ -35,7 +35,8 @@ impl Plugin for GamePlugin {
             .init_state::<GameState>()
             .init_state::<AudioState>()
             .init_resource::<GameSettings>()
-            .init_resource::<NightStats>();
+            .init_resource::<NightStats>()
+            .init_resource::<ExpeditionEvents>();
     }
 }
 
@@ -1,14 +1,26 @@
-use crate::constants::{NIGHT_DURATION, RESOURCE_FACTOR, SOLDIER_BASE_DAMAGE};
+use crate::constants::{
+    DAY_EVENT_PROB, DESERTION_MORALE, DESERTION_RATE, FOOD_CONSUMPTION, HOSPITAL_CAPACITY,
+    HOSPITAL_RECOVERY, HOUSING_CAPACITY, INITIAL_MORALE, MARKET_DEPTH, MARKET_HISTORY,
+    MARKET_REVERSION, MARKET_SPREAD, MARKET_SUPPLY, MARKET_VOLATILITY, MAX_ESCORT_FACTOR,
+    MAX_MORALE, MAX_RETURN_PROB, MORALE_EFFECT, MORALE_EXPEDITION_LOST, MORALE_HISTORY,
+    NIGHT_DURATION, RANK_KILLS, RESOURCE_FACTOR, SOLDIER_BASE_DAMAGE, SOLDIER_PROTECTION,
+    STARVATION_RATE, TRAINING_COST, WOUNDED_DEATH_RATE,
+};
 use crate::game::enemy::components::Size;
-use crate::game::weapon::components::{AirFireStrategy, FireStrategy, MortarShell, WeaponName};
+use crate::game::weapon::components::{
+    AirFireStrategy, FireStrategy, MortarShell, Mount, UpgradeNodeName, WeaponName,
+};
 use bevy::ecs::system::SystemId;
 use bevy::prelude::{default, Resource, Timer};
 use bevy::time::TimerMode;
 use bevy::utils::hashbrown::HashMap;
-use rand::random;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::{random, thread_rng};
+use rand_distr::Normal;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
@@ -22,6 +34,17 @@ pub enum DayTabs {
     Armory,
     Technology,
     Expeditions,
+    Market,
+}
+
+/// Weapon waiting for the player to select its target on the map
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Targeting {
+    Bomb,
+    Nuke,
+    Turret,
+    Mine,
+    Weapons,
 }
 
 #[derive(Resource, Clone)]
@@ -31,7 +54,17 @@ pub struct GameSettings {
     pub system: Option<SystemId>,
     pub day_tab: DayTabs,
     pub enemy_info: bool,
+    pub targeting: Option<Targeting>,
     pub just_loaded: bool,
+
+    /// Expedition being prepared in the expeditions tab
+    pub expedition: Expedition,
+
+    /// Trade being prepared in the market tab
+    pub trade: Trade,
+
+    /// Number of soldiers sent on the next sortie
+    pub sortie: u32,
 }
 
 impl Default for GameSettings {
@@ -42,37 +75,67 @@ impl Default for GameSettings {
             system: None,
             day_tab: DayTabs::Overview,
             enemy_info: false,
+            targeting: None,
             just_loaded: false,
+            expedition: Expedition::new(DestinationName::Ruins, 25, 0),
+            trade: Trade::default(),
+            sortie: 10,
         }
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Population {
     pub soldier: u32,
     pub armorer: u32,
     pub refiner: u32,
     pub constructor: u32,
     pub scientist: u32,
+    #[serde(default)]
+    pub farmer: u32,
     pub idle: u32,
+    #[serde(default)]
+    pub wounded: u32,
+    #[serde(default)]
+    pub sick: u32, // Quarantined until the next day
+    #[serde(default)]
+    pub recruit: u32,
 }
 
 impl Population {
     pub fn total(&self) -> u32 {
-        self.soldier + self.armorer + self.refiner + self.constructor + self.scientist + self.idle
+        self.available() + self.wounded + self.sick
     }
-}
 
-impl Default for Population {
-    fn default() -> Self {
-        Self {
-            soldier: 0,
-            armorer: 0,
-            refiner: 0,
-            constructor: 0,
-            scientist: 0,
-            idle: 0,
+    /// Population that isn't wounded or sick
+    pub fn available(&self) -> u32 {
+        self.soldier + self.recruit + self.workers() + self.idle
+    }
+
+    pub fn workers(&self) -> u32 {
+        self.armorer + self.refiner + self.constructor + self.scientist + self.farmer
+    }
+
+    /// Remove `n` workers, taking them from the largest jobs first.
+    /// Returns the number of workers removed
+    pub fn remove_workers(&mut self, n: u32) -> u32 {
+        let n = n.min(self.workers());
+        for _ in 0..n {
+            let job = [
+                &mut self.armorer,
+                &mut self.refiner,
+                &mut self.constructor,
+                &mut self.scientist,
+                &mut self.farmer,
+            ]
+            .into_iter()
+            .max_by_key(|job| **job)
+            .unwrap();
+
+            *job -= 1;
         }
+
+        n
     }
 }
 
@@ -98,11 +161,13 @@ pub struct Spotlight {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Resources {
     pub bullets: f32,
     pub gasoline: f32,
     pub materials: f32,
     pub technology: f32,
+    pub food: f32,
 }
 
 impl Default for Resources {
@@ -112,6 +177,7 @@ impl Default for Resources {
             gasoline: 0.,
             materials: 0.,
             technology: 0.,
+            food: 0.,
         }
     }
 }
@@ -121,12 +187,14 @@ impl PartialOrd for Resources {
         let all_gte = self.bullets >= other.bullets
             && self.gasoline >= other.gasoline
             && self.materials >= other.materials
-            && self.technology >= other.technology;
+            && self.technology >= other.technology
+            && self.food >= other.food;
 
         let all_lte = self.bullets <= other.bullets
             && self.gasoline <= other.gasoline
             && self.materials <= other.materials
-            && self.technology <= other.technology;
+            && self.technology <= other.technology
+            && self.food <= other.food;
 
         match (all_gte, all_lte) {
             (true, true) => Some(Ordering::Equal),
@@ -150,6 +218,7 @@ macro_rules! resources_binary_ops {
                         gasoline: self.gasoline $op rhs.gasoline,
                         materials: self.materials $op rhs.materials,
                         technology: self.technology $op rhs.technology,
+                        food: self.food $op rhs.food,
                     }
                 }
             }
@@ -165,6 +234,7 @@ macro_rules! resources_binary_ops {
                         gasoline: self.gasoline $op float,
                         materials: self.materials $op float,
                         technology: self.technology $op float,
+                        food: self.food $op float,
                     }
                 }
             }
@@ -180,6 +250,7 @@ macro_rules! resources_binary_ops {
                         gasoline: self.gasoline $op float,
                         materials: self.materials $op float,
                         technology: self.technology $op float,
+                        food: self.food $op float,
                     }
                 }
             }
@@ -204,6 +275,7 @@ macro_rules! resources_assignment_ops {
                     self.gasoline $op rhs.gasoline;
                     self.materials $op rhs.materials;
                     self.technology $op rhs.technology;
+                    self.food $op rhs.food;
                 }
             }
 
@@ -215,6 +287,7 @@ macro_rules! resources_assignment_ops {
                     self.gasoline $op float;
                     self.materials $op float;
                     self.technology $op float;
+                    self.food $op float;
                 }
             }
         )*
@@ -228,43 +301,253 @@ resources_assignment_ops!(
     DivAssign, div_assign, /=;
 );
 
+impl Resources {
+    pub fn get(&self, name: ResourceName) -> f32 {
+        match name {
+            ResourceName::Bullets => self.bullets,
+            ResourceName::Gasoline => self.gasoline,
+            ResourceName::Materials => self.materials,
+            ResourceName::Technology => self.technology,
+            ResourceName::Food => self.food,
+        }
+    }
+
+    pub fn get_mut(&mut self, name: ResourceName) -> &mut f32 {
+        match name {
+            ResourceName::Bullets => &mut self.bullets,
+            ResourceName::Gasoline => &mut self.gasoline,
+            ResourceName::Materials => &mut self.materials,
+            ResourceName::Technology => &mut self.technology,
+            ResourceName::Food => &mut self.food,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum ResourceName {
+    Bullets,
+    Gasoline,
+    Materials,
+    Technology,
+    Food,
+}
+
+/// Prices the market returns to, expressed in materials
+pub const BASE_PRICES: Resources = Resources {
+    bullets: 3.,
+    gasoline: 3.,
+    materials: 1.,
+    technology: 5.,
+    food: 1.,
+};
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Constructions {
     pub armory: u32,
     pub refinery: u32,
     pub factory: u32,
     pub laboratory: u32,
+    #[serde(default)]
+    pub drone_bay: u32,
+    #[serde(default = "default_housing")]
+    pub housing: u32,
+    #[serde(default)]
+    pub hospital: u32,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+fn default_housing() -> u32 {
+    1
+}
+
+/// Settings of the weapon placed on a spot
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct WeaponSettings {
     pub aaa: AirFireStrategy,
     pub artillery: FireStrategy,
     pub canon: AirFireStrategy,
+    #[serde(default)]
+    pub cryo: u32,
     pub flamethrower: u32,
     pub machine_gun: u32,
+    #[serde(default = "default_machine_gun_strategy")]
+    pub machine_gun_strategy: FireStrategy,
     pub missile_launcher: u32,
+    #[serde(default = "default_missile_launcher_strategy")]
+    pub missile_launcher_strategy: FireStrategy,
     pub mortar: MortarShell,
+    #[serde(default = "default_sniper")]
+    pub sniper: FireStrategy,
+    #[serde(default = "default_tesla")]
+    pub tesla: FireStrategy,
     pub turret: f32,
+}
+
+fn default_machine_gun_strategy() -> FireStrategy {
+    FireStrategy::Closest
+}
+
+fn default_missile_launcher_strategy() -> FireStrategy {
+    FireStrategy::Strongest
+}
+
+fn default_sniper() -> FireStrategy {
+    FireStrategy::Strongest
+}
+
+fn default_tesla() -> FireStrategy {
+    FireStrategy::Closest
+}
+
+impl Default for WeaponSettings {
+    fn default() -> Self {
+        Self {
+            aaa: AirFireStrategy::None,
+            artillery: FireStrategy::None,
+            canon: AirFireStrategy::None,
+            cryo: 0,
+            flamethrower: 0,
+            machine_gun: 2,
+            machine_gun_strategy: default_machine_gun_strategy(),
+            missile_launcher: 0,
+            missile_launcher_strategy: default_missile_launcher_strategy(),
+            mortar: MortarShell::None,
+            sniper: default_sniper(),
+            tesla: default_tesla(),
+            turret: 0.,
+        }
+    }
+}
+
+impl WeaponSettings {
+    /// Copy only the setting that belongs to `weapon` from `other`
+    pub fn copy_from(&mut self, other: &Self, weapon: WeaponName) {
+        match weapon {
+            WeaponName::AAA => self.aaa = other.aaa.clone(),
+            WeaponName::Artillery => self.artillery = other.artillery.clone(),
+            WeaponName::Canon => self.canon = other.canon.clone(),
+            WeaponName::Cryo => self.cryo = other.cryo,
+            WeaponName::Flamethrower => self.flamethrower = other.flamethrower,
+            WeaponName::MachineGun => {
+                self.machine_gun = other.machine_gun;
+                self.machine_gun_strategy = other.machine_gun_strategy.clone();
+            }
+            WeaponName::MissileLauncher => {
+                self.missile_launcher = other.missile_launcher;
+                self.missile_launcher_strategy = other.missile_launcher_strategy.clone();
+            }
+            WeaponName::Mortar => self.mortar = other.mortar.clone(),
+            WeaponName::Sniper => self.sniper = other.sniper.clone(),
+            WeaponName::Tesla => self.tesla = other.tesla.clone(),
+            WeaponName::Turret => self.turret = other.turret,
+        }
+    }
+}
+
+/// Settings shared by all explosives (not placed on a spot)
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExplosivesSettings {
     pub bomb: FireStrategy,
     pub mine: Size,
 }
 
-#[derive(Clone, Hash, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Spot {
     pub id: Uuid,
     pub weapon: Option<WeaponName>,
+    #[serde(default)]
+    pub settings: WeaponSettings,
+    #[serde(default)]
+    pub damage: f32, // Health the weapon on this spot lost and still has to be repaired
+    #[serde(default)]
+    pub kills: u32, // Enemies killed by the weapon on this spot
+    #[serde(default)]
+    pub x: Option<f32>, // Position from the left of the map. If None, spots are evenly spaced
+    #[serde(default)]
+    pub mount: Mount,
+}
+
+impl Spot {
+    pub fn new(weapon: Option<WeaponName>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            weapon,
+            settings: WeaponSettings::default(),
+            damage: 0.,
+            kills: 0,
+            x: None,
+            mount: Mount::Wall,
+        }
+    }
+
+    /// Rank of the weapon on this spot, earned with kills
+    pub fn rank(&self) -> u32 {
+        RANK_KILLS.iter().filter(|&&k| self.kills >= k).count() as u32
+    }
+}
+
+// Spots are identified by their id (e.g., when dragging them in the armory)
+impl Hash for Spot {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Weapons {
     pub owned: HashMap<WeaponName, u32>,
     pub upgrades: HashMap<WeaponName, (u32, u32)>,
+    #[serde(default)]
+    pub nodes: HashMap<WeaponName, Vec<UpgradeNodeName>>, // Bought nodes of the upgrade trees
     pub spots: Vec<Spot>,
     pub mines: u32,
+    #[serde(default)]
+    pub mine_positions: Vec<(f32, f32)>,
     pub bombs: u32,
     pub nuke: u32,
-    pub settings: WeaponSettings,
+    pub explosives: ExplosivesSettings,
+    #[serde(default)]
+    pub drones: u32,
+    #[serde(default = "default_drone_patrol")]
+    pub drone_patrol: f32, // Center of the patrolled band as fraction of the map's height
+    #[serde(default = "default_sortie_line")]
+    pub sortie_line: f32, // Line held by the soldiers on a sortie as fraction of the map's height
+}
+
+fn default_drone_patrol() -> f32 {
+    0.5
+}
+
+fn default_sortie_line() -> f32 {
+    0.3
+}
+
+impl Weapons {
+    /// Whether `node` of the weapon's upgrade tree has been bought
+    pub fn has_node(&self, weapon: WeaponName, node: UpgradeNodeName) -> bool {
+        self.nodes.get(&weapon).is_some_and(|n| n.contains(&node))
+    }
+
+    /// Place a weapon on the first empty spot, taking over the settings of
+    /// the weapons of the same type. Returns false if there is no empty spot
+    pub fn place(&mut self, weapon: WeaponName) -> bool {
+        if let Some(pos) = self.spots.iter().position(|s| s.weapon.is_none()) {
+            if let Some(settings) = self
+                .spots
+                .iter()
+                .find(|s| s.weapon == Some(weapon))
+                .map(|s| s.settings.clone())
+            {
+                self.spots[pos].settings.copy_from(&settings, weapon);
+            }
+
+            self.spots[pos].weapon = Some(weapon);
+            self.spots[pos].damage = 0.;
+            self.spots[pos].kills = 0;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, EnumIter, Hash, Eq, PartialEq, Serialize, Deserialize)]
@@ -276,8 +559,11 @@ pub enum TechnologyName {
     Aimbot,
     Explosives,
     Homing,
+    Ballistics,
     Charts,
+    Education,
     Productivity,
+    EfficientRefining,
 }
 
 #[derive(Clone, Copy, Debug, EnumIter, Hash, Eq, PartialEq)]
@@ -287,10 +573,41 @@ pub enum TechnologyCategory {
     Economy,
 }
 
+/// Stats of the fortress that technologies can modify
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stat {
+    SoldierDamage,
+    WeaponDamage,
+    WeaponRange,
+    ResourceYield,
+    ResearchSpeed,
+    GasolineCost,
+}
+
+/// Relative change of a stat, e.g. a value of 0.2 increases it by 20%
+pub struct Modifier {
+    pub stat: Stat,
+    pub value: f32,
+}
+
 pub struct Technology {
     pub name: TechnologyName,
     pub price: f32,
     pub category: TechnologyCategory,
+
+    /// Column of the technology in its category's tree
+    pub tier: u32,
+
+    /// Technologies that need to be researched first
+    pub requires: &'static [TechnologyName],
+
+    /// Research points needed. Every scientist produces one point
+    /// per day and laboratory level
+    pub research: f32,
+
+    /// Effects on the fortress' stats once researched
+    pub modifiers: &'static [Modifier],
+
     pub description: &'static str,
 }
 
@@ -301,6 +618,10 @@ impl Technology {
                 name,
                 price: 600.,
                 category: TechnologyCategory::Science,
+                tier: 1,
+                requires: &[],
+                research: 80.,
+                modifiers: &[],
                 description: "\
                     Enables the spotlight during the night. The spotlight \
                     increases the vision of the player, allowing weapons to \
@@ -310,25 +631,45 @@ impl Technology {
                 name: TechnologyName::Electricity,
                 price: 2400.,
                 category: TechnologyCategory::Science,
+                tier: 2,
+                requires: &[TechnologyName::Spotlight],
+                research: 200.,
+                modifiers: &[],
                 description: "\
-                        Enables the option to electrify the fence, doing damage to adjacent enemies.",
+                        Enables the option to electrify the fence, doing damage to adjacent enemies, \
+                        and unlocks the tesla coil.",
             },
             TechnologyName::Physics => Self {
                 name,
                 price: 5000.,
                 category: TechnologyCategory::Science,
+                tier: 3,
+                requires: &[TechnologyName::Electricity],
+                research: 400.,
+                modifiers: &[],
                 description: "Unlocks the nuke.",
             },
             TechnologyName::Marines => Self {
                 name,
                 price: 500.,
                 category: TechnologyCategory::Military,
+                tier: 1,
+                requires: &[],
+                research: 80.,
+                modifiers: &[Modifier {
+                    stat: Stat::SoldierDamage,
+                    value: 1.,
+                }],
                 description: "Doubles the strength of your soldiers.",
             },
             TechnologyName::Aimbot => Self {
                 name,
                 price: 1000.,
                 category: TechnologyCategory::Military,
+                tier: 1,
+                requires: &[],
+                research: 120.,
+                modifiers: &[],
                 description: "\
                     Predict the movement of enemies, shooting at the position where \
                     an enemy is going to be when the bullet arrives. Not relevant for \
@@ -338,30 +679,94 @@ impl Technology {
                 name: TechnologyName::Explosives,
                 price: 2500.,
                 category: TechnologyCategory::Military,
+                tier: 2,
+                requires: &[TechnologyName::Marines],
+                research: 200.,
+                modifiers: &[],
                 description: "Unlocks mines and bombs.",
             },
             TechnologyName::Homing => Self {
                 name: TechnologyName::Homing,
                 price: 5000.,
                 category: TechnologyCategory::Military,
+                tier: 3,
+                requires: &[TechnologyName::Aimbot, TechnologyName::Explosives],
+                research: 400.,
+                modifiers: &[],
                 description: "\
                 Homing bullets are directed to a specific enemy and follow its movement.\
                 Unlocks homing weapons turret and missile launcher.",
             },
+            TechnologyName::Ballistics => Self {
+                name,
+                price: 2000.,
+                category: TechnologyCategory::Military,
+                tier: 2,
+                requires: &[TechnologyName::Aimbot],
+                research: 200.,
+                modifiers: &[
+                    Modifier {
+                        stat: Stat::WeaponDamage,
+                        value: 0.1,
+                    },
+                    Modifier {
+                        stat: Stat::WeaponRange,
+                        value: 0.2,
+                    },
+                ],
+                description: "Weapons deal 10% more damage and their bullets reach 20% further.",
+            },
             TechnologyName::Charts => Self {
                 name: TechnologyName::Charts,
                 price: 1500.,
                 category: TechnologyCategory::Economy,
+                tier: 1,
+                requires: &[],
+                research: 120.,
+                modifiers: &[],
                 description: "\
                         Enables sending expeditions. Expeditions cost gasoline, materials and \
                         population, but can yield interesting rewards after some days.",
             },
+            TechnologyName::Education => Self {
+                name,
+                price: 1200.,
+                category: TechnologyCategory::Economy,
+                tier: 1,
+                requires: &[],
+                research: 120.,
+                modifiers: &[Modifier {
+                    stat: Stat::ResearchSpeed,
+                    value: 0.5,
+                }],
+                description: "Scientists research technologies 50% faster.",
+            },
             TechnologyName::Productivity => Self {
                 name,
                 price: 5000.,
                 category: TechnologyCategory::Economy,
+                tier: 2,
+                requires: &[TechnologyName::Education],
+                research: 300.,
+                modifiers: &[Modifier {
+                    stat: Stat::ResourceYield,
+                    value: 0.5,
+                }],
                 description: "Armorers, refiners and constructors produce 50% more resources.",
             },
+            TechnologyName::EfficientRefining => Self {
+                name,
+                price: 2000.,
+                category: TechnologyCategory::Economy,
+                tier: 2,
+                requires: &[TechnologyName::Charts],
+                research: 200.,
+                modifiers: &[Modifier {
+                    stat: Stat::GasolineCost,
+                    value: -0.15,
+                }],
+                description: "Weapons, the fence and the spotlight use 15% less gasoline.",
+            },
         }
     }
 
@@ -370,52 +775,71 @@ impl Technology {
     }
 }
 
+/// Technologies waiting to be researched. Only the first one
+/// in the queue is researched, the rest follow in order
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Research {
+    pub queue: VecDeque<TechnologyName>,
+
+    /// Research points spent on the first technology in the queue
+    pub progress: f32,
+}
+
 #[derive(Clone, Copy, Debug, EnumIter, Hash, Eq, PartialEq, Serialize, Deserialize)]
-pub enum ExpeditionName {
-    Small,
-    Medium,
-    Large,
+pub enum DestinationName {
+    Ruins,
+    FuelDepot,
+    MilitaryBase,
+    Hive,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
-pub struct ExpeditionReward {
-    pub population: u32,
-    pub resources: Resources,
-    pub mines: u32,
-    pub bombs: u32,
+impl DestinationName {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DestinationName::Ruins => "Ruins",
+            DestinationName::FuelDepot => "Fuel depot",
+            DestinationName::MilitaryBase => "Military base",
+            DestinationName::Hive => "Hive",
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
-pub enum ExpeditionStatus {
-    /// The expedition is still ongoing
-    Ongoing,
+/// Location on the expedition map that expeditions can be sent to
+pub struct Destination {
+    pub name: DestinationName,
 
-    /// The expedition did not return
-    Lost,
+    /// Position on the expedition map as fraction of its size
+    pub position: (f32, f32),
 
-    /// The expedition returns with reward
-    Returned(ExpeditionReward),
-}
+    pub description: &'static str,
+    pub duration: &'static str,
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Expedition {
-    pub name: ExpeditionName,
-    pub duration: String,
-    pub day: u32,
+    /// The expedition is lost if it hasn't returned by this day
     pub max_day: u32,
+
+    /// Probability per day to return with the recommended escort
     pub return_prob: f32,
+
+    /// Recommended size of the escort
     pub population: u32,
+
+    /// Price for the recommended escort
     pub price: Resources,
-    pub status: ExpeditionStatus,
+
+    /// Maximum reward per day away with the recommended escort
+    pub loot: ExpeditionReward,
 }
 
-impl Expedition {
-    pub fn get(name: ExpeditionName) -> Self {
+impl Destination {
+    pub fn get(name: DestinationName) -> Self {
         match name {
-            ExpeditionName::Small => Self {
+            DestinationName::Ruins => Self {
                 name,
-                duration: "1-2 days".to_string(),
-                day: 0,
+                position: (0.25, 0.55),
+                description: "\
+                    The remains of a nearby town. Safe and close, but most of \
+                    it has already been scavenged. Survivors may still hide there.",
+                duration: "1-2 days",
                 max_day: 3,
                 return_prob: 0.7,
                 population: 25,
@@ -424,73 +848,512 @@ impl Expedition {
                     materials: 75.,
                     ..default()
                 },
-                status: ExpeditionStatus::Ongoing,
+                loot: ExpeditionReward {
+                    population: 40,
+                    resources: Resources {
+                        bullets: 100.,
+                        materials: 600.,
+                        food: 300.,
+                        ..default()
+                    },
+                    mines: 0,
+                    bombs: 0,
+                },
             },
-            ExpeditionName::Medium => Self {
+            DestinationName::FuelDepot => Self {
                 name,
-                duration: "1-3 days".to_string(),
-                day: 0,
+                position: (0.75, 0.6),
+                description: "\
+                    An abandoned fuel depot along the highway. The tanks \
+                    should still hold plenty of gasoline.",
+                duration: "1-3 days",
                 max_day: 4,
                 return_prob: 0.5,
-                population: 75,
+                population: 50,
                 price: Resources {
-                    gasoline: 300.,
+                    gasoline: 100.,
                     materials: 150.,
                     ..default()
                 },
-                status: ExpeditionStatus::Ongoing,
+                loot: ExpeditionReward {
+                    population: 10,
+                    resources: Resources {
+                        gasoline: 1500.,
+                        ..default()
+                    },
+                    mines: 0,
+                    bombs: 0,
+                },
             },
-            ExpeditionName::Large => Self {
+            DestinationName::MilitaryBase => Self {
                 name,
-                duration: "2-4 days".to_string(),
-                day: 0,
+                position: (0.2, 0.2),
+                description: "\
+                    A military base overrun by the bugs. Its armory is \
+                    full of ammunition and explosives, but it's heavily infested.",
+                duration: "2-4 days",
                 max_day: 5,
-                return_prob: 0.3,
+                return_prob: 0.35,
+                population: 100,
+                price: Resources {
+                    gasoline: 400.,
+                    materials: 200.,
+                    ..default()
+                },
+                loot: ExpeditionReward {
+                    population: 10,
+                    resources: Resources {
+                        bullets: 2000.,
+                        materials: 500.,
+                        ..default()
+                    },
+                    mines: 4,
+                    bombs: 2,
+                },
+            },
+            DestinationName::Hive => Self {
+                name,
+                position: (0.7, 0.15),
+                description: "\
+                    The nest the swarm comes from. Studying it could teach our \
+                    scientists a lot, if anyone comes back to tell.",
+                duration: "2-5 days",
+                max_day: 6,
+                return_prob: 0.25,
                 population: 125,
                 price: Resources {
-                    gasoline: 450.,
-                    materials: 225.,
+                    gasoline: 500.,
+                    materials: 300.,
                     ..default()
                 },
-                status: ExpeditionStatus::Ongoing,
+                loot: ExpeditionReward {
+                    population: 0,
+                    resources: Resources {
+                        technology: 1500.,
+                        ..default()
+                    },
+                    mines: 0,
+                    bombs: 0,
+                },
             },
         }
     }
 
     pub fn iter() -> impl Iterator<Item = Self> {
-        ExpeditionName::iter().map(Self::get)
+        DestinationName::iter().map(Self::get)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ExpeditionReward {
+    pub population: u32,
+    pub resources: Resources,
+    pub mines: u32,
+    pub bombs: u32,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ExpeditionStatus {
+    /// The expedition is still ongoing
+    Ongoing,
+
+    /// The expedition did not return
+    Lost,
+
+    /// The expedition returns with reward
+    Returned(ExpeditionReward),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Expedition {
+    pub id: Uuid,
+    pub destination: DestinationName,
+    pub day: u32,
+
+    /// Size of the escort taken from the idle population
+    pub population: u32,
+
+    /// Soldiers protecting the escort. They return with the expedition
+    pub soldiers: u32,
+
+    pub status: ExpeditionStatus,
+
+    /// Event waiting for the player's decision
+    #[serde(default)]
+    pub event: Option<String>,
+
+    /// Decisions taken on the events during the expedition
+    #[serde(default)]
+    pub decisions: Vec<ExpeditionDecision>,
+
+    /// Combined effect of the decisions
+    #[serde(default)]
+    pub effect: EventEffect,
+}
+
+impl Expedition {
+    pub fn new(destination: DestinationName, population: u32, soldiers: u32) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            destination,
+            day: 0,
+            population,
+            soldiers,
+            status: ExpeditionStatus::Ongoing,
+            event: None,
+            decisions: Vec::new(),
+            effect: EventEffect::default(),
+        }
+    }
+
+    /// Size of the escort relative to the destination's recommended size
+    fn escort(&self) -> f32 {
+        let destination = Destination::get(self.destination);
+        (self.population as f32 / destination.population as f32).min(MAX_ESCORT_FACTOR)
+    }
+
+    /// The price grows with the size of the escort
+    pub fn price(&self) -> Resources {
+        let destination = Destination::get(self.destination);
+        destination.price * self.population as f32 / destination.population as f32
+    }
+
+    /// Probability per day to return. A larger escort is more likely
+    /// to return, and every soldier protects it a bit further
+    pub fn return_prob(&self) -> f32 {
+        let destination = Destination::get(self.destination);
+        (destination.return_prob * self.escort().sqrt()
+            + self.soldiers as f32 * SOLDIER_PROTECTION
+            + self.effect.return_prob)
+            .clamp(0., MAX_RETURN_PROB)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.status, ExpeditionStatus::Ongoing)
+    }
+
+    /// Take a decision on the pending event
+    pub fn decide(&mut self, event: &ExpeditionEvent, choice: usize) {
+        let effect = &event.choices[choice].effect;
+        self.soldiers -= effect.soldiers.min(self.soldiers);
+        self.effect += effect;
+
+        self.decisions.push(ExpeditionDecision {
+            day: self.day,
+            event: event.id.clone(),
+            choice: event.choices[choice].label.clone(),
+        });
+        self.event = None;
     }
 
     pub fn update(&mut self) {
+        let destination = Destination::get(self.destination);
         self.day += 1;
 
-        if self.day == self.max_day {
+        // Events without a decision are ignored
+        self.event = None;
+
+        // The longer away and the larger the escort, the more they bring back
+        let scale = self.day as f32 * self.escort() * self.effect.reward_factor;
+        let amount = |loot: f32| (loot * scale * random::<f32>() + 0.5).floor();
+
+        if self.day == destination.max_day {
             self.status = ExpeditionStatus::Lost;
-        } else if random::<f32>() < self.return_prob {
+        } else if random::<f32>() < self.return_prob() {
+            let loot = destination.loot;
             self.status = ExpeditionStatus::Returned(ExpeditionReward {
-                population: ((self.population * self.day.pow(3)) as f32 * random::<f32>() + 0.5)
-                    as u32,
+                population: amount(loot.population as f32) as u32 + self.effect.population,
                 resources: Resources {
-                    bullets: (self.price.gasoline as u32 * self.day.pow(3)) as f32
-                        * random::<f32>()
-                        + 0.5,
-                    gasoline: (self.price.gasoline as u32 * self.day.pow(3).pow(2)) as f32
-                        * random::<f32>()
-                        + 0.5,
-                    materials: (self.price.materials as u32 * self.day.pow(3).pow(2)) as f32
-                        * random::<f32>()
-                        + 0.5,
-                    technology: (self.price.materials as u32 * self.day.pow(3).pow(2)) as f32
-                        * random::<f32>()
-                        + 0.5,
-                },
-                mines: (self.day.pow(2) as f32 * random::<f32>() + 0.5) as u32,
-                bombs: (self.day as f32 * random::<f32>() + 0.5) as u32,
+                    bullets: amount(loot.resources.bullets),
+                    gasoline: amount(loot.resources.gasoline),
+                    materials: amount(loot.resources.materials),
+                    technology: amount(loot.resources.technology),
+                    food: amount(loot.resources.food),
+                } + &self.effect.resources,
+                mines: amount(loot.mines as f32) as u32 + self.effect.mines,
+                bombs: amount(loot.bombs as f32) as u32 + self.effect.bombs,
             });
         }
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExpeditionDecision {
+    pub day: u32,
+    pub event: String,
+    pub choice: String,
+}
+
+/// Record of an expedition that returned or got lost, kept in the save
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExpeditionLog {
+    pub destination: DestinationName,
+
+    /// Day the expedition was resolved
+    pub day: u32,
+
+    pub returned: bool,
+
+    /// Decisions taken on the events during the expedition
+    pub decisions: Vec<ExpeditionDecision>,
+}
+
+/// Effect of a decision on an expedition
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventEffect {
+    /// Extra population, resources and explosives brought back if the expedition returns
+    pub population: u32,
+    pub resources: Resources,
+    pub mines: u32,
+    pub bombs: u32,
+
+    /// Multiplier of the loot found
+    pub reward_factor: f32,
+
+    /// Change of the daily return probability
+    pub return_prob: f32,
+
+    /// Soldiers killed
+    pub soldiers: u32,
+}
+
+impl Default for EventEffect {
+    fn default() -> Self {
+        Self {
+            population: 0,
+            resources: Resources::default(),
+            mines: 0,
+            bombs: 0,
+            reward_factor: 1.,
+            return_prob: 0.,
+            soldiers: 0,
+        }
+    }
+}
+
+impl AddAssign<&EventEffect> for EventEffect {
+    fn add_assign(&mut self, rhs: &EventEffect) {
+        self.population += rhs.population;
+        self.resources += &rhs.resources;
+        self.mines += rhs.mines;
+        self.bombs += rhs.bombs;
+        self.reward_factor *= rhs.reward_factor;
+        self.return_prob += rhs.return_prob;
+        self.soldiers += rhs.soldiers;
+    }
+}
+
+/// Conditions for an event to happen. No destinations means any destination
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EventConditions {
+    pub destinations: Vec<DestinationName>,
+    pub min_day: u32,
+    pub min_soldiers: u32,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct EventChoice {
+    pub label: String,
+    #[serde(default)]
+    pub price: Resources,
+    #[serde(default)]
+    pub effect: EventEffect,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct ExpeditionEvent {
+    pub id: String,
+    pub weight: f32,
+    #[serde(default)]
+    pub conditions: EventConditions,
+    pub text: String,
+    pub choices: Vec<EventChoice>,
+}
+
+impl ExpeditionEvent {
+    pub fn applies(&self, expedition: &Expedition) -> bool {
+        let conditions = &self.conditions;
+        (conditions.destinations.is_empty()
+            || conditions.destinations.contains(&expedition.destination))
+            && expedition.day >= conditions.min_day
+            && expedition.soldiers >= conditions.min_soldiers
+    }
+}
+
+/// Events that can happen during expeditions, read from `assets/data/expedition_events.json`
+#[derive(Resource)]
+pub struct ExpeditionEvents(pub Vec<ExpeditionEvent>);
+
+impl Default for ExpeditionEvents {
+    fn default() -> Self {
+        Self(
+            serde_json::from_str(include_str!("../../assets/data/expedition_events.json"))
+                .expect("Invalid expedition events."),
+        )
+    }
+}
+
+impl ExpeditionEvents {
+    pub fn get(&self, id: &str) -> Option<&ExpeditionEvent> {
+        self.0.iter().find(|e| e.id == id)
+    }
+
+    /// Draw a random event that applies to the expedition, weighted by the events' weights
+    pub fn roll(&self, expedition: &Expedition) -> Option<&ExpeditionEvent> {
+        let events: Vec<_> = self.0.iter().filter(|e| e.applies(expedition)).collect();
+        let dist = WeightedIndex::new(events.iter().map(|e| e.weight)).ok()?;
+        Some(events[dist.sample(&mut thread_rng())])
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MoraleCause {
+    Kills,
+    QuietNight,
+    Breach,
+    ExpeditionLost,
+    Shortage,
+    Starvation,
+}
+
+impl MoraleCause {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MoraleCause::Kills => "Bugs killed",
+            MoraleCause::QuietNight => "Night without casualties",
+            MoraleCause::Breach => "Bugs entered the fortress",
+            MoraleCause::ExpeditionLost => "Expedition lost",
+            MoraleCause::Shortage => "Resource shortage",
+            MoraleCause::Starvation => "Starvation",
+        }
+    }
+}
+
+/// Change of the morale on a given day
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MoraleChange {
+    pub day: u32,
+    pub cause: MoraleCause,
+    pub value: f32,
+}
+
+/// Morale of the population, between 0 and `MAX_MORALE`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Morale {
+    pub value: f32,
+
+    /// Recent changes, used to explain the current morale
+    pub changes: Vec<MoraleChange>,
+}
+
+impl Default for Morale {
+    fn default() -> Self {
+        Self {
+            value: INITIAL_MORALE,
+            changes: vec![],
+        }
+    }
+}
+
+impl Morale {
+    /// Change the morale, merging the change with others of the same day and cause
+    pub fn add(&mut self, day: u32, cause: MoraleCause, value: f32) {
+        let value = (self.value + value).clamp(0., MAX_MORALE) - self.value;
+        if value == 0. {
+            return;
+        }
+
+        self.value += value;
+
+        self.changes.retain(|c| c.day + MORALE_HISTORY > day);
+        match self
+            .changes
+            .iter_mut()
+            .find(|c| c.day == day && c.cause == cause)
+        {
+            Some(change) => change.value += value,
+            None => self.changes.push(MoraleChange { day, cause, value }),
+        }
+    }
+
+    /// Relative change of productivity and soldier damage. Zero at half the maximum morale
+    pub fn modifier(&self) -> f32 {
+        (self.value / MAX_MORALE * 2. - 1.) * MORALE_EFFECT
+    }
+}
+
+/// Market where the resources are exchanged. The prices drift every day
+/// with the supply and demand, and react to the player's trades
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Market {
+    pub prices: Resources,
+
+    /// Prices at the start of the last days, oldest first
+    pub history: VecDeque<Resources>,
+}
+
+impl Default for Market {
+    fn default() -> Self {
+        Self {
+            prices: BASE_PRICES,
+            history: VecDeque::from([BASE_PRICES]),
+        }
+    }
+}
+
+impl Market {
+    /// Amount of `to` received per unit of `from`
+    pub fn rate(&self, from: ResourceName, to: ResourceName, markup: f32) -> f32 {
+        self.prices.get(from) / self.prices.get(to) * (1. - MARKET_SPREAD) / (1. + markup)
+    }
+
+    /// Selling a resource lowers its price, while buying one raises it
+    pub fn trade(&mut self, from: ResourceName, to: ResourceName, amount: f32) {
+        let value = amount * self.prices.get(from);
+        *self.prices.get_mut(from) *= (-value / MARKET_DEPTH).exp();
+        *self.prices.get_mut(to) *= (value / MARKET_DEPTH).exp();
+    }
+
+    /// Move the prices for a new day. They randomly drift with the supply
+    /// and demand, while slowly returning to their base price. Resources the
+    /// fortress had a surplus of yesterday (positive `net`) get cheaper, and
+    /// those it ran a deficit of more expensive
+    pub fn update(&mut self, net: &Resources) {
+        let dist = Normal::new(0., MARKET_VOLATILITY).unwrap();
+        for name in ResourceName::iter() {
+            let price = self.prices.get_mut(name);
+            *price += (BASE_PRICES.get(name) - *price) * MARKET_REVERSION;
+            *price *= (-net.get(name) * *price * MARKET_SUPPLY / MARKET_DEPTH).exp();
+            *price *= dist.sample(&mut thread_rng()).exp();
+        }
+
+        self.history.push_back(self.prices);
+        while self.history.len() > MARKET_HISTORY {
+            self.history.pop_front();
+        }
+    }
+}
+
+/// Trade being prepared in the market tab
+#[derive(Clone)]
+pub struct Trade {
+    pub from: ResourceName,
+    pub to: ResourceName,
+    pub amount: f32,
+}
+
+impl Default for Trade {
+    fn default() -> Self {
+        Self {
+            from: ResourceName::Materials,
+            to: ResourceName::Bullets,
+            amount: 100.,
+        }
+    }
+}
+
 #[derive(Clone, Resource, Serialize, Deserialize)]
 pub struct Player {
     pub day: u32,
@@ -502,8 +1365,19 @@ pub struct Player {
     pub constructions: Constructions,
     pub weapons: Weapons,
     pub technology: HashSet<TechnologyName>,
-    pub expedition: Option<Expedition>,
+    #[serde(default)]
+    pub research: Research,
+    #[serde(default)]
+    pub expeditions: Vec<Expedition>,
+    #[serde(default)]
+    pub expedition_logs: Vec<ExpeditionLog>,
     pub stats: HashMap<u32, NightInfo>,
+    #[serde(default)]
+    pub day_events: HashMap<u32, DayEvent>,
+    #[serde(default)]
+    pub morale: Morale,
+    #[serde(default)]
+    pub market: Market,
 }
 
 impl Player {
@@ -516,7 +1390,11 @@ impl Player {
                 refiner: 40,
                 constructor: 40,
                 scientist: 40,
+                farmer: 40,
                 idle: 0,
+                wounded: 0,
+                sick: 0,
+                recruit: 0,
             },
             wall: Wall {
                 health: 1_000.,
@@ -544,90 +1422,551 @@ impl Player {
                 gasoline: 400.,
                 materials: 400.,
                 technology: 400.,
+                food: 400.,
             },
             constructions: Constructions {
                 armory: 1,
                 refinery: 1,
                 factory: 1,
                 laboratory: 1,
+                drone_bay: 0,
+                housing: 1,
+                hospital: 0,
             },
+            morale: Morale::default(),
+            market: Market::default(),
             weapons: Weapons {
                 owned: HashMap::from([(WeaponName::MachineGun, 2)]),
                 upgrades: HashMap::default(),
+                nodes: HashMap::default(),
                 spots: vec![
-                    Spot {
-                        id: Uuid::new_v4(),
-                        weapon: Some(WeaponName::MachineGun),
-                    },
-                    Spot {
-                        id: Uuid::new_v4(),
-                        weapon: Some(WeaponName::MachineGun),
-                    },
+                    Spot::new(Some(WeaponName::MachineGun)),
+                    Spot::new(Some(WeaponName::MachineGun)),
                 ],
                 mines: 0,
+                mine_positions: Vec::new(),
                 bombs: 0,
                 nuke: 0,
-                settings: WeaponSettings {
-                    aaa: AirFireStrategy::None,
-                    artillery: FireStrategy::None,
-                    canon: AirFireStrategy::None,
-                    flamethrower: 0,
-                    machine_gun: 2,
-                    mortar: MortarShell::None,
-                    turret: 0.,
-                    missile_launcher: 0,
+                explosives: ExplosivesSettings {
                     bomb: FireStrategy::Density,
                     mine: Size::Medium,
                 },
+                drones: 0,
+                drone_patrol: default_drone_patrol(),
+                sortie_line: default_sortie_line(),
             },
             technology: HashSet::default(),
-            expedition: None,
+            research: Research::default(),
+            expeditions: Vec::new(),
+            expedition_logs: Vec::new(),
             stats: HashMap::default(),
+            day_events: HashMap::default(),
         }
     }
 
     pub fn new_resources(&self) -> Resources {
-        let productivity = if self.has_tech(TechnologyName::Productivity) {
-            1.5
-        } else {
-            1.
-        };
-
         Resources {
             bullets: (self.population.armorer * self.constructions.armory) as f32,
             gasoline: (self.population.refiner * self.constructions.refinery) as f32,
             materials: (self.population.constructor * self.constructions.factory) as f32,
             technology: (self.population.scientist * self.constructions.laboratory) as f32,
-        } * RESOURCE_FACTOR * productivity
+            food: self.population.farmer as f32,
+        } * RESOURCE_FACTOR * self.modifier(Stat::ResourceYield)
+    }
+
+    /// Maximum population the fortress can house
+    pub fn housing_capacity(&self) -> u32 {
+        self.constructions.housing * HOUSING_CAPACITY
+    }
+
+    /// Number of people that can still be housed in the fortress
+    pub fn free_housing(&self) -> u32 {
+        self.housing_capacity()
+            .saturating_sub(self.population.total())
+    }
+
+    /// Add `n` people to the idle population, up to the housing capacity.
+    /// Returns the number of people housed
+    pub fn house(&mut self, n: u32) -> u32 {
+        let housed = n.min(self.free_housing());
+        self.population.idle += housed;
+        housed
+    }
+
+    /// Exchange `amount` of a resource for another at the market's rate,
+    /// increased with `markup`. Returns the change of the resources
+    pub fn trade(
+        &mut self,
+        from: ResourceName,
+        to: ResourceName,
+        amount: f32,
+        markup: f32,
+    ) -> Option<Resources> {
+        if from == to || amount <= 0. || self.resources.get(from) < amount {
+            return None;
+        }
+
+        let mut change = Resources::default();
+        *change.get_mut(from) -= amount;
+        *change.get_mut(to) += amount * self.market.rate(from, to, markup);
+
+        self.resources += &change;
+        self.market.trade(from, to, amount);
+
+        Some(change)
+    }
+
+    /// Recruits that can still be trained with the current bullets
+    pub fn max_recruits(&self) -> u32 {
+        (self.resources.bullets / TRAINING_COST).max(0.) as u32
+    }
+
+    /// Turn the recruits into soldiers, paying for their training. Recruits
+    /// that can't be paid for return to the idle population. Returns the
+    /// number of trained soldiers
+    pub fn train_recruits(&mut self) -> u32 {
+        let trained = self.population.recruit.min(self.max_recruits());
+
+        self.resources.bullets -= trained as f32 * TRAINING_COST;
+        self.population.soldier += trained;
+        self.population.idle += self.population.recruit - trained;
+        self.population.recruit = 0;
+
+        trained
+    }
+
+    /// Maximum number of wounded treated in the hospital
+    pub fn hospital_capacity(&self) -> u32 {
+        self.constructions.hospital * HOSPITAL_CAPACITY
+    }
+
+    /// Part of the wounded in the hospital recover and return as idle population,
+    /// while part of the untreated wounded die. Returns the recovered and died
+    pub fn treat_wounded(&mut self) -> (u32, u32) {
+        let treated = self.population.wounded.min(self.hospital_capacity());
+        let untreated = self.population.wounded - treated;
+
+        let rate = (HOSPITAL_RECOVERY * self.constructions.hospital as f32).min(1.);
+        let recovered = (treated as f32 * rate).ceil() as u32;
+        let died = (untreated as f32 * WOUNDED_DEATH_RATE).ceil() as u32;
+
+        self.population.wounded -= recovered + died;
+        self.population.idle += recovered;
+
+        (recovered, died)
+    }
+
+    /// Food eaten by the population per day
+    pub fn food_consumption(&self) -> f32 {
+        self.population.total() as f32 * FOOD_CONSUMPTION
+    }
+
+    /// Let the population eat. Part of the people without food starve.
+    /// Returns the number of people that starved
+    pub fn eat(&mut self) -> u32 {
+        let consumption = self.food_consumption();
+        if self.resources.food >= consumption {
+            self.resources.food -= consumption;
+            return 0;
+        }
+
+        let hungry = (consumption - self.resources.food) / FOOD_CONSUMPTION;
+        self.resources.food = 0.;
+
+        // Starve the wounded, sick and idle population first, then the workers and lastly the soldiers
+        let total = self.population.total();
+        let mut starved = (hungry * STARVATION_RATE).ceil() as u32;
+
+        let wounded = starved.min(self.population.wounded);
+        self.population.wounded -= wounded;
+        starved -= wounded;
+        let sick = starved.min(self.population.sick);
+        self.population.sick -= sick;
+        starved -= sick;
+        let idle = starved.min(self.population.idle);
+        self.population.idle -= idle;
+        starved -= idle;
+        starved -= self.population.remove_workers(starved);
+        self.population.soldier -= starved.min(self.population.soldier);
+
+        total - self.population.total()
     }
 
     pub fn has_tech(&self, tech: TechnologyName) -> bool {
         self.technology.contains(&tech)
     }
 
+    /// Multiplier of a stat given by the modifiers of the researched
+    /// technologies and the population's morale
+    pub fn modifier(&self, stat: Stat) -> f32 {
+        let mut change: f32 = self
+            .technology
+            .iter()
+            .flat_map(|t| Technology::get(*t).modifiers)
+            .filter(|m| m.stat == stat)
+            .map(|m| m.value)
+            .sum();
+
+        if matches!(stat, Stat::ResourceYield | Stat::SoldierDamage) {
+            change += self.morale.modifier();
+        }
+
+        (1. + change).max(0.)
+    }
+
+    /// Resources paid during the night, after applying the cost modifiers
+    pub fn cost(&self, price: &Resources) -> Resources {
+        Resources {
+            gasoline: price.gasoline * self.modifier(Stat::GasolineCost),
+            ..*price
+        }
+    }
+
+    pub fn is_researching(&self, tech: TechnologyName) -> bool {
+        self.research.queue.contains(&tech)
+    }
+
+    /// Research points produced per day
+    pub fn research_rate(&self) -> f32 {
+        (self.population.scientist * self.constructions.laboratory) as f32
+            * self.modifier(Stat::ResearchSpeed)
+    }
+
+    /// Whether all prerequisites are researched or queued before this technology
+    pub fn can_research(&self, tech: TechnologyName) -> bool {
+        Technology::get(tech)
+            .requires
+            .iter()
+            .all(|t| self.has_tech(*t) || self.is_researching(*t))
+    }
+
+    /// Number of days until the technology is researched,
+    /// taking into account the technologies queued before it
+    pub fn research_eta(&self, tech: TechnologyName) -> Option<u32> {
+        let rate = self.research_rate();
+        if rate <= 0. {
+            return None;
+        }
+
+        let mut points = -self.research.progress;
+        for t in self.research.queue.iter() {
+            points += Technology::get(*t).research;
+            if *t == tech {
+                return Some((points / rate).ceil().max(1.) as u32);
+            }
+        }
+
+        None
+    }
+
+    /// Spend a day of research on the queue and return the finished technologies
+    pub fn update_research(&mut self) -> Vec<TechnologyName> {
+        let mut finished = vec![];
+
+        self.research.progress += self.research_rate();
+        while let Some(tech) = self.research.queue.front().copied() {
+            let needed = Technology::get(tech).research;
+            if self.research.progress < needed {
+                break;
+            }
+
+            self.research.progress -= needed;
+            self.research.queue.pop_front();
+            self.technology.insert(tech);
+            finished.push(tech);
+        }
+
+        // Points don't accumulate while there is nothing to research
+        if self.research.queue.is_empty() {
+            self.research.progress = 0.;
+        }
+
+        finished
+    }
+
+    /// Remove a technology from the queue, together with the queued
+    /// technologies that depend on it, and refund their price
+    pub fn cancel_research(&mut self, tech: TechnologyName) {
+        if self.research.queue.front() == Some(&tech) {
+            self.research.progress = 0.;
+        }
+
+        if let Some(i) = self.research.queue.iter().position(|t| *t == tech) {
+            self.research.queue.remove(i);
+            self.resources.technology += Technology::get(tech).price;
+
+            let dependents: Vec<_> = self
+                .research
+                .queue
+                .iter()
+                .filter(|t| !self.can_research(**t))
+                .copied()
+                .collect();
+
+            for t in dependents {
+                self.cancel_research(t);
+            }
+        }
+    }
+
     pub fn get_soldier_damage(&self) -> u32 {
-        if self.has_tech(TechnologyName::Marines) {
-            2 * SOLDIER_BASE_DAMAGE
-        } else {
-            SOLDIER_BASE_DAMAGE
+        (SOLDIER_BASE_DAMAGE as f32 * self.modifier(Stat::SoldierDamage)).round() as u32
+    }
+
+    /// Randomly pick today's event, if any
+    pub fn roll_day_event(&mut self) {
+        if random::<f32>() >= DAY_EVENT_PROB {
+            return;
+        }
+
+        let events: Vec<_> = DayEventName::iter()
+            .filter(|e| e.weight(self) > 0.)
+            .collect();
+
+        if let Ok(dist) = WeightedIndex::new(events.iter().map(|e| e.weight(self))) {
+            let name = events[dist.sample(&mut thread_rng())];
+            self.day_events.insert(
+                self.day,
+                DayEvent {
+                    name,
+                    amount: name.roll_amount(self),
+                    choice: None,
+                },
+            );
         }
     }
 
-    pub fn resolve_expedition(&mut self) {
-        if let Some(ref mut expedition) = self.expedition {
-            match expedition.status {
-                ExpeditionStatus::Returned(reward) => {
-                    self.population.idle += reward.population;
-                    self.resources += &reward.resources;
-                    self.weapons.mines += reward.mines;
-                    self.weapons.bombs += reward.bombs;
+    /// Today's event if the player still has to decide on it
+    pub fn pending_day_event(&self) -> Option<&DayEvent> {
+        self.day_events
+            .get(&self.day)
+            .filter(|e| e.choice.is_none())
+    }
 
-                    self.expedition = None;
-                }
-                ExpeditionStatus::Lost => self.expedition = None,
-                ExpeditionStatus::Ongoing => (),
+    /// Pay for the chosen option of today's event and apply its effects
+    pub fn resolve_day_event(&mut self, choice: usize) {
+        let Some(event) = self.day_events.get_mut(&self.day) else {
+            return;
+        };
+
+        event.choice = Some(choice);
+        let (name, amount) = (event.name, event.amount);
+
+        self.resources -= &name.choices(amount)[choice].price;
+
+        match (name, choice) {
+            (DayEventName::Refugees, 0) => {
+                // The fortress can have filled up since the event was rolled,
+                // don't charge for the refugees that are turned away
+                let housed = self.house(amount);
+                self.resources.materials += 2. * (amount - housed) as f32;
             }
+            (DayEventName::Storm, 1) => {
+                self.fence.health = (self.fence.health - amount as f32).max(0.);
+            }
+            (DayEventName::Disease, 0) => {
+                self.population.sick += self.population.remove_workers(amount);
+            }
+            (DayEventName::Disease, 1) => {
+                self.population.remove_workers(amount / 2);
+            }
+            (DayEventName::SupplyCache, _) => self.resources.materials += amount as f32,
+            (DayEventName::Saboteur, 1) => {
+                self.resources.gasoline = (self.resources.gasoline - amount as f32).max(0.);
+            }
+            _ => (),
+        }
+    }
+
+    /// Apply the outcome of the first finished expedition.
+    /// Returns false if there was no finished expedition
+    pub fn resolve_expedition(&mut self) -> bool {
+        let Some(i) = self.expeditions.iter().position(|e| e.is_finished()) else {
+            return false;
+        };
+
+        let expedition = self.expeditions.remove(i);
+        self.expedition_logs.push(ExpeditionLog {
+            destination: expedition.destination,
+            day: self.day,
+            returned: matches!(expedition.status, ExpeditionStatus::Returned(_)),
+            decisions: expedition.decisions,
+        });
+
+        if let ExpeditionStatus::Returned(reward) = expedition.status {
+            self.population.soldier += expedition.soldiers;
+            self.house(reward.population);
+            self.resources += &reward.resources;
+            self.weapons.mines += reward.mines;
+            self.weapons.bombs += reward.bombs;
+        } else {
+            self.morale.add(
+                self.day,
+                MoraleCause::ExpeditionLost,
+                -MORALE_EXPEDITION_LOST,
+            );
+        }
+
+        true
+    }
+
+    /// Part of the population leaves the fortress when the morale is
+    /// very low. Returns the number of people that deserted
+    pub fn desert(&mut self) -> u32 {
+        if self.morale.value >= DESERTION_MORALE {
+            return 0;
         }
+
+        let rate = DESERTION_RATE * (1. - self.morale.value / DESERTION_MORALE);
+        let deserters = (self.population.total() as f32 * rate).round() as u32;
+
+        // The idle population leaves first, soldiers stay loyal
+        let idle = deserters.min(self.population.idle);
+        self.population.idle -= idle;
+
+        idle + self.population.remove_workers(deserters - idle)
+    }
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DayEventName {
+    Refugees,
+    Storm,
+    Disease,
+    SupplyCache,
+    Saboteur,
+}
+
+/// Option the player can choose when an event happens
+pub struct DayChoice {
+    pub label: &'static str,
+    pub price: Resources,
+}
+
+impl DayEventName {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DayEventName::Refugees => "Refugees",
+            DayEventName::Storm => "Storm",
+            DayEventName::Disease => "Disease",
+            DayEventName::SupplyCache => "Supply cache",
+            DayEventName::Saboteur => "Saboteur",
+        }
+    }
+
+    /// Relative probability of the event happening today
+    pub fn weight(&self, player: &Player) -> f32 {
+        let day = player.day as f32;
+        match self {
+            DayEventName::Refugees if player.free_housing() > 0 => 3.,
+            DayEventName::Storm if player.fence.health > 0. => 1. + 0.1 * day,
+            DayEventName::Disease if player.day >= 3 => 1. + 0.05 * day,
+            DayEventName::SupplyCache => 2.,
+            DayEventName::Saboteur if player.day >= 5 && player.resources.gasoline > 0. => 1.,
+            _ => 0.,
+        }
+    }
+
+    /// Roll the magnitude of the event, e.g. the number of refugees
+    fn roll_amount(&self, player: &Player) -> u32 {
+        let f = random::<f32>();
+        let amount = match self {
+            DayEventName::Refugees => (30. + 50. * f).min(player.free_housing() as f32),
+            DayEventName::Storm => player.fence.health * (0.2 + 0.3 * f),
+            DayEventName::Disease => player.population.workers() as f32 * (0.1 + 0.1 * f),
+            DayEventName::SupplyCache => 100. * player.day as f32 * (0.5 + f),
+            DayEventName::Saboteur => player.resources.gasoline * (0.2 + 0.2 * f),
+        };
+
+        amount.round() as u32
+    }
+
+    pub fn description(&self, amount: u32) -> String {
+        match self {
+            DayEventName::Refugees => format!(
+                "A group of {amount} refugees is standing at the gate, begging to be let \
+                in. Giving them shelter will cost materials."
+            ),
+            DayEventName::Storm => format!(
+                "A heavy storm is approaching the fortress. If nothing is done, it will \
+                deal {amount} damage to the fence."
+            ),
+            DayEventName::Disease => format!(
+                "A disease is spreading through the fortress and {amount} workers fell ill. \
+                If they keep working, many of them won't make it."
+            ),
+            DayEventName::SupplyCache => format!(
+                "A patrol found a supply cache close to the fortress, containing \
+                {amount} materials."
+            ),
+            DayEventName::Saboteur => format!(
+                "A saboteur sneaked into the fortress last night and is trying to \
+                steal {amount} gasoline."
+            ),
+        }
+    }
+
+    pub fn choices(&self, amount: u32) -> Vec<DayChoice> {
+        let amount = amount as f32;
+        let free = |label| DayChoice {
+            label,
+            price: Resources::default(),
+        };
+
+        match self {
+            DayEventName::Refugees => vec![
+                DayChoice {
+                    label: "Take them in",
+                    price: Resources {
+                        materials: 2. * amount,
+                        ..default()
+                    },
+                },
+                free("Turn them away"),
+            ],
+            DayEventName::Storm => vec![
+                DayChoice {
+                    label: "Reinforce the fence",
+                    price: Resources {
+                        materials: amount,
+                        ..default()
+                    },
+                },
+                free("Wait it out"),
+            ],
+            DayEventName::Disease => vec![free("Quarantine the sick"), free("Keep them working")],
+            DayEventName::SupplyCache => vec![free("Collect the supplies")],
+            DayEventName::Saboteur => vec![
+                DayChoice {
+                    label: "Hunt the saboteur",
+                    price: Resources {
+                        bullets: amount,
+                        ..default()
+                    },
+                },
+                free("Let him go"),
+            ],
+        }
+    }
+}
+
+/// Event that happened at the start of a day
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DayEvent {
+    pub name: DayEventName,
+    pub amount: u32,
+
+    /// Index of the chosen option. None while the player hasn't decided yet
+    pub choice: Option<usize>,
+}
+
+impl DayEvent {
+    pub fn description(&self) -> String {
+        self.name.description(self.amount)
+    }
+
+    pub fn choices(&self) -> Vec<DayChoice> {
+        self.name.choices(self.amount)
     }
 }
 
@@ -637,6 +1976,8 @@ pub struct NightInfo {
     pub population: Population,
     pub resources: Resources,
     pub enemies: HashMap<String, EnemyStatus>,
+    #[serde(default)]
+    pub critical_hits: u32,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -661,6 +2002,7 @@ pub struct NightStats {
     pub population: Population,
     pub resources: Resources,
     pub enemies: HashMap<String, EnemyStatus>,
+    pub critical_hits: u32,
     pub warnings: ResourcesWarnings,
 }
 
@@ -673,6 +2015,7 @@ impl Default for NightStats {
             population: Population::default(),
             resources: Resources::default(),
             enemies: HashMap::default(),
+            critical_hits: 0,
             warnings: ResourcesWarnings {
                 low_bullets: false,
                 no_bullets: false,
@@ -4,6 +4,7 @@ use crate::game::resources::*;
 use crate::game::weapon::components::WeaponManager;
 use crate::game::{AppState, AudioState, GameState};
 use crate::messages::Messages;
+use crate::utils::NameFromEnum;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::utils::{load_game, save_game};
 use bevy::prelude::*;
@@ -28,7 +29,13 @@ pub fn new_game(
     next_state.set(GameState::Running);
 }
 
-pub fn start_night(mut commands: Commands, player: Res<Player>) {
+pub fn start_night(
+    mut commands: Commands,
+    player: Res<Player>,
+    mut game_settings: ResMut<GameSettings>,
+) {
+    game_settings.targeting = None;
+
     commands.insert_resource(NightStats {
         day: player.day,
         spawn_timer: Timer::from_seconds(
@@ -39,19 +46,51 @@ pub fn start_night(mut commands: Commands, player: Res<Player>) {
     });
 }
 
-pub fn end_night(mut player: ResMut<Player>, night_stats: Res<NightStats>) {
+pub fn end_night(
+    mut player: ResMut<Player>,
+    mut game_settings: ResMut<GameSettings>,
+    night_stats: Res<NightStats>,
+) {
+    game_settings.targeting = None;
+
     player.stats.entry(night_stats.day).or_insert(NightInfo {
         day: night_stats.day,
         population: night_stats.population.clone(),
-        resources: night_stats.resources.clone(),
+        resources: night_stats.resources,
         enemies: night_stats.enemies.clone(),
+        critical_hits: night_stats.critical_hits,
     });
+
+    // Update the morale with the night's results
+    let day = night_stats.day;
+    let kills: u32 = night_stats.enemies.values().map(|e| e.killed).sum();
+    player
+        .morale
+        .add(day, MoraleCause::Kills, kills as f32 * MORALE_PER_KILL);
+
+    if night_stats.population.total() == 0 {
+        player
+            .morale
+            .add(day, MoraleCause::QuietNight, MORALE_QUIET_NIGHT);
+    }
+
+    let shortages = [
+        night_stats.warnings.no_bullets,
+        night_stats.warnings.no_gasoline,
+    ];
+    for _ in shortages.into_iter().filter(|s| *s) {
+        player
+            .morale
+            .add(day, MoraleCause::Shortage, -MORALE_SHORTAGE);
+    }
 }
 
 pub fn start_day(
     mut player: ResMut<Player>,
     mut messages: ResMut<Messages>,
     mut game_settings: ResMut<GameSettings>,
+    night_stats: Res<NightStats>,
+    events: Res<ExpeditionEvents>,
 ) {
     if !game_settings.just_loaded {
         player.day += 1;
@@ -63,15 +102,84 @@ pub fn start_day(
         )
         .unwrap();
 
-        let new_population = dist.sample(&mut thread_rng()) as u32;
-        player.population.idle += new_population;
+        let arrivals = dist.sample(&mut thread_rng()) as u32;
+        let new_population = player.house(arrivals);
         messages.info(format!("Population increased by {}.", new_population));
+        if new_population < arrivals {
+            messages.warning(format!(
+                "{} survivors were turned away for lack of housing.",
+                arrivals - new_population
+            ));
+        }
 
         let new_resources = player.new_resources();
         player.resources += &new_resources;
 
-        if let Some(ref mut expedition) = &mut player.expedition {
+        let recruits = player.population.recruit;
+        let trained = player.train_recruits();
+        if trained > 0 {
+            messages.info(format!("{} recruits finished their training.", trained));
+        }
+        if trained < recruits {
+            messages.warning(format!(
+                "Not enough bullets to train {} recruits.",
+                recruits - trained
+            ));
+        }
+
+        // Resources spent during the night and food eaten today, moving the market
+        let consumption = night_stats.resources
+            + &Resources {
+                food: player.food_consumption(),
+                ..default()
+            };
+
+        let starved = player.eat();
+        if starved > 0 {
+            let day = player.day;
+            player
+                .morale
+                .add(day, MoraleCause::Starvation, -MORALE_STARVATION);
+            messages.error(format!("{} people starved.", starved));
+        } else if player.resources.food < player.food_consumption() {
+            messages.warning("Food is running out.");
+        }
+
+        if player.population.sick > 0 {
+            messages.info(format!(
+                "{} sick recovered from the quarantine.",
+                player.population.sick
+            ));
+            player.population.idle += player.population.sick;
+            player.population.sick = 0;
+        }
+
+        let (recovered, died) = player.treat_wounded();
+        if recovered > 0 {
+            messages.info(format!("{} wounded recovered in the hospital.", recovered));
+        }
+        if died > 0 {
+            messages.error(format!("{} untreated wounded died.", died));
+        }
+
+        let deserters = player.desert();
+        if deserters > 0 {
+            messages.error(format!("{} people deserted due to low morale.", deserters));
+        }
+
+        player.market.update(&(new_resources - &consumption));
+        player.roll_day_event();
+
+        for tech in player.update_research() {
+            messages.info(format!("Technology {} researched.", tech.name()));
+        }
+
+        for expedition in player.expeditions.iter_mut() {
             expedition.update();
+
+            if !expedition.is_finished() && random::<f32>() < EVENT_PROB {
+                expedition.event = events.roll(expedition).map(|e| e.id.clone());
+            }
         }
     } else {
         game_settings.just_loaded = false;
@@ -121,6 +229,7 @@ pub fn play_audio(mut game_settings: ResMut<GameSettings>) {
     game_settings.audio = true;
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn check_keys(
     mut commands: Commands,
     keyboard: Res<ButtonInput<KeyCode>>,
@@ -147,20 +256,13 @@ pub fn check_keys(
     if keyboard.just_pressed(KeyCode::Enter) {
         match *app_state.get() {
             AppState::StartGame => next_app_state.set(AppState::Night),
-            AppState::Day => {
-                if player.expedition.is_some()
-                    && !matches!(
-                        player.expedition.as_ref().unwrap().status,
-                        ExpeditionStatus::Ongoing
-                    )
-                {
-                    player.resolve_expedition();
+            AppState::Day if !player.resolve_expedition() => {
+                if player.pending_day_event().is_some() {
+                    messages.error("Decide on today's event first!");
+                } else if player.population.idle > 0 {
+                    messages.error("You have idle population!");
                 } else {
-                    if player.population.idle > 0 {
-                        messages.error("You have idle population!");
-                    } else {
-                        next_app_state.set(AppState::Night);
-                    }
+                    next_app_state.set(AppState::Night);
                 }
             }
             AppState::GameOver => next_app_state.set(AppState::StartGame),
@@ -1,17 +1,22 @@
 use crate::constants::{
-    EnemyQ, EXPLOSION_Z, MAP_SIZE, MAX_FLAMETHROWER_POWER, MAX_SPOTS, SIZE, WEAPONS_PANEL_SIZE,
+    EnemyQ, BALLISTIC_ARC, BALLISTIC_MIN_FLIGHT_TIME, EXPLOSION_Z, FROZEN_FIRE_MULTIPLIER,
+    LIGHTNING_DURATION, MAP_SIZE, MASTERY_LEVEL, MAX_CRYO_POWER, MAX_FLAMETHROWER_POWER, MAX_SLOW,
+    MAX_SPOTS, RANK_BONUS, SPECIALIZATION_LEVEL,
 };
 use crate::game::assets::WorldAssets;
 use crate::game::enemy::components::Enemy;
 use crate::game::map::components::{AnimationComponent, FogOfWar};
-use crate::game::map::utils::is_visible;
-use crate::game::resources::{GameSettings, Player, Resources};
-use crate::utils::scale_duration;
+use crate::game::map::utils::is_visible_through;
+use crate::game::resources::{GameSettings, NightStats, Player, Resources, Stat};
+use crate::utils::{scale_duration, NameFromEnum};
 use bevy::prelude::*;
+use rand::random;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::f32::consts::PI;
 use std::time::Duration;
+use strum_macros::EnumIter;
+use uuid::Uuid;
 
 #[derive(Component)]
 pub struct FenceComponent;
@@ -22,18 +27,30 @@ pub struct WallComponent;
 #[derive(Component)]
 pub struct Mine;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub enum WeaponName {
     AAA,
     Artillery,
     Canon,
+    Cryo,
     Flamethrower,
     MachineGun,
     MissileLauncher,
     Mortar,
+    Sniper,
+    Tesla,
     Turret,
 }
 
+/// Structure a weapon is mounted on
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Mount {
+    #[default]
+    Wall,
+    Fence,
+}
+
 #[derive(Clone)]
 pub struct FireAnimation {
     /// Name of the asset for firing animation
@@ -60,6 +77,55 @@ pub enum FireStrategy {
     /// Fire at the enemy with the most surrounding enemies at
     /// a distance given by the explosion's `radius`
     Density,
+
+    /// Fire at the enemy with the lowest current `health`
+    Weakest,
+
+    /// Fire at the enemy closest to the wall
+    Furthest,
+
+    /// Fire at the enemy with the highest current speed
+    Fastest,
+
+    /// Fire at the enemy with the highest `armor`
+    MostArmored,
+
+    /// Fire at the closest flying enemy, or the closest
+    /// enemy if there are no flying enemies in range
+    FlyingFirst,
+
+    /// Fire at the first enemy in range and don't
+    /// change target until it's dead
+    Sticky,
+}
+
+impl FireStrategy {
+    /// Strategies that select a single target for the weapon
+    pub const TARGETING: [FireStrategy; 8] = [
+        FireStrategy::Closest,
+        FireStrategy::Strongest,
+        FireStrategy::Weakest,
+        FireStrategy::Furthest,
+        FireStrategy::Fastest,
+        FireStrategy::MostArmored,
+        FireStrategy::FlyingFirst,
+        FireStrategy::Sticky,
+    ];
+
+    pub fn description(&self) -> &str {
+        match self {
+            FireStrategy::None => "Don't fire.",
+            FireStrategy::Closest => "Fire on the closest enemy.",
+            FireStrategy::Strongest => "Fire on the strongest enemy.",
+            FireStrategy::Density => "Fire on the location with the highest enemy density.",
+            FireStrategy::Weakest => "Fire on the enemy with the lowest health to finish it off.",
+            FireStrategy::Furthest => "Fire on the enemy closest to the wall.",
+            FireStrategy::Fastest => "Fire on the fastest enemy.",
+            FireStrategy::MostArmored => "Fire on the enemy with the most armor.",
+            FireStrategy::FlyingFirst => "Fire on flying enemies first, then on the closest.",
+            FireStrategy::Sticky => "Fire on the first enemy in range until it's dead.",
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -89,6 +155,100 @@ pub struct Upgrade {
     pub price: Resources,
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum UpgradeNodeName {
+    // AAA
+    TwinBarrels,
+    TripleBarrels,
+    LongBarrel,
+    Rifling,
+
+    // Artillery
+    HeavyShells,
+    ShapedCharges,
+    AutoLoader,
+    BeltFeed,
+
+    // Canon
+    ClusterShells,
+    Submunitions,
+    Airburst,
+    ProximityFuze,
+
+    // Cryo
+    Permafrost,
+    DeepFreeze,
+    Blizzard,
+    Whiteout,
+
+    // Flamethrower
+    Napalm,
+    WhitePhosphorus,
+    WideNozzle,
+    Afterburner,
+
+    // Machine gun
+    Incendiary,
+    Thermite,
+    ArmorPiercing,
+    TungstenCores,
+
+    // Missile launcher
+    ClusterWarheads,
+    Fragmentation,
+    BunkerBuster,
+    DeepPenetrator,
+
+    // Mortar
+    Barrage,
+    Saturation,
+    HeavyCharge,
+    HighExplosive,
+
+    // Sniper
+    Headhunter,
+    Marksman,
+    SemiAutomatic,
+    StraightPull,
+
+    // Tesla
+    Overcharge,
+    Supercharge,
+    Conductivity,
+    Superconductor,
+
+    // Turret
+    Capacitors,
+    Supercapacitors,
+    RecycledCasings,
+    Scavenging,
+}
+
+/// Node in the upgrade tree of a weapon, branching from its two upgrades
+#[derive(Clone)]
+pub struct UpgradeNode {
+    /// Name of the node
+    pub name: UpgradeNodeName,
+
+    /// Node description
+    pub description: &'static str,
+
+    /// Texture corresponding to an asset
+    pub texture: &'static str,
+
+    /// Node that has to be bought before this one
+    pub parent: Option<UpgradeNodeName>,
+
+    /// Sum of upgrade levels required to unlock the node
+    pub level: u32,
+
+    /// Nodes in the same group are mutually exclusive
+    pub group: Option<u32>,
+
+    /// Node cost
+    pub price: Resources,
+}
+
 #[derive(Component, Clone)]
 pub struct Weapon {
     /// Name of the weapon
@@ -112,6 +272,12 @@ pub struct Weapon {
     /// Price to buy the weapon
     pub price: Resources,
 
+    /// Current health. The weapon stops firing when it reaches zero
+    pub health: f32,
+
+    /// Maximum health
+    pub max_health: f32,
+
     /// Animation to play when firing
     pub fire_animation: FireAnimation,
 
@@ -121,9 +287,33 @@ pub struct Weapon {
     /// Target entity to point to
     pub target: Option<Entity>,
 
+    /// Enemies in range, in the order they entered it
+    pub in_range: Vec<Entity>,
+
+    /// Same enemies as `in_range`, for fast lookups
+    pub in_range_set: HashSet<Entity>,
+
+    /// Modifier of the player's range applied on the bullet's max distance
+    pub range_modifier: f32,
+
+    /// Id of the spot the weapon is placed on
+    pub spot: Uuid,
+
+    /// Structure the weapon is mounted on
+    pub mount: Mount,
+
+    /// Rank earned with kills, giving small bonuses
+    pub rank: u32,
+
     /// Time between shots (reload time)
     pub fire_timer: Option<Timer>,
 
+    /// Rounds fired before the weapon has to reload
+    pub magazine: Option<Magazine>,
+
+    /// Heat build-up that forces the weapon to cool down
+    pub overheat: Option<Overheat>,
+
     /// Strategy to select a target
     pub fire_strategy: FireStrategy,
 
@@ -136,6 +326,9 @@ pub struct Weapon {
     /// Upgrades for the weapon
     pub upgrade1: Upgrade,
     pub upgrade2: Upgrade,
+
+    /// Upgrade tree with the specializations of the weapon
+    pub tree: Vec<UpgradeNode>,
 }
 
 #[derive(Clone)]
@@ -148,6 +341,18 @@ pub struct Damage {
 
     /// Armor penetration. Also damages structures if in explosion
     pub penetration: f32,
+
+    /// Probability of a critical hit (only for single-target impacts)
+    pub crit_chance: f32,
+
+    /// Damage multiplier of a critical hit
+    pub crit_multiplier: f32,
+
+    /// Whether it's fire damage (extra damage on frozen enemies)
+    pub fire: bool,
+
+    /// Spot of the weapon that dealt the damage
+    pub source: Option<Uuid>,
 }
 
 impl Default for Damage {
@@ -156,6 +361,10 @@ impl Default for Damage {
             ground: 0.,
             air: 0.,
             penetration: 0.,
+            crit_chance: 0.,
+            crit_multiplier: 1.,
+            fire: false,
+            source: None,
         }
     }
 }
@@ -164,7 +373,13 @@ impl Damage {
     /// Calculate the damage inflicted on `enemy`
     pub fn calculate(&self, enemy: &Enemy) -> f32 {
         let base = if enemy.flies { self.air } else { self.ground };
-        (base - (enemy.armor - self.penetration).max(0.)).max(0.)
+        let damage = (base - (enemy.armor - self.penetration).max(0.)).max(0.);
+
+        if self.fire && enemy.slow > 0. {
+            damage * FROZEN_FIRE_MULTIPLIER
+        } else {
+            damage
+        }
     }
 }
 
@@ -194,6 +409,320 @@ impl Default for Explosion {
     }
 }
 
+#[derive(Clone)]
+pub struct Chain {
+    /// Damage inflicted on the first enemy hit
+    pub damage: Damage,
+
+    /// Number of times the arc jumps to a nearby enemy
+    pub jumps: u32,
+
+    /// Maximum distance of a jump
+    pub range: f32,
+
+    /// Fraction of the damage kept after every jump
+    pub falloff: f32,
+
+    /// Position where the arc starts (the weapon)
+    pub origin: Vec3,
+}
+
+/// Chain lightning that jumps between enemies and
+/// whose arcs are drawn until the timer finishes
+#[derive(Component)]
+pub struct Lightning {
+    /// Chain that produced the lightning
+    pub chain: Chain,
+
+    /// Jumps left to resolve
+    pub jumps: u32,
+
+    /// Enemies already hit by the arc
+    pub hits: HashSet<Entity>,
+
+    /// Segments of the arc from start to end
+    pub arcs: Vec<(Vec2, Vec2)>,
+
+    /// Time the arcs stay visible
+    pub timer: Timer,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DroneStatus {
+    /// Patrol the band and engage enemies in it
+    Patrolling,
+
+    /// Fly back to the bay to refill the ammo
+    Rearming,
+
+    /// Fly back to the bay and stay there
+    Recalled,
+}
+
+/// Drone that leaves the wall to engage enemies at close range
+#[derive(Component, Clone)]
+pub struct Drone {
+    /// Name of the asset for sprite
+    pub image: &'static str,
+
+    /// Dimensions (size) of the sprite
+    pub dim: Vec2,
+
+    /// Current health. The drone is lost when it reaches zero
+    pub health: f32,
+
+    /// Maximum health
+    pub max_health: f32,
+
+    /// Distance traveled per second
+    pub speed: f32,
+
+    /// Distance at which the drone engages enemies
+    pub range: f32,
+
+    /// Rounds the drone carries before having to rearm
+    pub max_ammo: u32,
+
+    /// Rounds left
+    pub ammo: u32,
+
+    /// Bullets cost per round when rearming
+    pub price: Resources,
+
+    /// Time between shots
+    pub fire_timer: Timer,
+
+    /// Bullet fired at the enemies
+    pub bullet: Bullet,
+
+    /// Position of the drone's bay on the wall
+    pub home: Vec3,
+
+    /// Current task of the drone
+    pub status: DroneStatus,
+
+    /// Enemy the drone is engaging
+    pub target: Option<Entity>,
+
+    /// Horizontal direction of the patrol (1 = right, -1 = left)
+    pub direction: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SortieStatus {
+    /// Hold the post on the line and engage enemies in range
+    Fighting,
+
+    /// Walk back to the fortress
+    Retreating,
+}
+
+/// Soldier deployed in front of the wall during a sortie
+#[derive(Component, Clone)]
+pub struct Soldier {
+    /// Name of the asset for sprite
+    pub image: &'static str,
+
+    /// Dimensions (size) of the sprite
+    pub dim: Vec2,
+
+    /// Current health. The soldier dies when it reaches zero
+    pub health: f32,
+
+    /// Maximum health
+    pub max_health: f32,
+
+    /// Distance walked per second
+    pub speed: f32,
+
+    /// Distance at which the soldier engages enemies
+    pub range: f32,
+
+    /// Damage dealt per shot
+    pub damage: Damage,
+
+    /// Time between shots
+    pub fire_timer: Timer,
+
+    /// Horizontal position of the soldier's post on the line
+    pub post: f32,
+
+    /// Position on the wall where the soldier left the fortress
+    pub home: Vec3,
+
+    /// Current order of the soldier
+    pub status: SortieStatus,
+}
+
+/// Bar under a weapon showing its health or the state of its magazine or heat
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum WeaponBar {
+    Health,
+    Magazine,
+    Heat,
+}
+
+impl WeaponBar {
+    pub fn color(&self) -> Color {
+        match self {
+            WeaponBar::Health => Color::srgb(0.2, 0.8, 0.2),
+            WeaponBar::Magazine => Color::srgb(0.9, 0.8, 0.2),
+            WeaponBar::Heat => Color::srgb(1., 0.5, 0.),
+        }
+    }
+}
+
+/// Colored part of a `WeaponBar`
+#[derive(Component)]
+pub struct WeaponBarFill;
+
+#[derive(Clone)]
+pub struct Magazine {
+    /// Base number of rounds
+    pub size: u32,
+
+    /// Number of rounds after upgrades
+    pub capacity: u32,
+
+    /// Rounds left before having to reload
+    pub rounds: u32,
+
+    /// Time to reload an empty magazine
+    pub reload: Timer,
+}
+
+impl Magazine {
+    pub fn new(size: u32, reload: f32) -> Self {
+        Self {
+            size,
+            capacity: size,
+            rounds: size,
+            reload: Timer::from_seconds(reload, TimerMode::Once),
+        }
+    }
+
+    /// Whether the magazine is empty and reloading
+    pub fn is_reloading(&self) -> bool {
+        self.rounds == 0
+    }
+
+    /// Fraction of the magazine that is filled (or reloaded)
+    pub fn fraction(&self) -> f32 {
+        if self.is_reloading() {
+            self.reload.fraction()
+        } else {
+            self.rounds as f32 / self.capacity as f32
+        }
+    }
+
+    /// Extend the capacity by `factor`, keeping a full magazine full
+    pub fn extend(&mut self, factor: f32) {
+        let full = self.rounds == self.capacity;
+        self.capacity = (self.size as f32 * factor).round() as u32;
+        self.rounds = if full {
+            self.capacity
+        } else {
+            self.rounds.min(self.capacity)
+        };
+    }
+}
+
+#[derive(Clone)]
+pub struct Overheat {
+    /// Base heat the weapon can take
+    pub size: f32,
+
+    /// Heat the weapon can take after upgrades
+    pub capacity: f32,
+
+    /// Heat generated per shot
+    pub per_shot: f32,
+
+    /// Heat dissipated per second
+    pub cooling: f32,
+
+    /// Current heat
+    pub heat: f32,
+
+    /// Whether the weapon reached its capacity and has to
+    /// cool down completely before it can fire again
+    pub overheated: bool,
+}
+
+impl Overheat {
+    pub fn new(size: f32, per_shot: f32, cooling: f32) -> Self {
+        Self {
+            size,
+            capacity: size,
+            per_shot,
+            cooling,
+            heat: 0.,
+            overheated: false,
+        }
+    }
+
+    /// Fraction of the capacity that is heated
+    pub fn fraction(&self) -> f32 {
+        self.heat / self.capacity
+    }
+}
+
+/// Shadow on the ground below a ballistic shell
+#[derive(Component)]
+pub struct Shadow(pub Entity);
+
+#[derive(Clone)]
+pub struct Ballistic {
+    /// Position the shell is fired from
+    pub start: Vec3,
+
+    /// Position where the shell lands
+    pub target: Vec3,
+
+    /// Seconds the shell is in the air
+    pub flight_time: f32,
+
+    /// Seconds since the shell was fired
+    pub elapsed: f32,
+
+    /// Visual height of the arc's apex
+    pub height: f32,
+}
+
+impl Ballistic {
+    pub fn new(start: Vec3, target: Vec3, speed: f32) -> Self {
+        let distance = start.distance(target);
+        Self {
+            start,
+            target,
+            flight_time: Self::flight_time(distance, speed),
+            elapsed: 0.,
+            height: distance * BALLISTIC_ARC,
+        }
+    }
+
+    /// Seconds a shell fired at `speed` takes to travel `distance`
+    pub fn flight_time(distance: f32, speed: f32) -> f32 {
+        (distance / speed).max(BALLISTIC_MIN_FLIGHT_TIME)
+    }
+
+    /// Fraction of the flight completed
+    pub fn progress(&self) -> f32 {
+        (self.elapsed / self.flight_time).min(1.)
+    }
+
+    /// Position on the ground below the shell
+    pub fn ground(&self) -> Vec3 {
+        self.start.lerp(self.target, self.progress())
+    }
+
+    /// Fraction of the apex height the shell is at (1 at the apex)
+    pub fn altitude(&self) -> f32 {
+        let t = self.progress();
+        4. * t * (1. - t)
+    }
+}
+
 #[derive(Clone)]
 pub enum Movement {
     /// Bullets impacts at fist enemy hit
@@ -202,6 +731,9 @@ pub enum Movement {
     /// Bullet impacts at location
     Location(Vec3),
 
+    /// Shell flies in an arc over the enemies and impacts at the end
+    Ballistic(Ballistic),
+
     /// Bullets impacts on `Entity`
     Homing(Entity),
 
@@ -220,11 +752,31 @@ pub enum Impact {
         hits: HashSet<Entity>, // Keep track of enemies already hit
     },
 
+    /// Slows down the ground enemies it hits without damaging them
+    Freeze {
+        slow: f32,     // Fraction of speed lost per hit
+        duration: f32, // Seconds the slow lasts
+        hits: HashSet<Entity>,
+    },
+
     /// Explodes after colliding with an enemy
     Explosion(Explosion),
+
+    /// Arc that jumps from the enemy hit to nearby enemies
+    Chain(Chain),
 }
 
 impl Impact {
+    /// Damage dealt by the impact, if any
+    pub fn damage_mut(&mut self) -> Option<&mut Damage> {
+        match self {
+            Impact::SingleTarget(d) | Impact::Piercing { damage: d, .. } => Some(d),
+            Impact::Explosion(e) => Some(&mut e.damage),
+            Impact::Chain(c) => Some(&mut c.damage),
+            Impact::Freeze { .. } => None,
+        }
+    }
+
     /// Resolve the impact of the bullet on the enemy
     /// Return whether the impact was resolved
     pub fn resolve(
@@ -233,13 +785,23 @@ impl Impact {
         bullet_e: Entity,
         bullet_t: &Transform,
         enemy: Option<(Entity, &mut Enemy)>,
+        night_stats: &mut NightStats,
         assets: &Local<WorldAssets>,
     ) -> bool {
         match self {
             Impact::SingleTarget(d) => {
                 let (_, enemy) = enemy.unwrap();
                 if (d.ground > 0. && !enemy.flies) || (d.air > 0. && enemy.flies) {
-                    enemy.health -= d.calculate(enemy).min(enemy.health);
+                    let mut damage = d.calculate(enemy);
+
+                    // Critical hits multiply the damage after the armor is applied
+                    if d.crit_chance > 0. && random::<f32>() < d.crit_chance {
+                        damage *= d.crit_multiplier;
+                        night_stats.critical_hits += 1;
+                    }
+
+                    enemy.health -= damage.min(enemy.health);
+                    enemy.last_hit = d.source;
                     commands.entity(bullet_e).try_despawn();
                     return true;
                 }
@@ -248,12 +810,28 @@ impl Impact {
                 let (enemy_e, enemy) = enemy.unwrap();
 
                 // The same enemy can only be hit once
-                if !hits.contains(&enemy_e) {
-                    if (d.ground > 0. && !enemy.flies) || (d.air > 0. && enemy.flies) {
-                        enemy.health -= d.calculate(enemy).min(enemy.health);
-                        hits.insert(enemy_e);
-                        return true;
-                    }
+                if !hits.contains(&enemy_e)
+                    && ((d.ground > 0. && !enemy.flies) || (d.air > 0. && enemy.flies))
+                {
+                    enemy.health -= d.calculate(enemy).min(enemy.health);
+                    enemy.last_hit = d.source;
+                    hits.insert(enemy_e);
+                    return true;
+                }
+            }
+            Impact::Freeze {
+                slow,
+                duration,
+                hits,
+            } => {
+                let (enemy_e, enemy) = enemy.unwrap();
+
+                // Hits stack the slow up to a maximum
+                if !hits.contains(&enemy_e) && !enemy.flies {
+                    enemy.slow = (enemy.slow + *slow).min(MAX_SLOW);
+                    enemy.slow_timer = enemy.slow_timer.max(*duration);
+                    hits.insert(enemy_e);
+                    return true;
                 }
             }
             Impact::Explosion(e) => {
@@ -291,6 +869,25 @@ impl Impact {
 
                 return true;
             }
+            Impact::Chain(c) => {
+                let (enemy_e, enemy) = enemy.unwrap();
+                if (c.damage.ground > 0. && !enemy.flies) || (c.damage.air > 0. && enemy.flies) {
+                    enemy.health -= c.damage.calculate(enemy).min(enemy.health);
+                    enemy.last_hit = c.damage.source;
+                    commands.entity(bullet_e).try_despawn();
+
+                    // The jumps to the next enemies are resolved by the lightning
+                    commands.spawn(Lightning {
+                        chain: c.clone(),
+                        jumps: c.jumps,
+                        hits: HashSet::from([enemy_e]),
+                        arcs: vec![(c.origin.truncate(), bullet_t.translation.truncate())],
+                        timer: Timer::from_seconds(LIGHTNING_DURATION, TimerMode::Once),
+                    });
+
+                    return true;
+                }
+            }
         }
 
         false
@@ -324,22 +921,110 @@ pub struct Bullet {
     pub max_distance: f32,
 }
 
+impl Bullet {
+    /// Seconds the bullet takes to travel from `start` to `end`
+    pub fn flight_time(&self, start: Vec3, end: Vec3) -> f32 {
+        let distance = start.distance(end);
+        match self.movement {
+            Movement::Ballistic(_) => Ballistic::flight_time(distance, self.speed),
+            _ => distance / self.speed,
+        }
+    }
+}
+
 impl Weapon {
+    /// Reason why `node` of the upgrade tree can't be bought, or None if it can
+    pub fn locked_reason(&self, node: &UpgradeNode, player: &Player) -> Option<String> {
+        let (l1, l2) = *player.weapons.upgrades.get(&self.name).unwrap_or(&(0, 0));
+        let has = |n: UpgradeNodeName| player.weapons.has_node(self.name, n);
+
+        if let Some(parent) = node.parent.filter(|p| !has(*p)) {
+            Some(format!("Requires {}.", parent.name()))
+        } else if self.tree.iter().any(|n| {
+            n.name != node.name && n.group.is_some() && n.group == node.group && has(n.name)
+        }) {
+            Some("Another specialization was chosen.".to_string())
+        } else if l1 + l2 < node.level {
+            Some(format!("Requires a total upgrade level of {}.", node.level))
+        } else {
+            None
+        }
+    }
+
+    /// Update the enemies in range, keeping the order in which they entered it
+    pub fn track_range(
+        &mut self,
+        transform: &Transform,
+        enemy_q: &Query<EnemyQ, (With<Enemy>, Without<Weapon>)>,
+    ) {
+        let range = self.bullet.max_distance;
+        let in_range = |t: &Transform| transform.translation.distance(t.translation) <= range;
+
+        let set = &mut self.in_range_set;
+        self.in_range.retain(|e| {
+            let keep = enemy_q
+                .get(*e)
+                .is_ok_and(|(_, enemy_t, _)| in_range(enemy_t));
+            if !keep {
+                set.remove(e);
+            }
+            keep
+        });
+
+        for (enemy_e, enemy_t, _) in enemy_q.iter() {
+            if in_range(enemy_t) && self.in_range_set.insert(enemy_e) {
+                self.in_range.push(enemy_e);
+            }
+        }
+    }
+
+    /// Whether the enemy is visible to the weapon and within its range
+    pub fn in_reach(
+        &self,
+        transform: &Transform,
+        enemy_t: &Transform,
+        enemy: &Enemy,
+        fow_t: &Transform,
+        vision: f32,
+    ) -> bool {
+        let distance = transform.translation.distance(enemy_t.translation);
+        is_visible_through(fow_t, enemy_t, enemy, vision)
+            && distance >= self.min_distance
+            && distance <= self.bullet.max_distance
+    }
+
     /// Acquire a target to fire at. If `self.target` is empty, it will
-    /// select a new target excluding the entities in `exclusions`.
+    /// select a new target excluding the entities in `exclusions`. The
+    /// weapon sees `vision` (fraction of the fog's height) into the fog.
     pub fn acquire_target(
         &self,
         transform: &Transform,
         enemy_q: &Query<EnemyQ, (With<Enemy>, Without<Weapon>)>,
         fow_q: &Query<&Transform, (With<FogOfWar>, Without<Weapon>)>,
         exclusions: &HashSet<Entity>,
+        vision: f32,
     ) -> Option<Entity> {
-        // Return target if it's already acquired, and it still exists and is visible
+        // Return target if it's already acquired, and it still exists and is visible. The
+        // sticky target is kept until it dies (the weapon holds its fire while it's out
+        // of reach), and the strategies that rank the enemies in range also drop their
+        // target once it leaves the range
         if let Some(enemy_e) = self.target.and_then(|enemy_e| {
             if let Ok((enemy_e, enemy_t, enemy)) = enemy_q.get(enemy_e) {
-                if is_visible(&fow_q.get_single().unwrap(), enemy_t, enemy)
-                    && !exclusions.contains(&enemy_e)
-                {
+                let visible =
+                    is_visible_through(fow_q.get_single().unwrap(), enemy_t, enemy, vision);
+                let in_range =
+                    transform.translation.distance(enemy_t.translation) <= self.bullet.max_distance;
+
+                let keep = match self.fire_strategy {
+                    FireStrategy::Sticky => true,
+                    FireStrategy::None
+                    | FireStrategy::Closest
+                    | FireStrategy::Strongest
+                    | FireStrategy::Density => visible,
+                    _ => visible && in_range,
+                };
+
+                if keep && !exclusions.contains(&enemy_e) {
                     return Some(enemy_e);
                 }
             }
@@ -352,7 +1037,7 @@ impl Weapon {
             .iter()
             .filter_map(|(enemy_e, enemy_t, enemy)| {
                 // Check if the enemy is behind the fog of war
-                if !is_visible(fow_q.get_single().unwrap(), enemy_t, enemy) {
+                if !is_visible_through(fow_q.get_single().unwrap(), enemy_t, enemy, vision) {
                     return None;
                 }
 
@@ -360,11 +1045,17 @@ impl Weapon {
                 match &self.bullet.impact {
                     Impact::SingleTarget(d)
                     | Impact::Piercing { damage: d, .. }
-                    | Impact::Explosion(Explosion { damage: d, .. }) => {
+                    | Impact::Explosion(Explosion { damage: d, .. })
+                    | Impact::Chain(Chain { damage: d, .. }) => {
                         if (d.ground == 0. && !enemy.flies) || (d.air == 0. && enemy.flies) {
                             return None;
                         }
                     }
+                    Impact::Freeze { .. } => {
+                        if enemy.flies {
+                            return None;
+                        }
+                    }
                 }
 
                 // Check if the enemy is in range
@@ -394,6 +1085,40 @@ impl Weapon {
                     e1.max_health.partial_cmp(&e2.max_health).unwrap()
                 })
                 .map(|((enemy_e, _, _), _)| *enemy_e),
+            FireStrategy::Weakest => targets
+                .iter()
+                .min_by(|((_, _, e1), _), ((_, _, e2), _)| {
+                    e1.health.partial_cmp(&e2.health).unwrap()
+                })
+                .map(|((enemy_e, _, _), _)| *enemy_e),
+            FireStrategy::Furthest => targets
+                .iter()
+                .min_by(|((_, t1, _), _), ((_, t2, _), _)| {
+                    t1.translation.y.partial_cmp(&t2.translation.y).unwrap()
+                })
+                .map(|((enemy_e, _, _), _)| *enemy_e),
+            FireStrategy::Fastest => targets
+                .iter()
+                .max_by(|((_, _, e1), _), ((_, _, e2), _)| {
+                    e1.current_speed().partial_cmp(&e2.current_speed()).unwrap()
+                })
+                .map(|((enemy_e, _, _), _)| *enemy_e),
+            FireStrategy::MostArmored => targets
+                .iter()
+                .max_by(|((_, _, e1), _), ((_, _, e2), _)| e1.armor.partial_cmp(&e2.armor).unwrap())
+                .map(|((enemy_e, _, _), _)| *enemy_e),
+            FireStrategy::FlyingFirst => targets
+                .iter()
+                .min_by(|((_, _, e1), d1), ((_, _, e2), d2)| {
+                    e2.flies.cmp(&e1.flies).then(d1.partial_cmp(d2).unwrap())
+                })
+                .map(|((enemy_e, _, _), _)| *enemy_e),
+            // The sticky target is kept by the early return above until it dies
+            FireStrategy::Sticky => self
+                .in_range
+                .iter()
+                .find(|e| targets.iter().any(|((enemy_e, _, _), _)| enemy_e == *e))
+                .copied(),
             FireStrategy::Density => {
                 if let Impact::Explosion(e) = &self.bullet.impact {
                     targets
@@ -423,12 +1148,75 @@ impl Weapon {
         }
     }
 
-    /// Whether the weapon's timer is finished
+    /// Multiplier of the rotation speed, damage and fire rate given by the rank
+    pub fn rank_bonus(&self) -> f32 {
+        1. + RANK_BONUS * self.rank as f32
+    }
+
+    /// Whether the weapon is destroyed and can't fire
+    pub fn is_destroyed(&self) -> bool {
+        self.health == 0.
+    }
+
+    /// Reduce the weapon's health. Returns true if this destroyed the weapon
+    pub fn take_damage(&mut self, damage: f32) -> bool {
+        if self.is_destroyed() {
+            return false;
+        }
+
+        self.health -= damage.min(self.health);
+        self.is_destroyed()
+    }
+
+    /// Reload an empty magazine and dissipate the heat
+    pub fn cool_down(&mut self, time: &Time, game_settings: &GameSettings) {
+        if let Some(magazine) = self.magazine.as_mut().filter(|m| m.is_reloading()) {
+            magazine
+                .reload
+                .tick(scale_duration(time.delta(), game_settings.speed));
+            if magazine.reload.finished() {
+                magazine.reload.reset();
+                magazine.rounds = magazine.capacity;
+            }
+        }
+
+        if let Some(overheat) = self.overheat.as_mut() {
+            overheat.heat = (overheat.heat
+                - overheat.cooling * game_settings.speed * time.delta_secs())
+            .max(0.);
+            if overheat.heat == 0. {
+                overheat.overheated = false;
+            }
+        }
+    }
+
+    /// Whether the weapon's timer is finished and it's neither reloading nor
+    /// overheated. Firing uses a round of the magazine and heats the weapon
     pub fn can_fire(&mut self, time: &Time, game_settings: &GameSettings) -> bool {
+        if self.magazine.as_ref().is_some_and(|m| m.is_reloading())
+            || self.overheat.as_ref().is_some_and(|o| o.overheated)
+        {
+            return false;
+        }
+
+        let speed = game_settings.speed * self.rank_bonus();
         if let Some(ref mut timer) = &mut self.fire_timer {
-            timer.tick(scale_duration(time.delta(), game_settings.speed));
+            timer.tick(scale_duration(time.delta(), speed));
             if timer.finished() {
                 timer.reset(); // Start reload
+
+                if let Some(magazine) = self.magazine.as_mut() {
+                    magazine.rounds -= 1;
+                }
+
+                if let Some(overheat) = self.overheat.as_mut() {
+                    overheat.heat += overheat.per_shot;
+                    if overheat.heat >= overheat.capacity {
+                        overheat.heat = overheat.capacity;
+                        overheat.overheated = true;
+                    }
+                }
+
                 return true;
             }
         }
@@ -441,28 +1229,60 @@ impl Weapon {
         (angle - PI * 0.5 - transform.rotation.to_euler(EulerRot::XYZ).2).abs() < 0.1
     }
 
-    /// Update the weapon's based on the player's settings
+    /// Update the weapon's based on the settings of its spot
     pub fn update(&mut self, player: &Player) {
+        let settings = player
+            .weapons
+            .spots
+            .iter()
+            .find(|s| s.id == self.spot)
+            .map(|s| s.settings.clone())
+            .unwrap_or_default();
+
         let upgrades = *player.weapons.upgrades.get(&self.name).unwrap_or(&(0, 0));
         let upgrade1 = upgrades.0 as f32;
         let upgrade2 = upgrades.1 as f32;
 
+        // Effects of the upgrade tree are applied on the base values
+        let has = |n: UpgradeNodeName| player.weapons.has_node(self.name, n);
+
+        // Undo the previous range modifier, since not every weapon resets its range
+        self.bullet.max_distance /= self.range_modifier;
+
         match self.name {
             WeaponName::AAA => {
+                self.n_bullets = if has(UpgradeNodeName::TripleBarrels) {
+                    3
+                } else if has(UpgradeNodeName::TwinBarrels) {
+                    2
+                } else {
+                    1
+                };
+
                 // Increase weapon range with upgrade
                 self.bullet.max_distance = (0.6 + 0.1 * upgrade2) * MAP_SIZE.y;
+                let mut penetration = upgrade1;
+                if has(UpgradeNodeName::LongBarrel) {
+                    self.bullet.max_distance *= 1.25;
+                    penetration += 10.;
+                }
+                if has(UpgradeNodeName::Rifling) {
+                    self.bullet.max_distance *= 1.15;
+                    penetration += 10.;
+                }
 
                 // Reset the target to avoid one last shot at the wrong enemy
                 self.target = None;
 
-                match player.weapons.settings.aaa {
+                match settings.aaa {
                     AirFireStrategy::None => self.fire_strategy = FireStrategy::None,
                     AirFireStrategy::All => {
                         self.fire_strategy = FireStrategy::Closest;
                         self.bullet.impact = Impact::SingleTarget(Damage {
                             ground: 5. + 5. * upgrade1,
                             air: 5. + 5. * upgrade1,
-                            penetration: upgrade1,
+                            penetration,
+                            ..default()
                         })
                     }
                     AirFireStrategy::Airborne => {
@@ -470,38 +1290,78 @@ impl Weapon {
                         self.bullet.impact = Impact::SingleTarget(Damage {
                             ground: 0.,
                             air: 20. + 5. * upgrade1,
-                            penetration: upgrade1,
+                            penetration,
+                            ..default()
                         })
                     }
                     _ => unreachable!(),
                 };
             }
             WeaponName::Artillery => {
+                let (damage, mut reload) = if has(UpgradeNodeName::HeavyShells) {
+                    (1.5, 1.3)
+                } else if has(UpgradeNodeName::AutoLoader) {
+                    (1., 0.7)
+                } else {
+                    (1., 1.)
+                };
+                if has(UpgradeNodeName::BeltFeed) {
+                    reload *= 0.8;
+                }
+                let penetration = if has(UpgradeNodeName::ShapedCharges) {
+                    30.
+                } else {
+                    10.
+                };
+
                 self.bullet.impact = Impact::SingleTarget(Damage {
-                    ground: 40. + 10. * upgrade1,
-                    air: 40. + 10. * upgrade1,
-                    penetration: 10. + upgrade1,
+                    ground: (40. + 10. * upgrade1) * damage,
+                    air: 0.,
+                    penetration: penetration + upgrade1,
+                    ..default()
                 });
-                self.fire_timer = Some(Timer::from_seconds(1. - 0.07 * upgrade2, TimerMode::Once));
+                self.fire_timer = Some(Timer::from_seconds(
+                    (1. - 0.07 * upgrade2) * reload,
+                    TimerMode::Once,
+                ));
 
                 self.target = None;
-                self.fire_strategy = player.weapons.settings.artillery.clone();
+                self.fire_strategy = settings.artillery.clone();
             }
             WeaponName::Canon => {
                 // Reset the target to avoid one last shot at the wrong enemy
                 self.target = None;
 
-                match player.weapons.settings.canon {
+                let (damage, mut radius) = if has(UpgradeNodeName::ClusterShells) {
+                    ((20. + 5. * upgrade1) * 0.8, (0.08 + 0.02 * upgrade2) * 1.5)
+                } else {
+                    (20. + 5. * upgrade1, 0.08 + 0.02 * upgrade2)
+                };
+                if has(UpgradeNodeName::Submunitions) {
+                    radius *= 1.2;
+                }
+
+                // Airburst shells deal part of the damage to the other enemy type
+                let splash = if has(UpgradeNodeName::ProximityFuze) {
+                    damage * 0.8
+                } else if has(UpgradeNodeName::Airburst) {
+                    damage * 0.5
+                } else {
+                    0.
+                };
+
+                match settings.canon {
                     AirFireStrategy::None => self.fire_strategy = FireStrategy::None,
                     AirFireStrategy::Grounded => {
                         self.fire_strategy = FireStrategy::Closest;
                         if let Impact::Explosion(ref mut explosion) = self.bullet.impact {
                             explosion.damage = Damage {
-                                ground: 20. + 5. * upgrade1,
-                                air: 0.,
+                                ground: damage,
+                                air: splash,
                                 penetration: 0.,
+                                ..default()
                             };
-                            explosion.radius = (0.08 + 0.02 * upgrade2) * MAP_SIZE.y;
+                            explosion.radius = radius * MAP_SIZE.y;
                             explosion.interval = 0.01 + 0.005 * upgrade2;
                         }
                     }
@@ -509,55 +1369,138 @@ impl Weapon {
                         self.fire_strategy = FireStrategy::Closest;
                         if let Impact::Explosion(ref mut explosion) = self.bullet.impact {
                             explosion.damage = Damage {
-                                ground: 0.,
-                                air: 20. + 5. * upgrade1,
+                                ground: splash,
+                                air: damage,
                                 penetration: 0.,
+                                ..default()
                             };
-                            explosion.radius = (0.08 + 0.02 * upgrade2) * MAP_SIZE.y;
+                            explosion.radius = radius * MAP_SIZE.y;
                             explosion.interval = 0.01 + 0.005 * upgrade2;
                         }
                     }
                     _ => unreachable!(),
                 };
             }
-            WeaponName::Flamethrower => match player.weapons.settings.flamethrower {
+            WeaponName::Cryo => match settings.cryo {
                 0 => self.fire_strategy = FireStrategy::None,
                 _ => {
-                    let power = player.weapons.settings.flamethrower as f32;
+                    let power = settings.cryo as f32;
+                    let width = if has(UpgradeNodeName::Whiteout) {
+                        3.
+                    } else if has(UpgradeNodeName::Blizzard) {
+                        2.
+                    } else {
+                        1.
+                    };
 
                     self.fire_strategy = FireStrategy::Closest;
                     self.fire_animation.scale.x = 1.5 + power * 0.5;
+                    self.fire_animation.scale.y = width;
+                    self.bullet.dim.y = 40. * width;
                     if let Some(timer) = self.fire_timer.as_mut() {
                         timer.set_duration(Duration::from_secs_f32(
-                            0.1 * (MAX_FLAMETHROWER_POWER + 1 - power as u32) as f32,
+                            0.1 * (MAX_CRYO_POWER + 1 - power as u32) as f32,
                         ));
                     }
                     self.bullet.max_distance = 100. * (1.5 + power * 0.5);
                     self.bullet.price.gasoline = power;
 
-                    if let Impact::Piercing { damage, .. } = &mut self.bullet.impact {
-                        *damage = Damage {
-                            ground: 5. + upgrade1,
-                            air: 5. + upgrade1,
+                    if let Impact::Freeze { slow, duration, .. } = &mut self.bullet.impact {
+                        *slow = 0.05
+                            + 0.01 * upgrade1
+                            + if has(UpgradeNodeName::DeepFreeze) {
+                                0.03
+                            } else {
+                                0.
+                            };
+                        *duration = (2. + 0.3 * upgrade2)
+                            * if has(UpgradeNodeName::Permafrost) {
+                                2.
+                            } else {
+                                1.
+                            };
+                    }
+                }
+            },
+            WeaponName::Flamethrower => match settings.flamethrower {
+                0 => self.fire_strategy = FireStrategy::None,
+                _ => {
+                    let power = settings.flamethrower as f32;
+                    let damage = if has(UpgradeNodeName::WhitePhosphorus) {
+                        2.
+                    } else if has(UpgradeNodeName::Napalm) {
+                        1.5
+                    } else {
+                        1.
+                    };
+                    let width = if has(UpgradeNodeName::WideNozzle) {
+                        2.
+                    } else {
+                        1.
+                    };
+                    let range = if has(UpgradeNodeName::Afterburner) {
+                        1.3
+                    } else {
+                        1.
+                    };
+
+                    self.fire_strategy = FireStrategy::Closest;
+                    self.fire_animation.scale.x = 1.5 + power * 0.5;
+                    self.fire_animation.scale.y = width;
+                    self.bullet.dim.y = 40. * width;
+                    if let Some(timer) = self.fire_timer.as_mut() {
+                        timer.set_duration(Duration::from_secs_f32(
+                            0.1 * (MAX_FLAMETHROWER_POWER + 1 - power as u32) as f32,
+                        ));
+                    }
+                    self.bullet.max_distance = 100. * (1.5 + power * 0.5) * range;
+                    self.bullet.price.gasoline = power * damage;
+
+                    if let Impact::Piercing { damage: d, .. } = &mut self.bullet.impact {
+                        *d = Damage {
+                            ground: (5. + upgrade1) * damage,
+                            air: (5. + upgrade1) * damage,
                             penetration: 5. + upgrade1 + 2. * upgrade2,
+                            fire: true,
+                            ..default()
                         }
                     }
                 }
             },
             WeaponName::MachineGun => {
-                match player.weapons.settings.machine_gun {
+                self.target = None;
+
+                match settings.machine_gun {
                     0 => {
                         self.fire_timer = None;
                         self.fire_strategy = FireStrategy::None;
                     }
                     v => {
+                        let incendiary = has(UpgradeNodeName::Incendiary);
+                        let fire = if has(UpgradeNodeName::Thermite) {
+                            2.
+                        } else if incendiary {
+                            1.5
+                        } else {
+                            1.
+                        };
+
                         self.bullet.impact = Impact::SingleTarget(Damage {
-                            ground: 5. + 2. * upgrade1,
+                            ground: (5. + 2. * upgrade1) * fire,
                             air: 5. + 2. * upgrade1,
-                            penetration: 0.,
+                            penetration: if has(UpgradeNodeName::TungstenCores) {
+                                25.
+                            } else if has(UpgradeNodeName::ArmorPiercing) {
+                                10.
+                            } else {
+                                0.
+                            },
+                            fire: incendiary,
+                            ..default()
                         });
+                        self.bullet.price.gasoline = if incendiary { 0.5 } else { 0. };
                         self.bullet.max_distance = (0.7 + 0.1 * upgrade2) * MAP_SIZE.y;
-                        self.fire_strategy = FireStrategy::Closest;
+                        self.fire_strategy = settings.machine_gun_strategy.clone();
                         if let Some(ref mut timer) = self.fire_timer {
                             timer.set_duration(Duration::from_secs_f32(1. / v as f32));
                         } else {
@@ -570,19 +1513,36 @@ impl Weapon {
             WeaponName::MissileLauncher => {
                 if let Impact::Explosion(Explosion { radius, damage, .. }) = &mut self.bullet.impact
                 {
+                    let (d, mut p) = if has(UpgradeNodeName::BunkerBuster) {
+                        (1.2, 20.)
+                    } else {
+                        (1., 0.)
+                    };
+                    if has(UpgradeNodeName::DeepPenetrator) {
+                        p += 20.;
+                    }
+
                     *radius = (0.1 + 0.02 * upgrade2) * MAP_SIZE.y;
+                    if has(UpgradeNodeName::ClusterWarheads) {
+                        *radius *= 1.5;
+                    }
+                    if has(UpgradeNodeName::Fragmentation) {
+                        *radius *= 1.2;
+                    }
                     *damage = Damage {
-                        ground: 30. + 5. * upgrade1,
-                        air: 30. + 5. * upgrade1,
-                        penetration: 5. + upgrade1,
+                        ground: (30. + 5. * upgrade1) * d,
+                        air: (30. + 5. * upgrade1) * d,
+                        penetration: 5. + upgrade1 + p,
+                        ..default()
                     }
                 }
 
-                self.n_bullets = player.weapons.settings.missile_launcher;
+                self.target = None;
+                self.n_bullets = settings.missile_launcher;
                 if self.n_bullets == 0 {
                     self.fire_strategy = FireStrategy::None;
                 } else {
-                    self.fire_strategy = FireStrategy::Strongest;
+                    self.fire_strategy = settings.missile_launcher_strategy.clone();
                 }
             }
             WeaponName::Mortar => {
@@ -590,8 +1550,21 @@ impl Weapon {
                 self.target = None;
 
                 self.n_bullets = 1 + upgrade2 as u32;
+                if has(UpgradeNodeName::Barrage) {
+                    self.n_bullets += 1;
+                }
+                if has(UpgradeNodeName::Saturation) {
+                    self.n_bullets += 1;
+                }
+                let radius = if has(UpgradeNodeName::HighExplosive) {
+                    1.6
+                } else if has(UpgradeNodeName::HeavyCharge) {
+                    1.3
+                } else {
+                    1.
+                };
 
-                match player.weapons.settings.mortar {
+                match settings.mortar {
                     MortarShell::None => self.fire_strategy = FireStrategy::None,
                     MortarShell::Light => {
                         self.fire_strategy = FireStrategy::Density;
@@ -600,11 +1573,12 @@ impl Weapon {
                             ..default()
                         };
                         self.bullet.impact = Impact::Explosion(Explosion {
-                            radius: 0.05 * MAP_SIZE.y,
+                            radius: 0.05 * radius * MAP_SIZE.y,
                             damage: Damage {
                                 ground: 50. + 10. * upgrade1,
                                 air: 50. + 10. * upgrade1,
                                 penetration: 0.,
+                                ..default()
                             },
                             ..default()
                         })
@@ -616,21 +1590,114 @@ impl Weapon {
                             ..default()
                         };
                         self.bullet.impact = Impact::Explosion(Explosion {
-                            radius: 0.1 * MAP_SIZE.y,
+                            radius: 0.1 * radius * MAP_SIZE.y,
                             damage: Damage {
                                 ground: 75. + 15. * upgrade1,
                                 air: 75. + 15. * upgrade1,
                                 penetration: 25. + 5. * upgrade1,
+                                ..default()
                             },
                             ..default()
                         })
                     }
                 };
             }
+            WeaponName::Sniper => {
+                self.target = None;
+                self.fire_strategy = settings.sniper.clone();
+
+                let reload = if has(UpgradeNodeName::StraightPull) {
+                    0.45
+                } else if has(UpgradeNodeName::SemiAutomatic) {
+                    0.6
+                } else {
+                    1.
+                };
+                self.fire_timer = Some(Timer::from_seconds(4. * reload, TimerMode::Once));
+                self.bullet.impact = Impact::SingleTarget(Damage {
+                    ground: 150. + 30. * upgrade1,
+                    air: 150. + 30. * upgrade1,
+                    penetration: 40.,
+                    crit_chance: 0.1
+                        + 0.04 * upgrade2
+                        + if has(UpgradeNodeName::Marksman) {
+                            0.1
+                        } else {
+                            0.
+                        },
+                    crit_multiplier: if has(UpgradeNodeName::Headhunter) {
+                        5.
+                    } else {
+                        3.
+                    },
+                    ..default()
+                });
+            }
+            WeaponName::Tesla => {
+                self.target = None;
+                self.fire_strategy = settings.tesla.clone();
+
+                let damage = if has(UpgradeNodeName::Supercharge) {
+                    2.
+                } else if has(UpgradeNodeName::Overcharge) {
+                    1.5
+                } else {
+                    1.
+                };
+                self.bullet.price.gasoline = 20. * damage;
+                self.bullet.max_distance = (0.4 + 0.03 * upgrade2) * MAP_SIZE.y;
+                self.bullet.impact = Impact::Chain(Chain {
+                    damage: Damage {
+                        ground: 25. * damage,
+                        air: 25. * damage,
+                        penetration: 5.,
+                        ..default()
+                    },
+                    jumps: 2 + upgrade1 as u32,
+                    range: (0.1 + 0.01 * upgrade2) * MAP_SIZE.y,
+                    falloff: if has(UpgradeNodeName::Superconductor) {
+                        0.95
+                    } else if has(UpgradeNodeName::Conductivity) {
+                        0.9
+                    } else {
+                        0.7
+                    },
+                    origin: Vec3::ZERO,
+                });
+            }
             WeaponName::Turret => {
-                self.fire_timer = Some(Timer::from_seconds(1. - 0.07 * upgrade2, TimerMode::Once));
+                let power_up = if has(UpgradeNodeName::Supercapacitors) {
+                    0.5
+                } else if has(UpgradeNodeName::Capacitors) {
+                    0.7
+                } else {
+                    1.
+                };
+                self.fire_timer = Some(Timer::from_seconds(
+                    (1. - 0.07 * upgrade2) * power_up,
+                    TimerMode::Once,
+                ));
+                self.bullet.price.bullets = if has(UpgradeNodeName::Scavenging) {
+                    50.
+                } else if has(UpgradeNodeName::RecycledCasings) {
+                    100.
+                } else {
+                    200.
+                };
             }
         }
+
+        // Every upgrade level extends the magazine and heat capacity by 10%
+        let extension = 1. + 0.1 * (upgrade1 + upgrade2);
+        if let Some(magazine) = self.magazine.as_mut() {
+            magazine.extend(extension);
+        }
+        if let Some(overheat) = self.overheat.as_mut() {
+            overheat.capacity = overheat.size * extension;
+        }
+
+        self.range_modifier = player.modifier(Stat::WeaponRange);
+        self.bullet.max_distance *= self.range_modifier;
     }
 }
 
@@ -639,15 +1706,21 @@ pub struct WeaponManager {
     pub aaa: Weapon,
     pub artillery: Weapon,
     pub canon: Weapon,
+    pub cryo: Weapon,
     pub flamethrower: Weapon,
     pub machine_gun: Weapon,
     pub mortar: Weapon,
     pub missile_launcher: Weapon,
+    pub sniper: Weapon,
+    pub tesla: Weapon,
     pub turret: Weapon,
 
     pub mine: Bullet,
     pub bomb: Bullet,
     pub nuke: Bullet,
+
+    pub drone: Drone,
+    pub soldier: Soldier,
 }
 
 impl WeaponManager {
@@ -656,10 +1729,13 @@ impl WeaponManager {
             WeaponName::AAA => self.aaa.clone(),
             WeaponName::Artillery => self.artillery.clone(),
             WeaponName::Canon => self.canon.clone(),
+            WeaponName::Cryo => self.cryo.clone(),
             WeaponName::Flamethrower => self.flamethrower.clone(),
             WeaponName::MachineGun => self.machine_gun.clone(),
             WeaponName::Mortar => self.mortar.clone(),
             WeaponName::MissileLauncher => self.missile_launcher.clone(),
+            WeaponName::Sniper => self.sniper.clone(),
+            WeaponName::Tesla => self.tesla.clone(),
             WeaponName::Turret => self.turret.clone(),
         }
     }
@@ -679,10 +1755,18 @@ impl Default for WeaponManager {
                 maximum: MAX_SPOTS,
                 rotation_speed: 5.,
                 target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
                 price: Resources {
                     materials: 300.,
                     ..default()
                 },
+                health: 600.,
+                max_health: 600.,
                 fire_animation: FireAnimation {
                     atlas: "single-flash",
                     scale: Vec3::splat(0.5),
@@ -690,6 +1774,8 @@ impl Default for WeaponManager {
                 },
                 n_bullets: 1,
                 fire_timer: Some(Timer::from_seconds(0.5, TimerMode::Once)),
+                magazine: Some(Magazine::new(8, 2.)),
+                overheat: None,
                 fire_strategy: FireStrategy::Closest,
                 min_distance: 0.,
                 bullet: Bullet {
@@ -705,6 +1791,7 @@ impl Default for WeaponManager {
                         ground: 5.,
                         air: 5.,
                         penetration: 0.,
+                        ..default()
                     }),
                     max_distance: 0.7 * MAP_SIZE.y,
                     distance: 0.,
@@ -725,23 +1812,82 @@ impl Default for WeaponManager {
                         ..default()
                     },
                 },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::TwinBarrels,
+                        description: "Fire two bullets per shot.",
+                        texture: "targets",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 800.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::LongBarrel,
+                        description: "Increase the fire range and the penetration.",
+                        texture: "range",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 600.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::TripleBarrels,
+                        description: "Fire three bullets per shot.",
+                        texture: "targets",
+                        parent: Some(UpgradeNodeName::TwinBarrels),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 1200.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Rifling,
+                        description: "Further increase the fire range and the penetration.",
+                        texture: "range",
+                        parent: Some(UpgradeNodeName::LongBarrel),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 900.,
+                            ..default()
+                        },
+                    },
+                ],
             },
             artillery: Weapon {
                 name: WeaponName::Artillery,
                 image: "weapon/artillery.png",
                 description:"\
                     Long range, single-target, high damage weapon. Although slow to reload, its \
-                    high penetration bullets can kill even the strongest of foes. It has two \
-                    firing strategies: closest (shoots at the closes enemy) and strongest (shoot \
-                    at the enemy with the highest maximum health).",
+                    high penetration shells can kill even the strongest of foes. The shells fly in \
+                    an arc over the enemies, so they can't hit flying bugs. It has two firing \
+                    strategies: closest (shoots at the closes enemy) and strongest (shoot at the \
+                    enemy with the highest maximum health).",
                 dim: Vec2::new(80., 80.),
                 maximum: MAX_SPOTS,
                 rotation_speed: 5.,
                 target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
                 price: Resources {
                     materials: 600.,
                     ..default()
                 },
+                health: 800.,
+                max_health: 800.,
                 fire_animation: FireAnimation {
                     atlas: "cone-flash",
                     scale: Vec3::splat(0.5),
@@ -749,6 +1895,8 @@ impl Default for WeaponManager {
                 },
                 n_bullets: 1,
                 fire_timer: Some(Timer::from_seconds(1., TimerMode::Once)),
+                magazine: Some(Magazine::new(4, 3.)),
+                overheat: None,
                 fire_strategy: FireStrategy::Closest,
                 min_distance: 0.,
                 bullet: Bullet {
@@ -759,11 +1907,12 @@ impl Default for WeaponManager {
                         ..default()
                     },
                     speed: 0.9 * MAP_SIZE.y,
-                    movement: Movement::Straight,
+                    movement: Movement::Ballistic(Ballistic::new(Vec3::ZERO, Vec3::ZERO, 1.)), // Set at spawn
                     impact: Impact::SingleTarget(Damage {
                         ground: 45.,
-                        air: 45.,
+                        air: 0.,
                         penetration: 40.,
+                        ..default()
                     }),
                     max_distance: 1. * MAP_SIZE.y,
                     distance: 0.,
@@ -783,65 +1932,294 @@ impl Default for WeaponManager {
                         technology: 400.,
                         ..default()
                     },
-                },
+                },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::HeavyShells,
+                        description: "Increase the damage, but also the reload time.",
+                        texture: "damage",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 2000.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::AutoLoader,
+                        description: "Decrease the reload time.",
+                        texture: "reload",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 2000.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::ShapedCharges,
+                        description: "Shells pierce through the heaviest armor.",
+                        texture: "damage",
+                        parent: Some(UpgradeNodeName::HeavyShells),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 3000.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::BeltFeed,
+                        description: "Further reduce the reload time.",
+                        texture: "reload",
+                        parent: Some(UpgradeNodeName::AutoLoader),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 3000.,
+                            ..default()
+                        },
+                    },
+                ],
+            },
+            canon: Weapon {
+                name: WeaponName::Canon,
+                image: "weapon/canon.png",
+                description:"\
+                    Medium range and damage weapon that fires explosive shells. Slow to reload. It \
+                    has two firing strategies: grounded (shoots only at ground enemies) and airborne \
+                    (shoots only at flying enemies).",
+                dim: Vec2::new(50., 70.),
+                maximum: MAX_SPOTS,
+                rotation_speed: 6.,
+                target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
+                price: Resources {
+                    materials: 200.,
+                    ..default()
+                },
+                health: 800.,
+                max_health: 800.,
+                fire_animation: FireAnimation {
+                    atlas: "cone-flash",
+                    scale: Vec3::splat(0.5),
+                    duration: 0.1,
+                },
+                n_bullets: 1,
+                fire_timer: Some(Timer::from_seconds(2., TimerMode::Once)),
+                magazine: Some(Magazine::new(6, 2.5)),
+                overheat: None,
+                fire_strategy: FireStrategy::Closest,
+                min_distance: 0.,
+                bullet: Bullet {
+                    image: "weapon/grenade.png",
+                    dim: Vec2::new(25., 10.),
+                    price: Resources {
+                        bullets: 10.,
+                        ..default()
+                    },
+                    speed: 0.6 * MAP_SIZE.y,
+                    movement: Movement::Straight,
+                    impact: Impact::Explosion(Explosion {
+                        atlas: "explosion2",
+                        radius: 0.08 * MAP_SIZE.y,
+                        ..default() // Damage set when updating fire strategy
+                    }),
+                    max_distance: 0.9 * MAP_SIZE.y,
+                    distance: 0.,
+                },
+                upgrade1: Upgrade {
+                    description: "Increase the damage.",
+                    texture: "damage",
+                    price: Resources {
+                        technology: 100.,
+                        ..default()
+                    },
+                },
+                upgrade2: Upgrade {
+                    description: "Increase the explosion radius.",
+                    texture: "explosion",
+                    price: Resources {
+                        technology: 150.,
+                        ..default()
+                    },
+                },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::ClusterShells,
+                        description: "Increase the explosion radius at the cost of some damage.",
+                        texture: "explosion",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 800.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Airburst,
+                        description: "Shells also deal half damage to the enemies of the other type (grounded or airborne).",
+                        texture: "targets",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 800.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Submunitions,
+                        description: "Further increase the explosion radius.",
+                        texture: "explosion",
+                        parent: Some(UpgradeNodeName::ClusterShells),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 1200.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::ProximityFuze,
+                        description: "Increase the damage to the other enemy type.",
+                        texture: "targets",
+                        parent: Some(UpgradeNodeName::Airburst),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 1200.,
+                            ..default()
+                        },
+                    },
+                ],
             },
-            canon: Weapon {
-                name: WeaponName::Canon,
-                image: "weapon/canon.png",
+            cryo: Weapon {
+                name: WeaponName::Cryo,
+                image: "weapon/cryo.png",
                 description:"\
-                    Medium range and damage weapon that fires explosive shells. Slow to reload. It \
-                    has two firing strategies: grounded (shoots only at ground enemies) and airborne \
-                    (shoots only at flying enemies).",
-                dim: Vec2::new(50., 70.),
+                    Short range weapon that sprays a continuous stream of liquid nitrogen. It \
+                    doesn't damage the bugs, but slows down the grounded ones it hits for a few \
+                    seconds. Frozen bugs take extra damage from fire. Like the flamethrower, the \
+                    cryo sprayer can adjust its power at an increased gasoline consumption.",
+                dim: Vec2::new(60., 60.),
                 maximum: MAX_SPOTS,
-                rotation_speed: 6.,
+                rotation_speed: 7.,
                 target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
                 price: Resources {
-                    materials: 200.,
+                    materials: 300.,
                     ..default()
                 },
+                health: 500.,
+                max_health: 500.,
                 fire_animation: FireAnimation {
-                    atlas: "cone-flash",
-                    scale: Vec3::splat(0.5),
-                    duration: 0.1,
+                    atlas: "frost",
+                    scale: Vec3::new(3., 1., 1.),
+                    duration: 0.02,
                 },
                 n_bullets: 1,
-                fire_timer: Some(Timer::from_seconds(2., TimerMode::Once)),
-                fire_strategy: FireStrategy::Closest,
+                fire_timer: Some(Timer::from_seconds(0.5, TimerMode::Once)),
+                magazine: None,
+                overheat: None,
+                fire_strategy: FireStrategy::None,
                 min_distance: 0.,
                 bullet: Bullet {
-                    image: "weapon/grenade.png",
-                    dim: Vec2::new(25., 10.),
+                    image: "weapon/invisible-bullet.png",
+                    dim: Vec2::new(20., 40.),
                     price: Resources {
-                        bullets: 10.,
+                        gasoline: 5.,
                         ..default()
                     },
-                    speed: 0.6 * MAP_SIZE.y,
+                    speed: 1.2 * MAP_SIZE.y,
                     movement: Movement::Straight,
-                    impact: Impact::Explosion(Explosion {
-                        atlas: "explosion2",
-                        radius: 0.08 * MAP_SIZE.y,
-                        ..default() // Damage set when updating fire strategy
-                    }),
-                    max_distance: 0.9 * MAP_SIZE.y,
+                    impact: Impact::Freeze {
+                        slow: 0.05,
+                        duration: 2.,
+                        hits: HashSet::new(),
+                    },
+                    max_distance: 0., // Is set by self.update()
                     distance: 0.,
                 },
                 upgrade1: Upgrade {
-                    description: "Increase the damage.",
-                    texture: "damage",
+                    description: "Increase the slow per hit.",
+                    texture: "hourglass",
                     price: Resources {
-                        technology: 100.,
+                        technology: 150.,
                         ..default()
                     },
                 },
                 upgrade2: Upgrade {
-                    description: "Increase the explosion radius.",
-                    texture: "explosion",
+                    description: "Increase the duration of the slow.",
+                    texture: "clock",
                     price: Resources {
-                        technology: 150.,
+                        technology: 100.,
                         ..default()
                     },
                 },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::Permafrost,
+                        description: "Double the duration of the slow.",
+                        texture: "clock",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 800.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Blizzard,
+                        description: "Double the width of the stream.",
+                        texture: "range",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 800.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::DeepFreeze,
+                        description: "Slow down the frozen enemies even more.",
+                        texture: "clock",
+                        parent: Some(UpgradeNodeName::Permafrost),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 1200.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Whiteout,
+                        description: "Further widen the spray.",
+                        texture: "range",
+                        parent: Some(UpgradeNodeName::Blizzard),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 1200.,
+                            ..default()
+                        },
+                    },
+                ],
             },
             flamethrower: Weapon {
                 name: WeaponName::Flamethrower,
@@ -854,10 +2232,18 @@ impl Default for WeaponManager {
                 maximum: MAX_SPOTS,
                 rotation_speed: 7.,
                 target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
                 price: Resources {
                     materials: 300.,
                     ..default()
                 },
+                health: 500.,
+                max_health: 500.,
                 fire_animation: FireAnimation {
                     atlas: "flame",
                     scale: Vec3::new(3., 1., 1.),
@@ -865,6 +2251,8 @@ impl Default for WeaponManager {
                 },
                 n_bullets: 1,
                 fire_timer: Some(Timer::from_seconds(0.5, TimerMode::Once)),
+                magazine: None,
+                overheat: Some(Overheat::new(100., 4., 15.)),
                 fire_strategy: FireStrategy::None,
                 min_distance: 0.,
                 bullet: Bullet {
@@ -881,6 +2269,8 @@ impl Default for WeaponManager {
                             ground: 5.,
                             air: 5.,
                             penetration: 5.,
+                            fire: true,
+                            ..default()
                         },
                         hits: HashSet::new(),
                     },
@@ -903,6 +2293,56 @@ impl Default for WeaponManager {
                         ..default()
                     },
                 },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::Napalm,
+                        description: "Increase the damage, but also the gasoline consumption.",
+                        texture: "damage",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 800.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::WideNozzle,
+                        description: "Double the width of the fire stream.",
+                        texture: "range",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 800.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::WhitePhosphorus,
+                        description: "Further increase the damage, at a higher gasoline cost.",
+                        texture: "damage",
+                        parent: Some(UpgradeNodeName::Napalm),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 1200.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Afterburner,
+                        description: "Increase the range of the flames.",
+                        texture: "range",
+                        parent: Some(UpgradeNodeName::WideNozzle),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 1200.,
+                            ..default()
+                        },
+                    },
+                ],
             },
             machine_gun: Weapon {
                 name: WeaponName::MachineGun,
@@ -914,10 +2354,18 @@ impl Default for WeaponManager {
                 maximum: MAX_SPOTS,
                 rotation_speed: 7.,
                 target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
                 price: Resources {
                     materials: 100.,
                     ..default()
                 },
+                health: 600.,
+                max_health: 600.,
                 fire_animation: FireAnimation {
                     atlas: "single-flash",
                     scale: Vec3::splat(0.5),
@@ -925,6 +2373,8 @@ impl Default for WeaponManager {
                 },
                 n_bullets: 1,
                 fire_timer: None,
+                magazine: Some(Magazine::new(30, 2.)),
+                overheat: Some(Overheat::new(100., 5., 15.)),
                 fire_strategy: FireStrategy::None,
                 min_distance: 0.,
                 bullet: Bullet {
@@ -940,6 +2390,7 @@ impl Default for WeaponManager {
                         ground: 5.,
                         air: 5.,
                         penetration: 0.,
+                        ..default()
                     }),
                     max_distance: 0.7 * MAP_SIZE.y,
                     distance: 0.,
@@ -960,6 +2411,56 @@ impl Default for WeaponManager {
                         ..default()
                     },
                 },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::Incendiary,
+                        description: "Increase the damage to ground enemies, but bullets cost gasoline.",
+                        texture: "damage",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 300.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::ArmorPiercing,
+                        description: "Increase the penetration.",
+                        texture: "penetration",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 300.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Thermite,
+                        description: "Further increase the damage of the incendiary rounds.",
+                        texture: "damage",
+                        parent: Some(UpgradeNodeName::Incendiary),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 450.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::TungstenCores,
+                        description: "Bullets pierce through heavier armor.",
+                        texture: "penetration",
+                        parent: Some(UpgradeNodeName::ArmorPiercing),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 450.,
+                            ..default()
+                        },
+                    },
+                ],
             },
             missile_launcher: Weapon {
                 name: WeaponName::MissileLauncher,
@@ -973,10 +2474,18 @@ impl Default for WeaponManager {
                 maximum: 2,
                 rotation_speed: 5.,
                 target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
                 price: Resources {
                     materials: 1200.,
                     ..default()
                 },
+                health: 700.,
+                max_health: 700.,
                 fire_animation: FireAnimation {
                     atlas: "wide-flash",
                     scale: Vec3::splat(0.7),
@@ -984,6 +2493,8 @@ impl Default for WeaponManager {
                 },
                 n_bullets: 1,
                 fire_timer: Some(Timer::from_seconds(3., TimerMode::Once)),
+                magazine: None,
+                overheat: None,
                 fire_strategy: FireStrategy::None,
                 min_distance: 0.15 * MAP_SIZE.y,
                 bullet: Bullet {
@@ -1001,6 +2512,7 @@ impl Default for WeaponManager {
                             ground: 30.,
                             air: 30.,
                             penetration: 5.,
+                            ..default()
                         },
                         ..default()
                     }),
@@ -1023,6 +2535,56 @@ impl Default for WeaponManager {
                         ..default()
                     },
                 },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::ClusterWarheads,
+                        description: "Increase the explosion radius.",
+                        texture: "explosion",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 4000.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::BunkerBuster,
+                        description: "Increase the damage and penetration.",
+                        texture: "penetration",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 4000.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Fragmentation,
+                        description: "Further increase the explosion radius.",
+                        texture: "explosion",
+                        parent: Some(UpgradeNodeName::ClusterWarheads),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 6000.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::DeepPenetrator,
+                        description: "Missiles pierce through the heaviest armor.",
+                        texture: "penetration",
+                        parent: Some(UpgradeNodeName::BunkerBuster),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 6000.,
+                            ..default()
+                        },
+                    },
+                ],
             },
             mortar: Weapon {
                 name: WeaponName::Mortar,
@@ -1036,10 +2598,18 @@ impl Default for WeaponManager {
                 maximum: MAX_SPOTS,
                 rotation_speed: 5.,
                 target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
                 price: Resources {
                     materials: 400.,
                     ..default()
                 },
+                health: 700.,
+                max_health: 700.,
                 fire_animation: FireAnimation {
                     atlas: "wide-flash",
                     scale: Vec3::splat(0.5),
@@ -1047,6 +2617,8 @@ impl Default for WeaponManager {
                 },
                 n_bullets: 1,
                 fire_timer: Some(Timer::from_seconds(3., TimerMode::Once)),
+                magazine: None,
+                overheat: None,
                 fire_strategy: FireStrategy::None,
                 min_distance: 0.2 * MAP_SIZE.y,
                 bullet: Bullet {
@@ -1057,13 +2629,14 @@ impl Default for WeaponManager {
                         ..default()
                     },
                     speed: 0.6 * MAP_SIZE.y,
-                    movement: Movement::Location(Vec3::splat(0.)), // Set at spawn
+                    movement: Movement::Ballistic(Ballistic::new(Vec3::ZERO, Vec3::ZERO, 1.)), // Set at spawn
                     impact: Impact::Explosion(Explosion {
                         radius: 0.15 * MAP_SIZE.y,
                         damage: Damage {
                             ground: 50.,
                             air: 50.,
                             penetration: 0.,
+                            ..default()
                         },
                         ..default()
                     }),
@@ -1086,6 +2659,303 @@ impl Default for WeaponManager {
                         ..default()
                     },
                 },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::Barrage,
+                        description: "Fire an extra shell.",
+                        texture: "targets",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 1500.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::HeavyCharge,
+                        description: "Increase the explosion radius.",
+                        texture: "explosion",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 1500.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Saturation,
+                        description: "Fire yet another shell per shot.",
+                        texture: "targets",
+                        parent: Some(UpgradeNodeName::Barrage),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 2250.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::HighExplosive,
+                        description: "Further increase the explosion radius.",
+                        texture: "explosion",
+                        parent: Some(UpgradeNodeName::HeavyCharge),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 2250.,
+                            ..default()
+                        },
+                    },
+                ],
+            },
+            sniper: Weapon {
+                name: WeaponName::Sniper,
+                image: "weapon/sniper.png",
+                description: "\
+                    Very long range, single-target weapon with a very slow reload. Every shot \
+                    has a chance to hit a weak point, dealing critical damage. With the spotlight \
+                    on, the sniper can see partially through the fog. By default, it shoots at \
+                    the enemy with the highest maximum health.",
+                dim: Vec2::new(40., 90.),
+                maximum: MAX_SPOTS,
+                rotation_speed: 4.,
+                target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
+                price: Resources {
+                    materials: 700.,
+                    ..default()
+                },
+                health: 400.,
+                max_health: 400.,
+                fire_animation: FireAnimation {
+                    atlas: "single-flash",
+                    scale: Vec3::splat(0.4),
+                    duration: 0.1,
+                },
+                n_bullets: 1,
+                fire_timer: Some(Timer::from_seconds(4., TimerMode::Once)),
+                magazine: Some(Magazine::new(5, 3.)),
+                overheat: None,
+                fire_strategy: FireStrategy::Strongest,
+                min_distance: 0.,
+                bullet: Bullet {
+                    image: "weapon/bullet.png",
+                    dim: Vec2::new(25., 7.),
+                    price: Resources {
+                        bullets: 50.,
+                        ..default()
+                    },
+                    speed: 2.5 * MAP_SIZE.y,
+                    movement: Movement::Straight,
+                    impact: Impact::SingleTarget(Damage {
+                        ground: 150.,
+                        air: 150.,
+                        penetration: 40.,
+                        crit_chance: 0.1,
+                        crit_multiplier: 3.,
+                        ..default()
+                    }),
+                    max_distance: 2. * MAP_SIZE.y,
+                    distance: 0.,
+                },
+                upgrade1: Upgrade {
+                    description: "Increase the damage.",
+                    texture: "damage",
+                    price: Resources {
+                        technology: 300.,
+                        ..default()
+                    },
+                },
+                upgrade2: Upgrade {
+                    description: "Increase the critical hit chance.",
+                    texture: "penetration",
+                    price: Resources {
+                        technology: 400.,
+                        ..default()
+                    },
+                },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::Headhunter,
+                        description: "Increase the damage of critical hits.",
+                        texture: "damage",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 2000.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::SemiAutomatic,
+                        description: "Decrease the reload time.",
+                        texture: "reload",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 2000.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Marksman,
+                        description: "Increase the chance of a critical hit.",
+                        texture: "damage",
+                        parent: Some(UpgradeNodeName::Headhunter),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 3000.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::StraightPull,
+                        description: "Further reduce the reload time.",
+                        texture: "reload",
+                        parent: Some(UpgradeNodeName::SemiAutomatic),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 3000.,
+                            ..default()
+                        },
+                    },
+                ],
+            },
+            tesla: Weapon {
+                name: WeaponName::Tesla,
+                image: "weapon/tesla.png",
+                description: "\
+                    Short range weapon that fires an electric arc. The arc jumps from the enemy \
+                    hit to the closest enemies around it, losing damage with every jump. Very \
+                    effective against groups of weak enemies. It consumes gasoline to fire.",
+                dim: Vec2::new(60., 75.),
+                maximum: MAX_SPOTS,
+                rotation_speed: 10.,
+                target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
+                price: Resources {
+                    materials: 800.,
+                    ..default()
+                },
+                health: 600.,
+                max_health: 600.,
+                fire_animation: FireAnimation {
+                    atlas: "single-flash",
+                    scale: Vec3::splat(0.3),
+                    duration: 0.1,
+                },
+                n_bullets: 1,
+                fire_timer: Some(Timer::from_seconds(1.5, TimerMode::Once)),
+                magazine: None,
+                overheat: None,
+                fire_strategy: FireStrategy::Closest,
+                min_distance: 0.,
+                bullet: Bullet {
+                    image: "weapon/invisible-bullet.png",
+                    dim: Vec2::new(10., 10.),
+                    price: Resources {
+                        gasoline: 20.,
+                        ..default()
+                    },
+                    speed: 3. * MAP_SIZE.y,
+                    movement: Movement::Homing(Entity::from_raw(0)), // Set at spawn
+                    impact: Impact::Chain(Chain {
+                        damage: Damage {
+                            ground: 25.,
+                            air: 25.,
+                            penetration: 5.,
+                            ..default()
+                        },
+                        jumps: 2,
+                        range: 0.1 * MAP_SIZE.y,
+                        falloff: 0.7,
+                        origin: Vec3::ZERO, // Set at spawn
+                    }),
+                    max_distance: 0.4 * MAP_SIZE.y,
+                    distance: 0.,
+                },
+                upgrade1: Upgrade {
+                    description: "Increase the number of jumps of the arc.",
+                    texture: "targets",
+                    price: Resources {
+                        technology: 300.,
+                        ..default()
+                    },
+                },
+                upgrade2: Upgrade {
+                    description: "Increase the range of the arc.",
+                    texture: "range",
+                    price: Resources {
+                        technology: 250.,
+                        ..default()
+                    },
+                },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::Overcharge,
+                        description: "Increase the damage, but also the gasoline consumption.",
+                        texture: "damage",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 1500.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Conductivity,
+                        description: "The arc loses less damage with every jump.",
+                        texture: "targets",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 1500.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Supercharge,
+                        description: "Further increase the damage, at a higher gasoline cost.",
+                        texture: "damage",
+                        parent: Some(UpgradeNodeName::Overcharge),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 2250.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Superconductor,
+                        description: "The arc barely loses damage when jumping.",
+                        texture: "targets",
+                        parent: Some(UpgradeNodeName::Conductivity),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 2250.,
+                            ..default()
+                        },
+                    },
+                ],
             },
             turret: Weapon {
                 name: WeaponName::Turret,
@@ -1101,10 +2971,18 @@ impl Default for WeaponManager {
                 maximum: 1,
                 rotation_speed: 5.,
                 target: None,
+                in_range: Vec::new(),
+                in_range_set: HashSet::new(),
+                range_modifier: 1.,
+                spot: Uuid::nil(),
+                mount: Mount::Wall,
+                rank: 0,
                 price: Resources {
                     materials: 1000.,
                     ..default()
                 },
+                health: 1000.,
+                max_health: 1000.,
                 fire_animation: FireAnimation {
                     atlas: "triple-flash",
                     scale: Vec3::splat(0.6),
@@ -1112,6 +2990,8 @@ impl Default for WeaponManager {
                 },
                 n_bullets: 1,
                 fire_timer: Some(Timer::from_seconds(1., TimerMode::Once)),
+                magazine: None,
+                overheat: None,
                 fire_strategy: FireStrategy::None,
                 min_distance: 0.,
                 bullet: Bullet {
@@ -1129,6 +3009,7 @@ impl Default for WeaponManager {
                             ground: 50.,
                             air: 50.,
                             penetration: 0.,
+                            ..default()
                         },
                         hits: HashSet::new(),
                     },
@@ -1151,6 +3032,56 @@ impl Default for WeaponManager {
                         ..default()
                     },
                 },
+                tree: vec![
+                    UpgradeNode {
+                        name: UpgradeNodeName::Capacitors,
+                        description: "Increase the power-up speed.",
+                        texture: "reload",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 2500.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::RecycledCasings,
+                        description: "Halve the bullets cost of every shot.",
+                        texture: "bullets",
+                        parent: None,
+                        level: SPECIALIZATION_LEVEL,
+                        group: Some(0),
+                        price: Resources {
+                            technology: 2500.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Supercapacitors,
+                        description: "Further reduce the time to power up.",
+                        texture: "reload",
+                        parent: Some(UpgradeNodeName::Capacitors),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 3750.,
+                            ..default()
+                        },
+                    },
+                    UpgradeNode {
+                        name: UpgradeNodeName::Scavenging,
+                        description: "Further reduce the bullets consumed per shot.",
+                        texture: "bullets",
+                        parent: Some(UpgradeNodeName::RecycledCasings),
+                        level: MASTERY_LEVEL,
+                        group: None,
+                        price: Resources {
+                            technology: 3750.,
+                            ..default()
+                        },
+                    },
+                ],
             },
             mine: Bullet {
                 image: "weapon/mine.png",
@@ -1169,6 +3100,7 @@ impl Default for WeaponManager {
                         ground: 50.,
                         air: 0.,
                         penetration: 20.,
+                        ..default()
                     },
                     ..default()
                 }),
@@ -1192,6 +3124,7 @@ impl Default for WeaponManager {
                         ground: 80.,
                         air: 80.,
                         penetration: 20.,
+                        ..default()
                     },
                     ..default()
                 }),
@@ -1207,11 +3140,7 @@ impl Default for WeaponManager {
                     ..default()
                 },
                 speed: 0.2 * MAP_SIZE.y,
-                movement: Movement::Location(Vec3::new(
-                    -WEAPONS_PANEL_SIZE.x * 0.5,
-                    SIZE.y * 0.5 - MAP_SIZE.y * 0.5,
-                    EXPLOSION_Z,
-                )),
+                movement: Movement::Location(Vec3::splat(0.)), // Set at spawn
                 impact: Impact::Explosion(Explosion {
                     interval: 0.05,
                     radius: 1.5 * MAP_SIZE.y,
@@ -1219,12 +3148,63 @@ impl Default for WeaponManager {
                         ground: 100_000.,
                         air: 100_000.,
                         penetration: 100_000.,
+                        ..default()
                     },
                     ..default()
                 }),
                 max_distance: f32::MAX,
                 distance: 0.,
             },
+            drone: Drone {
+                image: "weapon/drone.png",
+                dim: Vec2::new(40., 40.),
+                health: 100.,
+                max_health: 100.,
+                speed: 0.25 * MAP_SIZE.y,
+                range: 0.15 * MAP_SIZE.y,
+                max_ammo: 20,
+                ammo: 20,
+                price: Resources {
+                    bullets: 2.,
+                    ..default()
+                },
+                fire_timer: Timer::from_seconds(0.3, TimerMode::Once),
+                bullet: Bullet {
+                    image: "weapon/bullet.png",
+                    dim: Vec2::new(15., 4.),
+                    price: Resources::default(), // Paid when rearming
+                    speed: 0.8 * MAP_SIZE.y,
+                    movement: Movement::Homing(Entity::from_raw(0)), // Set at spawn
+                    impact: Impact::SingleTarget(Damage {
+                        ground: 10.,
+                        air: 10.,
+                        ..default()
+                    }),
+                    max_distance: 0.3 * MAP_SIZE.y,
+                    distance: 0.,
+                },
+                home: Vec3::ZERO, // Set at spawn
+                status: DroneStatus::Patrolling,
+                target: None,
+                direction: 1.,
+            },
+            soldier: Soldier {
+                image: "weapon/soldier.png",
+                dim: Vec2::new(30., 30.),
+                health: 100.,
+                max_health: 100.,
+                speed: 0.08 * MAP_SIZE.y,
+                range: 0.12 * MAP_SIZE.y,
+                damage: Damage {
+                    ground: 12.,
+                    air: 6.,
+                    ..default()
+                }, // Scaled with the soldier damage at spawn
+                fire_timer: Timer::from_seconds(0.6, TimerMode::Once),
+                post: 0.,         // Set at spawn
+                home: Vec3::ZERO, // Set at spawn
+                status: SortieStatus::Fighting,
+            },
         }
     }
 }
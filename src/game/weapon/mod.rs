@@ -11,10 +11,25 @@ pub struct WeaponPlugin;
 impl Plugin for WeaponPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::Night), spawn_weapons)
+            .add_systems(OnEnter(AppState::Day), respawn_mines)
             .add_systems(
                 Update,
-                (spawn_bullets, move_bullets)
+                (
+                    spawn_bullets,
+                    move_bullets,
+                    move_drones,
+                    move_soldiers,
+                    run_lightning,
+                )
                     .run_if(in_state(AppState::Night).and(in_state(GameState::Running))),
-            );
+            )
+            .add_systems(
+                Update,
+                (draw_lightning, update_weapon_bars).run_if(in_state(AppState::Night)),
+            )
+            .add_systems(Update, draw_targeting)
+            .add_observer(target_map)
+            .add_observer(drag_weapon)
+            .add_observer(drop_weapon);
     }
 }
@@ -3,10 +3,20 @@ use crate::game::assets::WorldAssets;
 use crate::game::enemy::components::Enemy;
 use crate::game::enemy::utils::get_future_position;
 use crate::game::map::components::{AnimationComponent, FogOfWar};
-use crate::game::map::utils::collision;
-use crate::game::resources::{GameSettings, NightStats, Player, TechnologyName};
+use crate::game::map::utils::{collision, is_visible};
+use crate::game::resources::{GameSettings, NightStats, Player, Stat, Targeting, TechnologyName};
 use crate::game::weapon::components::*;
+use crate::game::weapon::utils::{
+    closest_mount, cursor_to_map, free_weapon_x, get_closest_enemy, is_valid_mine_position,
+    is_valid_weapon_position, mine_area, mount_y,
+};
+use crate::game::GameState;
+use crate::messages::Messages;
+use crate::utils::scale_duration;
+use bevy::color::palettes::basic::{AQUA, GRAY, LIME, RED};
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::EguiContexts;
 use rand::prelude::*;
 use std::collections::HashSet;
 use std::f32::consts::PI;
@@ -30,11 +40,7 @@ pub fn spawn_fence(
                 custom_size: Some(Vec2::new(FENCE_SIZE.x, FENCE_SIZE.y * level as f32 / 3.)),
                 ..default()
             },
-            Transform::from_xyz(
-                -WEAPONS_PANEL_SIZE.x * 0.5,
-                -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + WALL_SIZE.y * 1.4,
-                STRUCTURE_Z,
-            ),
+            Transform::from_xyz(-WEAPONS_PANEL_SIZE.x * 0.5, FENCE_Y, STRUCTURE_Z),
             FenceComponent,
         ));
     }
@@ -53,25 +59,23 @@ pub fn spawn_wall(
                 custom_size: Some(WALL_SIZE),
                 ..default()
             },
-            Transform::from_xyz(
-                -WEAPONS_PANEL_SIZE.x * 0.5,
-                -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + WALL_SIZE.y * 0.5,
-                STRUCTURE_Z,
-            ),
+            Transform::from_xyz(-WEAPONS_PANEL_SIZE.x * 0.5, WALL_Y, STRUCTURE_Z),
             WallComponent,
         ));
     }
 }
 
+/// Spawn the weapons on their spots. Returns the number of weapons
+/// that weren't spawned because there's no room left on their mount
 pub fn spawn_spots(
     commands: &mut Commands,
     weapon_q: &Query<Entity, With<Weapon>>,
     player: &Player,
     weapons: &WeaponManager,
     asset_server: &AssetServer,
-) {
+) -> u32 {
     for entity in weapon_q.iter() {
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
     }
 
     let positions = player
@@ -82,82 +86,258 @@ pub fn spawn_spots(
         .map(|(i, _)| (i + 1) as f32 * MAP_SIZE.x / (player.weapons.spots.len() + 1) as f32)
         .collect::<Vec<f32>>();
 
-    for (spot, pos) in player.weapons.spots.iter().zip(positions) {
+    // Place the weapons with a fixed position first, so the
+    // evenly spaced ones can move aside instead of overlapping
+    let mut spots = player
+        .weapons
+        .spots
+        .iter()
+        .zip(positions)
+        .collect::<Vec<_>>();
+    spots.sort_by_key(|(spot, _)| spot.x.is_none());
+
+    let mut placed: Vec<(Transform, Weapon)> = vec![];
+    let mut skipped = 0;
+    for (spot, pos) in spots {
         if let Some(w) = spot.weapon {
             let mut w = weapons.get(&w);
-            w.update(&player); // Set the weapon's setting at start
-
-            commands.spawn((
-                Sprite {
-                    image: asset_server.load(w.image),
-                    custom_size: Some(w.dim),
-                    ..default()
-                },
-                Transform::from_translation(Vec3::new(
-                    -SIZE.x * 0.5 + pos,
-                    -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + WALL_SIZE.y * 0.5,
-                    WEAPON_Z,
-                )),
-                w,
-            ));
+            w.spot = spot.id;
+            w.mount = if spot.mount == Mount::Fence && player.fence.health > 0. {
+                Mount::Fence
+            } else {
+                Mount::Wall
+            };
+            w.rank = spot.rank();
+            w.health = (w.max_health - spot.damage).max(0.);
+            w.update(player); // Set the weapon's setting at start
+
+            let bars = [
+                Some(WeaponBar::Health),
+                w.magazine.as_ref().map(|_| WeaponBar::Magazine),
+                w.overheat.as_ref().map(|_| WeaponBar::Heat),
+            ];
+            let dim = w.dim;
+
+            let x = match spot.x {
+                Some(x) => -SIZE.x * 0.5 + x,
+                None => {
+                    let Some(x) = free_weapon_x(
+                        -SIZE.x * 0.5 + pos,
+                        w.dim.x,
+                        w.mount,
+                        placed.iter().map(|(t, w)| (t, w)),
+                    ) else {
+                        skipped += 1;
+                        continue;
+                    };
+                    x
+                }
+            };
+            let transform = Transform::from_xyz(x, mount_y(w.mount), WEAPON_Z);
+            placed.push((transform, w.clone()));
+
+            commands
+                .spawn((
+                    Sprite {
+                        image: asset_server.load(w.image),
+                        custom_size: Some(w.dim),
+                        color: if w.is_destroyed() {
+                            Color::from(GRAY)
+                        } else {
+                            Color::WHITE
+                        },
+                        ..default()
+                    },
+                    transform,
+                    w,
+                ))
+                .with_children(|parent| {
+                    for bar in bars.into_iter().flatten() {
+                        parent
+                            .spawn((
+                                Sprite {
+                                    color: Color::BLACK,
+                                    custom_size: Some(Vec2::new(dim.x * 0.8, dim.y * 0.08)),
+                                    ..default()
+                                },
+                                Transform::from_xyz(0., 0., 0.1),
+                                bar,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Sprite {
+                                        color: bar.color(),
+                                        custom_size: Some(Vec2::new(
+                                            dim.x * 0.8 - 2.,
+                                            dim.y * 0.08 - 2.,
+                                        )),
+                                        ..default()
+                                    },
+                                    Transform::from_xyz(0., 0., 0.1),
+                                    WeaponBarFill,
+                                ));
+                            });
+                    }
+                });
         }
     }
+
+    skipped
+}
+
+/// Drop a bomb or nuke from the top of the screen on the target location
+pub fn spawn_airstrike(
+    commands: &mut Commands,
+    mut bullet: Bullet,
+    target: Vec2,
+    asset_server: &AssetServer,
+) {
+    bullet.movement = Movement::Location(target.extend(WEAPON_Z));
+
+    commands.spawn((
+        Sprite {
+            image: asset_server.load(bullet.image),
+            custom_size: Some(bullet.dim),
+            ..default()
+        },
+        Transform {
+            translation: Vec3::new(target.x, SIZE.y * 0.5, WEAPON_Z),
+            rotation: Quat::from_rotation_z(-PI * 0.5),
+            ..default()
+        },
+        bullet,
+    ));
+}
+
+pub fn spawn_mines(
+    commands: &mut Commands,
+    mine_q: &Query<Entity, With<Mine>>,
+    player: &Player,
+    weapons: &WeaponManager,
+    asset_server: &AssetServer,
+) {
+    for entity in mine_q.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for &(x, y) in player.weapons.mine_positions.iter() {
+        commands.spawn((
+            Sprite {
+                image: asset_server.load(weapons.mine.image),
+                custom_size: Some(weapons.mine.dim),
+                ..default()
+            },
+            Transform::from_xyz(x, y, STRUCTURE_Z),
+            weapons.mine.clone(),
+            Mine,
+        ));
+    }
 }
 
+pub fn spawn_drones(
+    commands: &mut Commands,
+    drone_q: &Query<Entity, With<Drone>>,
+    player: &Player,
+    weapons: &WeaponManager,
+    asset_server: &AssetServer,
+) {
+    for entity in drone_q.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // The bays are spread over the top of the wall
+    for i in 0..player.weapons.drones {
+        let home = Vec3::new(
+            -SIZE.x * 0.5 + (i + 1) as f32 * MAP_SIZE.x / (player.weapons.drones + 1) as f32,
+            -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + WALL_SIZE.y * 0.9,
+            WEAPON_Z,
+        );
+
+        commands.spawn((
+            Sprite {
+                image: asset_server.load(weapons.drone.image),
+                custom_size: Some(weapons.drone.dim),
+                ..default()
+            },
+            Transform::from_translation(home),
+            Drone {
+                home,
+                ..weapons.drone.clone()
+            },
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_weapons(
     mut commands: Commands,
     fence_q: Query<SpriteQ, With<FenceComponent>>,
     wall_q: Query<SpriteQ, With<WallComponent>>,
     weapon_q: Query<Entity, With<Weapon>>,
-    mine_q: Query<SpriteQ, With<Mine>>,
-    player: Res<Player>,
+    mine_q: Query<Entity, With<Mine>>,
+    drone_q: Query<Entity, With<Drone>>,
+    mut player: ResMut<Player>,
     weapons: Res<WeaponManager>,
+    mut messages: ResMut<Messages>,
     asset_server: Res<AssetServer>,
 ) {
     spawn_fence(&mut commands, &fence_q, &player, &asset_server);
     spawn_wall(&mut commands, &wall_q, &player, &asset_server);
-    spawn_spots(&mut commands, &weapon_q, &player, &weapons, &asset_server);
 
-    // Spawn mines
-    let mut positions = mine_q
-        .iter()
-        .map(|(_, t, _)| t.translation.truncate())
-        .collect::<Vec<_>>();
+    let skipped = spawn_spots(&mut commands, &weapon_q, &player, &weapons, &asset_server);
+    if skipped > 0 {
+        messages.warning(format!(
+            "{skipped} weapons don't fit on the wall or the fence and stay in the armory. Drag the \
+            other weapons to make room."
+        ));
+    }
 
+    // Mines that weren't placed by the player are scattered at random. Mines
+    // that don't fit anymore are left in the armory for the next night
     let size = weapons.mine.dim;
-    while positions.len() < player.weapons.mines as usize {
-        let x = thread_rng()
-            .gen_range(-SIZE.x * 0.5 + size.x..=SIZE.x * 0.5 - WEAPONS_PANEL_SIZE.x - size.x);
-        let y = thread_rng().gen_range(
-            -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + 2. * WALL_SIZE.y
-                ..=SIZE.y * 0.5 - MENU_PANEL_SIZE.y - FOW_SIZE.y - size.y,
+    let area = mine_area(size);
+    let mut attempts = 0;
+    while player.weapons.mine_positions.len() < player.weapons.mines as usize
+        && attempts < MINE_ATTEMPTS
+    {
+        attempts += 1;
+        let pos = Vec2::new(
+            thread_rng().gen_range(area.min.x..=area.max.x),
+            thread_rng().gen_range(area.min.y..=area.max.y),
         );
-        let pos = Vec2::new(x, y);
 
-        // Check mines spawn at a minimum distance from each other
-        if positions
-            .iter()
-            .all(|&v| pos.distance(v) >= 2. * size.length())
-        {
-            positions.push(pos);
-
-            commands.spawn((
-                Sprite {
-                    image: asset_server.load(weapons.mine.image),
-                    custom_size: Some(weapons.mine.dim),
-                    ..default()
-                },
-                Transform::from_xyz(pos.x, pos.y, STRUCTURE_Z),
-                weapons.mine.clone(),
-                Mine,
-            ));
+        if is_valid_mine_position(pos, &player.weapons.mine_positions, size) {
+            player.weapons.mine_positions.push((pos.x, pos.y));
         }
     }
+
+    let unplaced =
+        (player.weapons.mines as usize).saturating_sub(player.weapons.mine_positions.len());
+    if unplaced > 0 {
+        messages.warning(format!(
+            "{unplaced} mines didn't fit on the map and stay in the armory."
+        ));
+    }
+
+    spawn_mines(&mut commands, &mine_q, &player, &weapons, &asset_server);
+    spawn_drones(&mut commands, &drone_q, &player, &weapons, &asset_server);
 }
 
+/// Show the mines placed by the player during the day
+pub fn respawn_mines(
+    mut commands: Commands,
+    mine_q: Query<Entity, With<Mine>>,
+    player: Res<Player>,
+    weapons: Res<WeaponManager>,
+    asset_server: Res<AssetServer>,
+) {
+    spawn_mines(&mut commands, &mine_q, &player, &weapons, &asset_server);
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_bullets(
     mut commands: Commands,
-    mut weapon_q: Query<(&mut Transform, &mut Weapon)>,
+    mut weapon_q: Query<(&mut Transform, &mut Sprite, &mut Weapon)>,
     enemy_q: Query<EnemyQ, (With<Enemy>, Without<Weapon>)>,
     fence_q: Query<SpriteQ, (With<FenceComponent>, Without<Weapon>)>,
     wall_q: Query<SpriteQ, (With<WallComponent>, Without<Weapon>)>,
@@ -169,10 +349,29 @@ pub fn spawn_bullets(
     assets: Local<WorldAssets>,
     asset_server: Res<AssetServer>,
 ) {
-    for (mut weapon_t, mut weapon) in weapon_q.iter_mut() {
+    for (mut weapon_t, mut weapon_s, mut weapon) in weapon_q.iter_mut() {
+        // Destroyed weapons don't fire for the rest of the night
+        if weapon.is_destroyed() {
+            weapon_s.color = Color::from(GRAY);
+            continue;
+        }
+
+        weapon.cool_down(&time, &game_settings);
+
         let mut targets = HashSet::new();
 
-        weapon.target = weapon.acquire_target(&weapon_t, &enemy_q, &fow_q, &targets);
+        // Special case => the sniper sees partially through the fog with the spotlight on
+        let vision = if weapon.name == WeaponName::Sniper && player.spotlight.power > 0 {
+            SNIPER_FOG_VISION
+        } else {
+            0.
+        };
+
+        if weapon.fire_strategy == FireStrategy::Sticky {
+            weapon.track_range(&weapon_t, &enemy_q);
+        }
+
+        weapon.target = weapon.acquire_target(&weapon_t, &enemy_q, &fow_q, &targets, vision);
         if let Some(enemy_e) = weapon.target {
             let (_, enemy_t, enemy) = enemy_q.get(enemy_e).unwrap();
 
@@ -183,9 +382,10 @@ pub fn spawn_bullets(
                 {
                     get_future_position(
                         enemy_t.translation,
-                        enemy.speed,
-                        weapon_t.translation,
-                        weapon.bullet.speed,
+                        enemy.current_speed(),
+                        weapon
+                            .bullet
+                            .flight_time(weapon_t.translation, enemy_t.translation),
                         fence_q.get_single(),
                         wall_q.get_single(),
                     )
@@ -195,14 +395,26 @@ pub fn spawn_bullets(
 
             let angle = d.y.atan2(d.x);
 
+            // The sticky target is kept while it's out of range or behind the
+            // fog, but the weapon holds its fire until the bullets can reach it
+            let in_reach = weapon.fire_strategy != FireStrategy::Sticky
+                || weapon.in_reach(
+                    &weapon_t,
+                    enemy_t,
+                    enemy,
+                    fow_q.get_single().unwrap(),
+                    vision,
+                );
+
             // Check if the player has enough resources to fire
-            if player.resources >= weapon.bullet.price && weapon.n_bullets > 0 {
+            let price = player.cost(&weapon.bullet.price);
+            if player.resources >= price && weapon.n_bullets > 0 {
                 // Check if the weapon points towards the first target
                 if weapon.is_aiming(&angle, &weapon_t) {
                     // Check if the weapon can fire (fire timer is finished)
-                    if weapon.can_fire(&time, &game_settings) {
+                    if in_reach && weapon.can_fire(&time, &game_settings) {
                         // Spawn fire animation
-                        let atlas = assets.get_atlas(&weapon.fire_animation.atlas);
+                        let atlas = assets.get_atlas(weapon.fire_animation.atlas);
                         commands.spawn((
                             Sprite {
                                 image: atlas.image,
@@ -223,7 +435,6 @@ pub fn spawn_bullets(
                                 ),
                                 rotation: Quat::from_rotation_z(angle),
                                 scale: weapon.fire_animation.scale,
-                                ..default()
                             },
                             AnimationComponent {
                                 timer: Timer::from_seconds(
@@ -236,11 +447,11 @@ pub fn spawn_bullets(
                         ));
 
                         for i in 0..weapon.n_bullets {
-                            if player.resources >= weapon.bullet.price {
+                            if player.resources >= price {
                                 let mut bullet = weapon.bullet.clone();
 
-                                night_stats.resources += &bullet.price;
-                                player.resources -= &bullet.price;
+                                night_stats.resources += &price;
+                                player.resources -= &price;
 
                                 // Special case => turret only fires with ui button
                                 if weapon.name == WeaponName::Turret {
@@ -254,16 +465,24 @@ pub fn spawn_bullets(
                                                 .0
                                                 as f32;
 
-                                    // The damage increases exponentially with the firepower
-                                    bullet.impact = Impact::Piercing {
-                                        damage: Damage {
-                                            ground: power.powf(player.weapons.settings.turret),
-                                            air: power.powf(player.weapons.settings.turret),
-                                            penetration: power.powf(player.weapons.settings.turret),
-                                        },
-                                        hits: HashSet::new(),
-                                    };
-                                    player.weapons.settings.turret = 0.;
+                                    if let Some(spot) = player
+                                        .weapons
+                                        .spots
+                                        .iter_mut()
+                                        .find(|s| s.id == weapon.spot)
+                                    {
+                                        // The damage increases exponentially with the firepower
+                                        bullet.impact = Impact::Piercing {
+                                            damage: Damage {
+                                                ground: power.powf(spot.settings.turret),
+                                                air: power.powf(spot.settings.turret),
+                                                penetration: power.powf(spot.settings.turret),
+                                                ..default()
+                                            },
+                                            hits: HashSet::new(),
+                                        };
+                                        spot.settings.turret = 0.;
+                                    }
                                     weapon.fire_strategy = FireStrategy::None;
                                 }
 
@@ -271,7 +490,9 @@ pub fn spawn_bullets(
                                 let (enemy_e, enemy_t, enemy) = match i {
                                     i if i > 0 => {
                                         let enemy_e = weapon
-                                            .acquire_target(&weapon_t, &enemy_q, &fow_q, &targets)
+                                            .acquire_target(
+                                                &weapon_t, &enemy_q, &fow_q, &targets, vision,
+                                            )
                                             .unwrap_or(enemy_e);
                                         enemy_q.get(enemy_e).unwrap()
                                     }
@@ -280,23 +501,47 @@ pub fn spawn_bullets(
 
                                 targets.insert(enemy_e);
 
+                                let start = Vec3::new(
+                                    weapon_t.translation.x + weapon.dim.x * 0.5 * angle.cos(),
+                                    weapon_t.translation.y + weapon.dim.y * 0.5 * angle.sin(),
+                                    3.0,
+                                );
+
+                                // Position of the enemy when the bullet arrives
+                                let target = || {
+                                    if player.has_tech(TechnologyName::Aimbot) {
+                                        get_future_position(
+                                            enemy_t.translation,
+                                            enemy.current_speed(),
+                                            weapon.bullet.flight_time(start, enemy_t.translation),
+                                            fence_q.get_single(),
+                                            wall_q.get_single(),
+                                        )
+                                    } else {
+                                        enemy_t.translation
+                                    }
+                                };
+
+                                // Veteran weapons deal more damage, and get credited with the kills
+                                let bonus =
+                                    weapon.rank_bonus() * player.modifier(Stat::WeaponDamage);
+                                if let Some(damage) = bullet.impact.damage_mut() {
+                                    damage.ground *= bonus;
+                                    damage.air *= bonus;
+                                    damage.source = Some(weapon.spot);
+                                }
+
                                 // Determine the bullet's movement
                                 match bullet.movement {
                                     Movement::Location(_) => {
-                                        bullet.movement = Movement::Location(
-                                            if player.has_tech(TechnologyName::Aimbot) {
-                                                get_future_position(
-                                                    enemy_t.translation,
-                                                    enemy.speed,
-                                                    weapon_t.translation,
-                                                    weapon.bullet.speed,
-                                                    fence_q.get_single(),
-                                                    wall_q.get_single(),
-                                                )
-                                            } else {
-                                                enemy_t.translation
-                                            },
-                                        );
+                                        bullet.movement = Movement::Location(target());
+                                    }
+                                    Movement::Ballistic(_) => {
+                                        bullet.movement = Movement::Ballistic(Ballistic::new(
+                                            start,
+                                            target().with_z(start.z),
+                                            bullet.speed,
+                                        ));
                                     }
                                     Movement::Homing(_) => {
                                         bullet.movement = Movement::Homing(enemy_e)
@@ -307,36 +552,58 @@ pub fn spawn_bullets(
                                     _ => (),
                                 }
 
-                                commands.spawn((
-                                    Sprite {
-                                        image: asset_server.load(bullet.image),
-                                        custom_size: Some(bullet.dim),
-                                        ..default()
-                                    },
-                                    Transform {
-                                        translation: Vec3::new(
-                                            weapon_t.translation.x
-                                                + weapon.dim.x * 0.5 * angle.cos(),
-                                            weapon_t.translation.y
-                                                + weapon.dim.y * 0.5 * angle.sin(),
-                                            3.0,
-                                        ),
-                                        rotation: Quat::from_rotation_z(angle),
-                                        ..default()
-                                    },
-                                    bullet,
-                                ));
+                                // Arcs start at the weapon
+                                if let Impact::Chain(chain) = &mut bullet.impact {
+                                    chain.origin = weapon_t.translation;
+                                }
+
+                                let ballistic = matches!(bullet.movement, Movement::Ballistic(_));
+                                let dim = bullet.dim;
+
+                                let bullet_e = commands
+                                    .spawn((
+                                        Sprite {
+                                            image: asset_server.load(bullet.image),
+                                            custom_size: Some(bullet.dim),
+                                            ..default()
+                                        },
+                                        Transform {
+                                            translation: start,
+                                            rotation: Quat::from_rotation_z(angle),
+                                            ..default()
+                                        },
+                                        bullet,
+                                    ))
+                                    .id();
+
+                                // Ballistic shells cast a shadow on the ground below them
+                                if ballistic {
+                                    commands.spawn((
+                                        Sprite {
+                                            image: asset_server.load("weapon/shadow.png"),
+                                            custom_size: Some(Vec2::new(dim.x, dim.x * 0.5)),
+                                            ..default()
+                                        },
+                                        Transform::from_translation(start.with_z(BULLET_Z)),
+                                        Shadow(bullet_e),
+                                    ));
+                                }
                             }
 
-                            // Reset target lock
-                            weapon.target = None;
+                            // Reset target lock, unless the weapon sticks to its target
+                            if weapon.fire_strategy != FireStrategy::Sticky {
+                                weapon.target = None;
+                            }
                         }
                     }
                 } else {
                     // Not pointing at target -> rotate towards it
                     weapon_t.rotation = weapon_t.rotation.rotate_towards(
                         Quat::from_rotation_z(angle - PI * 0.5),
-                        weapon.rotation_speed * game_settings.speed * time.delta_secs(),
+                        weapon.rotation_speed
+                            * weapon.rank_bonus()
+                            * game_settings.speed
+                            * time.delta_secs(),
                     );
                 }
 
@@ -353,20 +620,139 @@ pub fn spawn_bullets(
     }
 }
 
+/// Keep the health, magazine and heat bars level under the (rotating) weapons
+pub fn update_weapon_bars(
+    weapon_q: Query<WeaponQ, (Without<WeaponBar>, Without<WeaponBarFill>)>,
+    children_q: Query<&Children>,
+    mut bar_q: Query<WeaponBarQ, (Without<Weapon>, Without<WeaponBarFill>)>,
+    mut fill_q: Query<(&mut Transform, &mut Sprite), With<WeaponBarFill>>,
+) {
+    for (weapon_e, weapon_t, weapon) in weapon_q.iter() {
+        let Ok(children) = children_q.get(weapon_e) else {
+            continue;
+        };
+
+        let rotation = weapon_t.rotation.inverse();
+
+        for (i, &bar_e) in children.iter().enumerate() {
+            let Ok((mut bar_t, bar)) = bar_q.get_mut(bar_e) else {
+                continue;
+            };
+
+            // Stack the bars below the weapon
+            let offset = Vec3::new(0., -weapon.dim.y * (0.55 + 0.1 * i as f32), 0.1);
+            bar_t.translation = rotation * offset;
+            bar_t.rotation = rotation;
+
+            let (fraction, color) = match bar {
+                WeaponBar::Health => (weapon.health / weapon.max_health, bar.color()),
+                WeaponBar::Magazine => {
+                    let magazine = weapon.magazine.as_ref().unwrap();
+                    let color = if magazine.is_reloading() {
+                        Color::from(GRAY)
+                    } else {
+                        bar.color()
+                    };
+                    (magazine.fraction(), color)
+                }
+                WeaponBar::Heat => {
+                    let overheat = weapon.overheat.as_ref().unwrap();
+                    let color = if overheat.overheated {
+                        Color::from(RED)
+                    } else {
+                        bar.color()
+                    };
+                    (overheat.fraction(), color)
+                }
+            };
+
+            for fill_e in children_q.iter_descendants(bar_e) {
+                if let Ok((mut fill_t, mut fill)) = fill_q.get_mut(fill_e) {
+                    fill.color = color;
+                    if let Some(size) = fill.custom_size.as_mut() {
+                        let full_size = weapon.dim.x * 0.8 - 2.;
+                        size.x = full_size * fraction;
+                        fill_t.translation.x = (size.x - full_size) * 0.5;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn move_bullets(
     mut commands: Commands,
     mut bullet_q: Query<(Entity, &mut Transform, &mut Bullet)>,
     mut enemy_q: Query<(Entity, &Transform, &mut Enemy), Without<Bullet>>,
+    mut shadow_q: Query<ShadowQ, (Without<Bullet>, Without<Enemy>)>,
+    mut night_stats: ResMut<NightStats>,
     mut player: ResMut<Player>,
     game_settings: Res<GameSettings>,
     time: Res<Time>,
     assets: Local<WorldAssets>,
 ) {
     for (bullet_e, mut bullet_t, mut bullet) in bullet_q.iter_mut() {
+        // Ballistic shells follow their arc and ignore collisions until they land
+        if let Movement::Ballistic(b) = &mut bullet.movement {
+            b.elapsed += game_settings.speed * time.delta_secs();
+
+            let altitude = b.altitude();
+            let pos = b.ground() + Vec3::Y * altitude * b.height;
+            let landed = b.progress() >= 1.;
+
+            let d = pos - bullet_t.translation;
+            if d.length() > 0. {
+                bullet_t.rotation = Quat::from_rotation_z(d.y.atan2(d.x));
+            }
+            bullet_t.translation = pos;
+            bullet_t.scale = Vec3::splat(1. + BALLISTIC_APEX_SCALE * altitude);
+
+            if landed {
+                if matches!(bullet.impact, Impact::Explosion(_)) {
+                    bullet.impact.resolve(
+                        &mut commands,
+                        bullet_e,
+                        &bullet_t,
+                        None,
+                        &mut night_stats,
+                        &assets,
+                    );
+                } else {
+                    // The shell can only hit a grounded enemy at the landing position
+                    if let Some((enemy_e, _, mut enemy)) =
+                        enemy_q.iter_mut().find(|(_, enemy_t, enemy)| {
+                            !enemy.flies
+                                && collision(
+                                    &bullet_t.translation,
+                                    &bullet.dim,
+                                    &enemy_t.translation,
+                                    &enemy.dim,
+                                )
+                        })
+                    {
+                        bullet.impact.resolve(
+                            &mut commands,
+                            bullet_e,
+                            &bullet_t,
+                            Some((enemy_e, &mut enemy)),
+                            &mut night_stats,
+                            &assets,
+                        );
+                    }
+
+                    commands.entity(bullet_e).try_despawn();
+                }
+            }
+
+            continue;
+        }
+
         // Calculate the new target's position wrt the bullet
         let d = match bullet.movement {
             Movement::Straight => None,
             Movement::Location(v) => Some(v - bullet_t.translation),
+            Movement::Ballistic(_) => unreachable!(),
             Movement::Homing(enemy_e) | Movement::PiercingHoming(enemy_e) => {
                 if let Ok((_, enemy_t, _)) = enemy_q.get(enemy_e) {
                     Some(enemy_t.translation - bullet_t.translation)
@@ -407,7 +793,7 @@ pub fn move_bullets(
                     ) {
                         // Special case: mines are only triggered by a certain size
                         // (only mines have 0 speed)
-                        if bullet.speed == 0. && enemy.size < player.weapons.settings.mine {
+                        if bullet.speed == 0. && enemy.size < player.weapons.explosives.mine {
                             continue;
                         }
 
@@ -416,22 +802,36 @@ pub fn move_bullets(
                             bullet_e,
                             &bullet_t,
                             Some((enemy_e, &mut enemy)),
+                            &mut night_stats,
                             &assets,
                         );
 
-                        // Update mine counts
+                        // Update mine counts. A mine only explodes once, even
+                        // if multiple enemies step on it in the same frame
                         if impacted && bullet.speed == 0. {
+                            let pos = bullet_t.translation.truncate();
                             player.weapons.mines -= 1;
+                            player
+                                .weapons
+                                .mine_positions
+                                .retain(|&(x, y)| Vec2::new(x, y).distance(pos) > 1.);
+                            break;
                         }
                     }
                 }
             }
+            Movement::Ballistic(_) => unreachable!(),
             Movement::Location(v) => {
                 // Accept a 0.5% error margin
                 if bullet_t.translation.distance(v) <= MAP_SIZE.y * 0.005 {
-                    bullet
-                        .impact
-                        .resolve(&mut commands, bullet_e, &bullet_t, None, &assets);
+                    bullet.impact.resolve(
+                        &mut commands,
+                        bullet_e,
+                        &bullet_t,
+                        None,
+                        &mut night_stats,
+                        &assets,
+                    );
                 }
             }
             Movement::Homing(enemy_e) => {
@@ -448,6 +848,7 @@ pub fn move_bullets(
                         bullet_e,
                         &bullet_t,
                         Some((enemy_e, &mut enemy)),
+                        &mut night_stats,
                         &assets,
                     );
                 }
@@ -467,6 +868,7 @@ pub fn move_bullets(
                             bullet_e,
                             &bullet_t,
                             Some((enemy_e, &mut enemy)),
+                            &mut night_stats,
                             &assets,
                         );
 
@@ -489,4 +891,663 @@ pub fn move_bullets(
             commands.entity(bullet_e).try_despawn();
         }
     }
+
+    // Keep the shadows below their shells
+    for (shadow_e, mut shadow_t, shadow) in shadow_q.iter_mut() {
+        if let Ok((_, _, bullet)) = bullet_q.get(shadow.0) {
+            if let Movement::Ballistic(b) = &bullet.movement {
+                shadow_t.translation = b.ground().with_z(BULLET_Z);
+                shadow_t.scale = Vec3::splat(1. - 0.5 * b.altitude());
+            }
+        } else {
+            commands.entity(shadow_e).try_despawn();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn move_drones(
+    mut commands: Commands,
+    mut drone_q: Query<(Entity, &mut Transform, &mut Drone)>,
+    enemy_q: Query<EnemyQ, (With<Enemy>, Without<Drone>)>,
+    fow_q: Query<&Transform, (With<FogOfWar>, Without<Drone>)>,
+    mut night_stats: ResMut<NightStats>,
+    mut player: ResMut<Player>,
+    mut messages: ResMut<Messages>,
+    game_settings: Res<GameSettings>,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+) {
+    let dt = game_settings.speed * time.delta_secs();
+    let fow_t = fow_q.get_single().unwrap();
+
+    // Vertical limits of the patrolled band
+    let center = -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + MAP_SIZE.y * player.weapons.drone_patrol;
+    let band = center - MAP_SIZE.y * DRONE_PATROL_BAND * 0.5
+        ..=center + MAP_SIZE.y * DRONE_PATROL_BAND * 0.5;
+
+    for (drone_e, mut drone_t, mut drone) in drone_q.iter_mut() {
+        let pos = drone_t.translation;
+
+        // Flying bugs shoot at the drones around them
+        let range = drone.range;
+        drone.health -= enemy_q
+            .iter()
+            .filter(|(_, enemy_t, enemy)| enemy.flies && enemy_t.translation.distance(pos) <= range)
+            .map(|(_, _, enemy)| enemy.damage * dt)
+            .sum::<f32>();
+
+        if drone.health <= 0. {
+            messages.error("A drone was shot down");
+            commands.entity(drone_e).despawn();
+            player.weapons.drones = player.weapons.drones.saturating_sub(1);
+            continue;
+        }
+
+        drone
+            .fire_timer
+            .tick(scale_duration(time.delta(), game_settings.speed));
+
+        if drone.status == DroneStatus::Patrolling && drone.ammo == 0 {
+            drone.status = DroneStatus::Rearming;
+        }
+
+        let (destination, facing) = match drone.status {
+            DroneStatus::Patrolling => {
+                let in_band = |(_, enemy_t, enemy): &EnemyQ| {
+                    is_visible(fow_t, enemy_t, enemy) && band.contains(&enemy_t.translation.y)
+                };
+
+                // Keep engaging the target while it's in the band, else take the closest
+                drone.target = drone
+                    .target
+                    .filter(|enemy_e| enemy_q.get(*enemy_e).is_ok_and(|e| in_band(&e)))
+                    .or_else(|| {
+                        enemy_q
+                            .iter()
+                            .filter(in_band)
+                            .min_by(|(_, t1, _), (_, t2, _)| {
+                                let d1 = t1.translation.distance(pos);
+                                let d2 = t2.translation.distance(pos);
+                                d1.partial_cmp(&d2).unwrap()
+                            })
+                            .map(|(enemy_e, _, _)| enemy_e)
+                    });
+
+                if let Some((enemy_e, enemy_t, _)) = drone.target.and_then(|e| enemy_q.get(e).ok())
+                {
+                    let d = enemy_t.translation - pos;
+                    let angle = d.y.atan2(d.x);
+
+                    if d.length() <= drone.range && drone.fire_timer.finished() {
+                        drone.fire_timer.reset();
+                        drone.ammo -= 1;
+
+                        let mut bullet = drone.bullet.clone();
+                        bullet.movement = Movement::Homing(enemy_e);
+
+                        commands.spawn((
+                            Sprite {
+                                image: asset_server.load(bullet.image),
+                                custom_size: Some(bullet.dim),
+                                ..default()
+                            },
+                            Transform {
+                                translation: Vec3::new(pos.x, pos.y, BULLET_Z),
+                                rotation: Quat::from_rotation_z(angle),
+                                ..default()
+                            },
+                            bullet,
+                        ));
+                    }
+
+                    // Hover at half the range from the enemy
+                    (
+                        enemy_t.translation - d.normalize_or_zero() * drone.range * 0.5,
+                        enemy_t.translation,
+                    )
+                } else {
+                    // Sweep the band from side to side
+                    let min_x = -SIZE.x * 0.5 + drone.dim.x;
+                    let max_x = SIZE.x * 0.5 - WEAPONS_PANEL_SIZE.x - drone.dim.x;
+                    if pos.x <= min_x {
+                        drone.direction = 1.;
+                    } else if pos.x >= max_x {
+                        drone.direction = -1.;
+                    }
+
+                    let x = if drone.direction > 0. { max_x } else { min_x };
+                    let destination = Vec3::new(x, center, pos.z);
+                    (destination, destination)
+                }
+            }
+            DroneStatus::Rearming | DroneStatus::Recalled => {
+                drone.target = None;
+
+                // Refill as many rounds as the resources cover once back at the bay
+                if pos.distance(drone.home) < 1. {
+                    let price = player.cost(&drone.price);
+                    while drone.ammo < drone.max_ammo && player.resources >= price {
+                        night_stats.resources += &price;
+                        player.resources -= &price;
+                        drone.ammo += 1;
+                    }
+                }
+
+                if drone.status == DroneStatus::Rearming && drone.ammo == drone.max_ammo {
+                    drone.status = DroneStatus::Patrolling;
+                }
+
+                (drone.home, drone.home + Vec3::Y)
+            }
+        };
+
+        // Fly towards the destination, facing the target
+        let step = drone.speed * dt;
+        drone_t.translation += (destination - pos).clamp_length_max(step);
+
+        let d = facing - pos;
+        if d.length() > 1. {
+            drone_t.rotation = drone_t
+                .rotation
+                .rotate_towards(Quat::from_rotation_z(d.y.atan2(d.x) - PI * 0.5), 5. * dt);
+        }
+    }
+}
+
+/// Deploy a squad of soldiers in front of the wall, spread over the map's width
+pub fn spawn_sortie(
+    commands: &mut Commands,
+    n: u32,
+    player: &Player,
+    weapons: &WeaponManager,
+    asset_server: &AssetServer,
+) {
+    let mut soldier = weapons.soldier.clone();
+    soldier.damage.ground *= player.modifier(Stat::SoldierDamage);
+    soldier.damage.air *= player.modifier(Stat::SoldierDamage);
+
+    for i in 0..n {
+        let post = -SIZE.x * 0.5 + (i + 1) as f32 * MAP_SIZE.x / (n + 1) as f32;
+        let home = Vec3::new(
+            post,
+            -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + WALL_SIZE.y * 0.9,
+            WEAPON_Z,
+        );
+
+        commands.spawn((
+            Sprite {
+                image: asset_server.load(soldier.image),
+                custom_size: Some(soldier.dim),
+                ..default()
+            },
+            Transform::from_translation(home),
+            Soldier {
+                post,
+                home,
+                ..soldier.clone()
+            },
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn move_soldiers(
+    mut commands: Commands,
+    mut soldier_q: Query<(Entity, &mut Transform, &mut Soldier)>,
+    mut enemy_q: Query<(Entity, &Transform, &mut Enemy), Without<Soldier>>,
+    fow_q: Query<&Transform, (With<FogOfWar>, Without<Soldier>)>,
+    mut night_stats: ResMut<NightStats>,
+    mut player: ResMut<Player>,
+    mut messages: ResMut<Messages>,
+    game_settings: Res<GameSettings>,
+    time: Res<Time>,
+) {
+    let dt = game_settings.speed * time.delta_secs();
+    let fow_t = fow_q.get_single().unwrap();
+
+    let line = -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + MAP_SIZE.y * player.weapons.sortie_line;
+
+    for (soldier_e, mut soldier_t, mut soldier) in soldier_q.iter_mut() {
+        let pos = soldier_t.translation;
+
+        // Ground bugs attack the soldiers they run into
+        soldier.health -= enemy_q
+            .iter()
+            .filter(|(_, enemy_t, enemy)| {
+                !enemy.flies && collision(&enemy_t.translation, &enemy.dim, &pos, &soldier.dim)
+            })
+            .map(|(_, _, enemy)| enemy.damage * dt)
+            .sum::<f32>();
+
+        if soldier.health <= 0. {
+            messages.error("A soldier was killed");
+            commands.entity(soldier_e).despawn();
+            player.population.soldier = player.population.soldier.saturating_sub(1);
+            night_stats.population.soldier += 1;
+            continue;
+        }
+
+        soldier
+            .fire_timer
+            .tick(scale_duration(time.delta(), game_settings.speed));
+
+        let (destination, facing) = match soldier.status {
+            SortieStatus::Fighting => {
+                let target = enemy_q
+                    .iter()
+                    .filter(|(_, enemy_t, enemy)| {
+                        enemy.health > 0.
+                            && is_visible(fow_t, enemy_t, enemy)
+                            && enemy_t.translation.distance(pos) <= soldier.range
+                    })
+                    .min_by(|(_, t1, _), (_, t2, _)| {
+                        let d1 = t1.translation.distance(pos);
+                        let d2 = t2.translation.distance(pos);
+                        d1.partial_cmp(&d2).unwrap()
+                    })
+                    .map(|(enemy_e, enemy_t, _)| (enemy_e, enemy_t.translation));
+
+                let post = Vec3::new(soldier.post, line, pos.z);
+                if let Some((enemy_e, enemy_pos)) = target {
+                    if soldier.fire_timer.finished() {
+                        soldier.fire_timer.reset();
+
+                        let (_, _, mut enemy) = enemy_q.get_mut(enemy_e).unwrap();
+                        enemy.health -= soldier.damage.calculate(&enemy).min(enemy.health);
+
+                        // Soldiers aren't on a spot, so no weapon is credited with the kill
+                        enemy.last_hit = None;
+                    }
+
+                    (post, enemy_pos)
+                } else {
+                    (post, post + Vec3::Y)
+                }
+            }
+            SortieStatus::Retreating => {
+                // Back inside the fortress
+                if pos.distance(soldier.home) < 1. {
+                    commands.entity(soldier_e).despawn();
+                    continue;
+                }
+
+                (soldier.home, soldier.home)
+            }
+        };
+
+        // Walk towards the destination, facing the target
+        let step = soldier.speed * dt;
+        soldier_t.translation += (destination - pos).clamp_length_max(step);
+
+        let d = facing - pos;
+        if d.length() > 1. {
+            soldier_t.rotation = soldier_t
+                .rotation
+                .rotate_towards(Quat::from_rotation_z(d.y.atan2(d.x) - PI * 0.5), 5. * dt);
+        }
+    }
+}
+
+/// Jump the lightning arcs from enemy to enemy, and remove them once they faded
+pub fn run_lightning(
+    mut commands: Commands,
+    mut lightning_q: Query<(Entity, &mut Lightning)>,
+    mut enemy_q: Query<(Entity, &Transform, &mut Enemy)>,
+    game_settings: Res<GameSettings>,
+    time: Res<Time>,
+) {
+    for (lightning_e, mut lightning) in lightning_q.iter_mut() {
+        // All jumps are resolved at once, since the arc is instantaneous
+        while lightning.jumps > 0 {
+            let start = lightning.arcs.last().unwrap().1;
+            let chain = lightning.chain.clone();
+
+            // Jump to the closest enemy that wasn't hit yet
+            let next = enemy_q
+                .iter()
+                .filter(|(enemy_e, enemy_t, enemy)| {
+                    !lightning.hits.contains(enemy_e)
+                        && enemy.health > 0.
+                        && ((chain.damage.ground > 0. && !enemy.flies)
+                            || (chain.damage.air > 0. && enemy.flies))
+                        && enemy_t.translation.truncate().distance(start) <= chain.range
+                })
+                .map(|(enemy_e, enemy_t, _)| (enemy_e, enemy_t.translation.truncate()))
+                .min_by(|(_, p1), (_, p2)| {
+                    p1.distance(start).partial_cmp(&p2.distance(start)).unwrap()
+                });
+
+            let Some((enemy_e, end)) = next else {
+                lightning.jumps = 0;
+                break;
+            };
+
+            // The damage decreases with every jump
+            let falloff = chain.falloff.powi(lightning.arcs.len() as i32);
+            let damage = Damage {
+                ground: chain.damage.ground * falloff,
+                air: chain.damage.air * falloff,
+                penetration: chain.damage.penetration,
+                source: chain.damage.source,
+                ..default()
+            };
+
+            let (_, _, mut enemy) = enemy_q.get_mut(enemy_e).unwrap();
+            enemy.health -= damage.calculate(&enemy).min(enemy.health);
+            enemy.last_hit = damage.source;
+
+            lightning.hits.insert(enemy_e);
+            lightning.arcs.push((start, end));
+            lightning.jumps -= 1;
+        }
+
+        lightning
+            .timer
+            .tick(scale_duration(time.delta(), game_settings.speed));
+
+        if lightning.timer.finished() {
+            commands.entity(lightning_e).despawn();
+        }
+    }
+}
+
+/// Draw the arcs of the lightning as jagged lines
+pub fn draw_lightning(mut gizmos: Gizmos, lightning_q: Query<&Lightning>) {
+    for lightning in lightning_q.iter() {
+        for (start, end) in lightning.arcs.iter() {
+            // Jagged line with random offsets perpendicular to the arc
+            let normal = (*end - *start).perp().normalize_or_zero();
+            let points = (0..=LIGHTNING_SEGMENTS).map(|i| {
+                let offset = if i == 0 || i == LIGHTNING_SEGMENTS {
+                    0.
+                } else {
+                    thread_rng().gen_range(-6.0..=6.)
+                };
+
+                start.lerp(*end, i as f32 / LIGHTNING_SEGMENTS as f32) + normal * offset
+            });
+
+            gizmos.linestrip_2d(points, AQUA);
+        }
+    }
+}
+
+/// Fire the weapon in targeting mode at the location clicked on the map
+#[allow(clippy::too_many_arguments)]
+pub fn target_map(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    mut weapon_q: Query<&mut Weapon>,
+    enemy_q: Query<EnemyQ, With<Enemy>>,
+    mine_q: Query<Entity, With<Mine>>,
+    fow_q: Query<&Transform, With<FogOfWar>>,
+    mut contexts: EguiContexts,
+    mut player: ResMut<Player>,
+    mut game_settings: ResMut<GameSettings>,
+    mut messages: ResMut<Messages>,
+    game_state: Res<State<GameState>>,
+    weapons: Res<WeaponManager>,
+    asset_server: Res<AssetServer>,
+) {
+    // The event bubbles up through all parents, only handle it once
+    if trigger.entity() != trigger.event().target || contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+
+    let Some(targeting) = game_settings.targeting else {
+        return;
+    };
+
+    if trigger.event().event.button == PointerButton::Secondary {
+        game_settings.targeting = None;
+        return;
+    }
+
+    let Some(pos) = cursor_to_map(&camera_q, trigger.event().pointer_location.position) else {
+        return;
+    };
+
+    if *game_state.get() != GameState::Running {
+        return;
+    }
+
+    match targeting {
+        Targeting::Bomb if player.weapons.bombs > 0 => {
+            spawn_airstrike(&mut commands, weapons.bomb.clone(), pos, &asset_server);
+            player.weapons.bombs -= 1;
+        }
+        Targeting::Nuke if player.weapons.nuke > 0 => {
+            spawn_airstrike(&mut commands, weapons.nuke.clone(), pos, &asset_server);
+            player.weapons.nuke -= 1;
+            messages.info("A nuke is launched");
+        }
+        Targeting::Turret => {
+            let fow_t = fow_q.get_single().unwrap();
+            let target = get_closest_enemy(
+                pos,
+                enemy_q.iter().filter(|(_, t, e)| is_visible(fow_t, t, e)),
+            );
+
+            if let Some((enemy_e, _)) = target {
+                if let Some(mut turret) = weapon_q.iter_mut().find(|w| w.name == WeaponName::Turret)
+                {
+                    turret.target = Some(enemy_e);
+                    turret.fire_strategy = FireStrategy::Strongest;
+                }
+            } else {
+                messages.error("There are no visible enemies to fire at.");
+                return;
+            }
+        }
+        Targeting::Mine => {
+            let size = weapons.mine.dim;
+
+            // Click on a placed mine to pick it up, else place a new one
+            if let Some(i) = player
+                .weapons
+                .mine_positions
+                .iter()
+                .position(|&(x, y)| Vec2::new(x, y).distance(pos) <= size.length() * 0.5)
+            {
+                player.weapons.mine_positions.remove(i);
+            } else if player.weapons.mine_positions.len() >= player.weapons.mines as usize {
+                messages.error("All mines are already placed.");
+            } else if !mine_area(size).contains(pos) {
+                messages.error("Mines can't be placed at this location.");
+            } else if !is_valid_mine_position(pos, &player.weapons.mine_positions, size) {
+                messages.error("Mines can't be placed this close to each other.");
+            } else {
+                player.weapons.mine_positions.push((pos.x, pos.y));
+            }
+
+            spawn_mines(&mut commands, &mine_q, &player, &weapons, &asset_server);
+
+            // Keep placing mines until the player is done
+            return;
+        }
+        Targeting::Weapons => return, // Weapons are placed by dragging them
+        _ => (),
+    }
+
+    game_settings.targeting = None;
+}
+
+/// Move a weapon along with the pointer, snapping it to the closest mount
+pub fn drag_weapon(
+    trigger: Trigger<Pointer<Drag>>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    mut weapon_q: Query<(Entity, &mut Transform, &mut Sprite, &mut Weapon)>,
+    player: Res<Player>,
+    game_settings: Res<GameSettings>,
+) {
+    if game_settings.targeting != Some(Targeting::Weapons) {
+        return;
+    }
+
+    let Ok((camera, camera_t)) = camera_q.get_single() else {
+        return;
+    };
+
+    let Ok(pos) = camera.viewport_to_world_2d(camera_t, trigger.event().pointer_location.position)
+    else {
+        return;
+    };
+
+    let Ok((.., weapon)) = weapon_q.get(trigger.entity()) else {
+        return;
+    };
+
+    let x = pos.x.clamp(
+        -SIZE.x * 0.5 + weapon.dim.x * 0.5,
+        -SIZE.x * 0.5 + MAP_SIZE.x - weapon.dim.x * 0.5,
+    );
+    let mount = closest_mount(pos.y, &player);
+
+    let valid = is_valid_weapon_position(
+        x,
+        weapon.dim.x,
+        mount,
+        weapon_q
+            .iter()
+            .filter(|(e, ..)| *e != trigger.entity())
+            .map(|(_, t, _, w)| (t, w)),
+    );
+
+    let (_, mut weapon_t, mut weapon_s, mut weapon) = weapon_q.get_mut(trigger.entity()).unwrap();
+    weapon_t.translation.x = x;
+    weapon_t.translation.y = mount_y(mount);
+    weapon_s.color = if valid {
+        Color::WHITE
+    } else {
+        Color::from(RED)
+    };
+    weapon.mount = mount;
+}
+
+/// Store the position of the dropped weapon on its spot, unless it overlaps
+#[allow(clippy::too_many_arguments)]
+pub fn drop_weapon(
+    trigger: Trigger<Pointer<DragEnd>>,
+    mut commands: Commands,
+    weapon_q: Query<(Entity, &Transform, &Weapon)>,
+    spot_q: Query<Entity, With<Weapon>>,
+    mut player: ResMut<Player>,
+    mut messages: ResMut<Messages>,
+    game_settings: Res<GameSettings>,
+    weapons: Res<WeaponManager>,
+    asset_server: Res<AssetServer>,
+) {
+    if game_settings.targeting != Some(Targeting::Weapons) {
+        return;
+    }
+
+    let Ok((weapon_e, weapon_t, weapon)) = weapon_q.get(trigger.entity()) else {
+        return;
+    };
+
+    if is_valid_weapon_position(
+        weapon_t.translation.x,
+        weapon.dim.x,
+        weapon.mount,
+        weapon_q
+            .iter()
+            .filter(|(e, ..)| *e != weapon_e)
+            .map(|(_, t, w)| (t, w)),
+    ) {
+        // Fix the positions of all weapons, so the others don't move
+        for (_, t, w) in weapon_q.iter() {
+            if let Some(spot) = player.weapons.spots.iter_mut().find(|s| s.id == w.spot) {
+                spot.x = Some(t.translation.x + SIZE.x * 0.5);
+                spot.mount = w.mount;
+            }
+        }
+    } else {
+        messages.error("Weapons can't overlap.");
+    }
+
+    spawn_spots(&mut commands, &spot_q, &player, &weapons, &asset_server);
+}
+
+/// Show where the weapon in targeting mode is going to hit
+#[allow(clippy::too_many_arguments)]
+pub fn draw_targeting(
+    mut gizmos: Gizmos,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    weapon_q: Query<(&Transform, &Weapon)>,
+    enemy_q: Query<EnemyQ, With<Enemy>>,
+    fow_q: Query<&Transform, With<FogOfWar>>,
+    mut contexts: EguiContexts,
+    player: Res<Player>,
+    game_settings: Res<GameSettings>,
+    weapons: Res<WeaponManager>,
+) {
+    let Some(targeting) = game_settings.targeting else {
+        return;
+    };
+
+    if contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+
+    let Some(pos) = window_q
+        .get_single()
+        .ok()
+        .and_then(|w| w.cursor_position())
+        .and_then(|c| cursor_to_map(&camera_q, c))
+    else {
+        return;
+    };
+
+    match targeting {
+        Targeting::Bomb | Targeting::Nuke => {
+            let bullet = if targeting == Targeting::Bomb {
+                &weapons.bomb
+            } else {
+                &weapons.nuke
+            };
+
+            if let Impact::Explosion(e) = &bullet.impact {
+                gizmos.circle_2d(Isometry2d::from_translation(pos), e.radius, RED);
+            }
+        }
+        Targeting::Turret => {
+            let fow_t = fow_q.get_single().unwrap();
+            let target = get_closest_enemy(
+                pos,
+                enemy_q.iter().filter(|(_, t, e)| is_visible(fow_t, t, e)),
+            );
+
+            if let Some(((turret_t, _), (_, enemy_t))) = weapon_q
+                .iter()
+                .find(|(_, w)| w.name == WeaponName::Turret)
+                .zip(target)
+            {
+                gizmos.line_2d(
+                    turret_t.translation.truncate(),
+                    enemy_t.translation.truncate(),
+                    RED,
+                );
+            }
+        }
+        Targeting::Weapons => (),
+        Targeting::Mine => {
+            let size = weapons.mine.dim;
+            let color = if mine_area(size).contains(pos)
+                && is_valid_mine_position(pos, &player.weapons.mine_positions, size)
+            {
+                LIME
+            } else {
+                RED
+            };
+
+            gizmos.circle_2d(
+                Isometry2d::from_translation(pos),
+                size.length() * 0.5,
+                color,
+            );
+        }
+    }
 }
@@ -1,5 +1,8 @@
-use crate::constants::{SpriteQ, STRUCTURE_OFFSET};
+use crate::constants::*;
+use crate::game::resources::Player;
+use crate::game::weapon::components::{Mount, Weapon};
 use bevy::ecs::query::QuerySingleError;
+use bevy::prelude::*;
 
 /// Get the top y coordinate of a structure (fence or wall).
 /// Returns None if the structure doesn't exist
@@ -11,3 +14,108 @@ pub fn get_structure_top(sprite_q: Result<SpriteQ, QuerySingleError>) -> Option<
         None
     }
 }
+
+/// Convert a position on the screen to a location on the map above the
+/// wall. Returns None if the position is outside that area
+pub fn cursor_to_map(
+    camera_q: &Query<(&Camera, &GlobalTransform)>,
+    position: Vec2,
+) -> Option<Vec2> {
+    let (camera, camera_t) = camera_q.get_single().ok()?;
+    let pos = camera.viewport_to_world_2d(camera_t, position).ok()?;
+
+    let x_range = -SIZE.x * 0.5..=SIZE.x * 0.5 - WEAPONS_PANEL_SIZE.x;
+    let y_range =
+        -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + WALL_SIZE.y..=SIZE.y * 0.5 - MENU_PANEL_SIZE.y;
+
+    (x_range.contains(&pos.x) && y_range.contains(&pos.y)).then_some(pos)
+}
+
+/// Get the enemy closest to a location on the map
+pub fn get_closest_enemy<'a>(
+    pos: Vec2,
+    enemies: impl Iterator<Item = EnemyQ<'a>>,
+) -> Option<(Entity, &'a Transform)> {
+    enemies
+        .map(|(enemy_e, enemy_t, _)| (enemy_e, enemy_t))
+        .min_by(|(_, t1), (_, t2)| {
+            let d1 = t1.translation.truncate().distance(pos);
+            let d2 = t2.translation.truncate().distance(pos);
+            d1.partial_cmp(&d2).unwrap()
+        })
+}
+
+/// Area of the map where mines can be placed
+pub fn mine_area(size: Vec2) -> Rect {
+    Rect::new(
+        -SIZE.x * 0.5 + size.x,
+        -SIZE.y * 0.5 + RESOURCES_PANEL_SIZE.y + 2. * WALL_SIZE.y,
+        SIZE.x * 0.5 - WEAPONS_PANEL_SIZE.x - size.x,
+        SIZE.y * 0.5 - MENU_PANEL_SIZE.y - FOW_SIZE.y - size.y,
+    )
+}
+
+/// Whether a mine can be placed at `pos` without being too close to the other mines
+pub fn is_valid_mine_position(pos: Vec2, positions: &[(f32, f32)], size: Vec2) -> bool {
+    positions
+        .iter()
+        .all(|&(x, y)| pos.distance(Vec2::new(x, y)) >= MINE_SPACING * size.length())
+}
+
+/// Height (y coordinate) of the weapons mounted on a structure
+pub fn mount_y(mount: Mount) -> f32 {
+    match mount {
+        Mount::Wall => WALL_Y,
+        Mount::Fence => FENCE_Y,
+    }
+}
+
+/// Mount closest to height `y`. Weapons can only be mounted on the fence while it stands
+pub fn closest_mount(y: f32, player: &Player) -> Mount {
+    if player.fence.health > 0.
+        && (y - mount_y(Mount::Fence)).abs() < (y - mount_y(Mount::Wall)).abs()
+    {
+        Mount::Fence
+    } else {
+        Mount::Wall
+    }
+}
+
+/// Whether a weapon of `width` can be placed at `x` on `mount`
+/// without overlapping with the other weapons on that mount
+pub fn is_valid_weapon_position<'a>(
+    x: f32,
+    width: f32,
+    mount: Mount,
+    weapons: impl Iterator<Item = (&'a Transform, &'a Weapon)>,
+) -> bool {
+    weapons
+        .filter(|(_, w)| w.mount == mount)
+        .all(|(t, w)| (t.translation.x - x).abs() >= (w.dim.x + width) * 0.5)
+}
+
+/// Closest x to `x` where a weapon of `width` fits on `mount` without
+/// overlapping with the other weapons. Returns None if there is no room left
+pub fn free_weapon_x<'a>(
+    x: f32,
+    width: f32,
+    mount: Mount,
+    weapons: impl Iterator<Item = (&'a Transform, &'a Weapon)> + Clone,
+) -> Option<f32> {
+    let x_range = -SIZE.x * 0.5 + width * 0.5..=-SIZE.x * 0.5 + MAP_SIZE.x - width * 0.5;
+
+    // The closest free position is either `x` itself or right next to another weapon
+    let candidates = weapons
+        .clone()
+        .filter(|(_, w)| w.mount == mount)
+        .flat_map(|(t, w)| {
+            let d = (w.dim.x + width) * 0.5;
+            [t.translation.x - d, t.translation.x + d]
+        })
+        .chain([x]);
+
+    candidates
+        .filter(|c| x_range.contains(c))
+        .filter(|&c| is_valid_weapon_position(c, width, mount, weapons.clone()))
+        .min_by(|c1, c2| (c1 - x).abs().total_cmp(&(c2 - x).abs()))
+}
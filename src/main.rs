@@ -1,3 +1,5 @@
+#[cfg(not(target_arch = "wasm32"))]
+mod benchmark;
 pub mod constants;
 mod game;
 mod messages;
@@ -15,6 +17,12 @@ use bevy_kira_audio::prelude::*;
 use constants::{SIZE, TITLE};
 
 fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(settings) = benchmark::BenchmarkSettings::from_args() {
+        benchmark::run(settings);
+        return;
+    }
+
     let mut app = App::new();
 
     app.add_plugins(
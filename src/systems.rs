@@ -1,5 +1,8 @@
+#[cfg(target_os = "windows")]
 use bevy::prelude::NonSend;
+#[cfg(target_os = "windows")]
 use bevy::winit::WinitWindows;
+#[cfg(target_os = "windows")]
 use winit::window::Icon;
 
 #[cfg(target_os = "windows")]
@@ -1,13 +1,16 @@
+use crate::constants::{FOOD_CONSUMPTION, MIGRATED_FOOD_DAYS};
 use crate::game::resources::{GameSettings, Player};
 use crate::game::AppState;
 use crate::messages::Messages;
 use bevy::prelude::{Commands, NextState};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::Write;
 use std::time::Duration;
 use std::{fs, io};
+use uuid::Uuid;
 
 #[cfg(not(target_arch = "wasm32"))]
 use rfd::FileDialog;
@@ -44,9 +47,72 @@ fn save_to_json(file_path: &str, data: &SaveAll) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Convert a save from an older version of the game to the current format
+/// Malformed saves are left untouched, and fail to deserialize afterwards
+fn migrate_save(data: &mut Value) {
+    let Some(player) = data.get_mut("player").and_then(Value::as_object_mut) else {
+        return;
+    };
+
+    // Weapon settings used to be global per weapon type instead of per spot
+    if let Some(weapons) = player.get_mut("weapons").and_then(Value::as_object_mut) {
+        if let Some(Value::Object(mut settings)) = weapons.remove("settings") {
+            weapons.insert(
+                "explosives".to_string(),
+                json!({
+                    "bomb": settings.remove("bomb"),
+                    "mine": settings.remove("mine"),
+                }),
+            );
+
+            if let Some(spots) = weapons.get_mut("spots").and_then(Value::as_array_mut) {
+                for spot in spots.iter_mut().filter_map(Value::as_object_mut) {
+                    spot.insert("settings".to_string(), Value::Object(settings.clone()));
+                }
+            }
+        }
+    }
+
+    // Food didn't exist, so give the population time to assign farmers before starving
+    let population: f64 = player
+        .get("population")
+        .and_then(Value::as_object)
+        .map(|p| p.values().filter_map(Value::as_f64).sum())
+        .unwrap_or(0.);
+    if let Some(resources) = player.get_mut("resources").and_then(Value::as_object_mut) {
+        if !resources.contains_key("food") {
+            let food = population * (FOOD_CONSUMPTION * MIGRATED_FOOD_DAYS) as f64;
+            resources.insert("food".to_string(), json!(food));
+        }
+    }
+
+    // Only one expedition used to be sent at a time, sized instead of with a destination
+    if let Some(Value::Object(mut expedition)) = player.remove("expedition") {
+        let destination = match expedition.get("name").and_then(Value::as_str) {
+            Some("Small") => "Ruins",
+            Some("Medium") => "FuelDepot",
+            _ => "MilitaryBase",
+        };
+
+        player.insert(
+            "expeditions".to_string(),
+            json!([{
+                "id": Uuid::new_v4(),
+                "destination": destination,
+                "day": expedition.remove("day"),
+                "population": expedition.remove("population"),
+                "soldiers": 0,
+                "status": expedition.remove("status"),
+            }]),
+        );
+    }
+}
+
 fn load_from_json(file_path: &str) -> io::Result<SaveAll> {
     let json_data = fs::read_to_string(file_path)?;
-    let data: SaveAll = serde_json::from_str(&json_data)?;
+    let mut value: Value = serde_json::from_str(&json_data)?;
+    migrate_save(&mut value);
+    let data: SaveAll = serde_json::from_value(value)?;
     Ok(data)
 }
 